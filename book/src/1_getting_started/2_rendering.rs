@@ -30,7 +30,9 @@ fn main() {
 
     render(&context, &surface, &window, &mut canvas);
 
-    loop {}
+    loop {
+        std::thread::park();
+    }
 }
 
 fn create_window(event_loop: &EventLoop<()>) -> (PossiblyCurrentContext, Display, Window, Surface<WindowSurface>) {
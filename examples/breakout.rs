@@ -207,11 +207,9 @@ impl Game {
                         self.balls[0].on_paddle = true;
                     }
                     State::Paused => self.state = State::InGame,
-                    State::InGame => {
-                        if self.balls[0].on_paddle {
-                            self.balls[0].velocity = Vector::new(100.0, -350.0);
-                            self.balls[0].on_paddle = false;
-                        }
+                    State::InGame if self.balls[0].on_paddle => {
+                        self.balls[0].velocity = Vector::new(100.0, -350.0);
+                        self.balls[0].on_paddle = false;
                     }
                     _ => (),
                 },
@@ -220,18 +218,16 @@ impl Game {
             Event::DeviceEvent {
                 event: DeviceEvent::MouseMotion { delta },
                 ..
-            } => {
-                if self.state == State::InGame {
-                    // Move the paddle
-                    self.paddle_rect.origin.x += delta.0 as f32;
-
-                    // Clamp it to the window
-                    self.paddle_rect.origin.y = self.size.height - self.paddle_rect.size.height - 10.0;
-                    self.paddle_rect.origin = self.paddle_rect.origin.clamp(
-                        Point::new(0.0, self.paddle_rect.origin.y),
-                        Point::new(self.size.width - self.paddle_rect.size.width, self.paddle_rect.origin.y),
-                    );
-                }
+            } if self.state == State::InGame => {
+                // Move the paddle
+                self.paddle_rect.origin.x += delta.0 as f32;
+
+                // Clamp it to the window
+                self.paddle_rect.origin.y = self.size.height - self.paddle_rect.size.height - 10.0;
+                self.paddle_rect.origin = self.paddle_rect.origin.clamp(
+                    Point::new(0.0, self.paddle_rect.origin.y),
+                    Point::new(self.size.width - self.paddle_rect.size.width, self.paddle_rect.origin.y),
+                );
             }
             _ => (),
         }
@@ -490,7 +486,7 @@ impl Game {
                 let strength = 4.0;
                 let old_velocity = ball.velocity;
                 ball.velocity.x = 100.0 * percentage * strength;
-                ball.velocity.y = -1.0 * ball.velocity.y.abs();
+                ball.velocity.y = -ball.velocity.y.abs();
                 ball.velocity = ball.velocity.normalize() * old_velocity.length();
             }
         }
@@ -1275,7 +1271,7 @@ fn run<W: WindowSurface>(mut canvas: Canvas<W::Renderer>, el: EventLoop<()>, mut
                     game.size = Size::new(physical_size.width as f32, physical_size.height as f32);
                 }
                 WindowEvent::CloseRequested => event_loop_window_target.exit(),
-                WindowEvent::RedrawRequested { .. } => {
+                WindowEvent::RedrawRequested => {
                     let dpi_factor = window.scale_factor();
                     let size = window.inner_size();
                     canvas.set_size(size.width, size.height, dpi_factor as f32);
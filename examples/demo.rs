@@ -158,7 +158,7 @@ fn run<W: WindowSurface>(mut canvas: Canvas<W::Renderer>, el: EventLoop<()>, mut
                         screenshot_image_id = Some(canvas.create_image(image.as_ref(), ImageFlags::empty()).unwrap());
                     }
                 }
-                WindowEvent::RedrawRequested { .. } => {
+                WindowEvent::RedrawRequested => {
                     let now = Instant::now();
                     let dt = (now - prevt).as_secs_f32();
                     prevt = now;
@@ -304,6 +304,7 @@ fn run<W: WindowSurface>(mut canvas: Canvas<W::Renderer>, el: EventLoop<()>, mut
     .unwrap();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_paragraph<T: Renderer>(
     canvas: &mut Canvas<T>,
     font: FontId,
@@ -442,6 +443,7 @@ fn draw_paragraph<T: Renderer>(
     canvas.restore();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_eyes<T: Renderer>(canvas: &mut Canvas<T>, x: f32, y: f32, w: f32, h: f32, mx: f32, my: f32, t: f32) {
     let ex = w * 0.23;
     let ey = h * 0.5;
@@ -896,6 +898,7 @@ fn draw_edit_box<T: Renderer>(canvas: &mut Canvas<T>, fonts: &Fonts, title: &str
     let _ = canvas.fill_text(x + h * 0.5, y + h * 0.5, title, &text_paint);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_edit_box_num<T: Renderer>(
     canvas: &mut Canvas<T>,
     fonts: &Fonts,
@@ -953,6 +956,7 @@ fn draw_check_box<T: Renderer>(canvas: &mut Canvas<T>, fonts: &Fonts, text: &str
     let _ = canvas.fill_text(x + 9.0 + 2.0, y + h * 0.5, "\u{2713}", &paint);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_button<T: Renderer>(
     canvas: &mut Canvas<T>,
     fonts: &Fonts,
@@ -211,7 +211,7 @@ fn run<W: WindowSurface>(mut canvas: Canvas<W::Renderer>, el: EventLoop<()>, mut
                     surface.resize(physical_size.width, physical_size.height);
                 }
                 WindowEvent::CloseRequested => event_loop_window_target.exit(),
-                WindowEvent::RedrawRequested { .. } => {
+                WindowEvent::RedrawRequested => {
                     let dpi_factor = window.scale_factor() as f32;
                     let size = window.inner_size();
                     canvas.set_size(size.width, size.height, 1.0);
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use femtovg::{Align, Baseline, Canvas, Color, FontId, ImageFlags, ImageId, Paint, Path, Renderer};
+use femtovg::{Align, Baseline, Canvas, Color, FontId, ImageFlags, ImageId, LineHeight, Paint, Path, Renderer};
 use instant::Instant;
 use resource::resource;
 use winit::{
@@ -134,7 +134,7 @@ fn run<W: WindowSurface>(mut canvas: Canvas<W::Renderer>, el: EventLoop<()>, mut
                     font_size += *y / 2.0;
                     font_size = font_size.max(2.0);
                 }
-                WindowEvent::RedrawRequested { .. } => {
+                WindowEvent::RedrawRequested => {
                     let dpi_factor = window.scale_factor();
                     let size = window.inner_size();
                     canvas.set_size(size.width, size.height, dpi_factor as f32);
@@ -272,36 +272,13 @@ fn draw_paragraph<T: Renderer>(canvas: &mut Canvas<T>, fonts: &Fonts, x: f32, y:
         //.with_text_align(Align::Right)
         .with_font_size(font_size);
 
-    let font_metrics = canvas.measure_font(&paint).expect("Error measuring font");
-
     let width = canvas.width() as f32;
-    let mut y = y;
 
-    let lines = canvas
-        .break_text_vec(width, text, &paint)
+    let paragraph = canvas
+        .layout_paragraph(text, &paint, width, LineHeight::Normal)
         .expect("Error while breaking text");
 
-    for line_range in lines {
-        if let Ok(_res) = canvas.fill_text(x, y, &text[line_range], &paint) {
-            y += font_metrics.height();
-        }
-    }
-
-    // let mut start = 0;
-
-    // while start < text.len() {
-    //     let substr = &text[start..];
-
-    //     if let Ok(index) = canvas.break_text(width, substr, &paint) {
-    //         if let Ok(res) = canvas.fill_text(x, y, &substr[0..index], &paint) {
-    //             y += res.height;
-    //         }
-
-    //         start += &substr[0..index].len();
-    //     } else {
-    //         break;
-    //     }
-    // }
+    let _ = canvas.fill_paragraph(x, y, &paragraph, &paint);
 }
 
 fn draw_inc_size<T: Renderer>(canvas: &mut Canvas<T>, fonts: &Fonts, x: f32, y: f32) {
@@ -42,6 +42,8 @@ pub enum ErrorKind {
     UnsupportedImageFormat,
     /// The requested operation is not supported (for example screenshot by wgpu renderer).
     UnsupportedOperation,
+    /// An error occurred while parsing SVG path data, at the given byte offset.
+    SvgPathDataParseError(usize),
 }
 
 impl Display for ErrorKind {
@@ -436,6 +436,7 @@ impl MulAssign for Transform2D {
 impl Div for Transform2D {
     type Output = Self;
 
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn div(self, other: Self) -> Self::Output {
         self * other.inverse()
     }
@@ -497,11 +498,16 @@ impl Rect {
     }
 }
 
+/// An axis-aligned bounding box.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub struct Bounds {
+    /// The left edge.
     pub minx: f32,
+    /// The top edge.
     pub miny: f32,
+    /// The right edge.
     pub maxx: f32,
+    /// The bottom edge.
     pub maxy: f32,
 }
 
@@ -517,6 +523,23 @@ impl Default for Bounds {
 }
 
 impl Bounds {
+    /// Inverted bounds to start from, which become the bounds of the first point included.
+    pub(crate) const EMPTY: Self = Self {
+        minx: f32::INFINITY,
+        miny: f32::INFINITY,
+        maxx: f32::NEG_INFINITY,
+        maxy: f32::NEG_INFINITY,
+    };
+
+    pub(crate) fn including(self, pos: Position) -> Self {
+        Self {
+            minx: self.minx.min(pos.x),
+            miny: self.miny.min(pos.y),
+            maxx: self.maxx.max(pos.x),
+            maxy: self.maxy.max(pos.y),
+        }
+    }
+
     pub(crate) fn contains(&self, x: f32, y: f32) -> bool {
         (self.minx..=self.maxx).contains(&x) && (self.miny..=self.maxy).contains(&y)
     }
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use crate::{
     image::ImageStore,
-    paint::{GradientStop, MultiStopGradient},
+    paint::{GradientColorSpace, GradientStop, MultiStopGradient},
     Color, ErrorKind, ImageFlags, ImageId, ImageInfo, ImageSource, Renderer,
 };
 
@@ -68,7 +68,14 @@ impl GradientStore {
 
 #[allow(clippy::many_single_char_names)]
 // Gradient filling, adapted from https://github.com/lieff/lvg/blob/master/render/common.c#L147
-fn gradient_span(dest: &mut [rgb::RGBA8; 256], color0: Color, color1: Color, offset0: f32, offset1: f32) {
+fn gradient_span(
+    dest: &mut [rgb::RGBA8; 256],
+    color_space: GradientColorSpace,
+    color0: Color,
+    color1: Color,
+    offset0: f32,
+    offset1: f32,
+) {
     let s0o = offset0.clamp(0.0, 1.0);
     let s1o = offset1.clamp(0.0, 1.0);
 
@@ -79,20 +86,21 @@ fn gradient_span(dest: &mut [rgb::RGBA8; 256], color0: Color, color1: Color, off
     let s = (s0o * 256.0) as usize;
     let e = (s1o * 256.0) as usize;
 
-    let mut r = color0.r;
-    let mut g = color0.g;
-    let mut b = color0.b;
-    let mut a = color0.a;
+    let c0 = color_space.encode([color0.r, color0.g, color0.b]);
+    let c1 = color_space.encode([color1.r, color1.g, color1.b]);
 
     let steps = (e - s) as f32;
 
-    let dr = (color1.r - r) / steps;
-    let dg = (color1.g - g) / steps;
-    let db = (color1.b - b) / steps;
-    let da = (color1.a - a) / steps;
-
     #[allow(clippy::needless_range_loop)]
     for i in s..e {
+        let t = (i - s) as f32 / steps;
+        let [r, g, b] = color_space.decode([
+            c0[0] + (c1[0] - c0[0]) * t,
+            c0[1] + (c1[1] - c0[1]) * t,
+            c0[2] + (c1[2] - c0[2]) * t,
+        ]);
+        let a = color0.a + (color1.a - color0.a) * t;
+
         // The output must be premultiplied, but we don't premultiply until this point
         // so that we can do gradients from transparent colors correctly -- for example
         // if we have a stop that is fully transparent red and it transitions to opaque
@@ -105,21 +113,19 @@ fn gradient_span(dest: &mut [rgb::RGBA8; 256], color0: Color, color1: Color, off
             (b * a * 255.0) as u8,
             (a * 255.0) as u8,
         );
-        r += dr;
-        g += dg;
-        b += db;
-        a += da;
     }
 }
+
 fn linear_gradient_stops(gradient: &MultiStopGradient) -> imgref::Img<Vec<rgb::RGBA8>> {
     let mut dest = [rgb::RGBA8::new(0, 0, 0, 0); 256];
+    let color_space = gradient.color_space();
 
     // Fill the gradient up to the first stop.
     let first_stop = gradient.get(0);
     if first_stop.0 > 0.0 {
         let s0 = first_stop.0;
         let color0 = first_stop.1;
-        gradient_span(&mut dest, color0, color0, 0.0, s0);
+        gradient_span(&mut dest, color_space, color0, color0, 0.0, s0);
     }
 
     // Iterate over the stops in overlapping pairs and fill out the rest of the
@@ -130,9 +136,9 @@ fn linear_gradient_stops(gradient: &MultiStopGradient) -> imgref::Img<Vec<rgb::R
         // Catch the case where the last stop doesn't go all the way to 1.0 and
         // pad it.
         if s0 < 1.0 && s1 > 1.0 {
-            gradient_span(&mut dest, color0, color0, s0, 1.0);
+            gradient_span(&mut dest, color_space, color0, color0, s0, 1.0);
         } else {
-            gradient_span(&mut dest, color0, color1, s0, s1);
+            gradient_span(&mut dest, color_space, color0, color1, s0, s1);
         }
 
         // If the first stop is >1.0 then we're done.
@@ -0,0 +1,56 @@
+use crate::{image::ImageStore, ErrorKind, ImageFlags, ImageId, ImageInfo, PixelFormat, Renderer};
+
+/// `LayerPool` holds the images that layers are drawn into, so that they can be reused by
+/// later layers and frames. The actual images/textures are contained by the Canvas's `ImageStore`.
+pub struct LayerPool {
+    // Images that no layer is drawing to, and whether they were used in the current frame.
+    available: Vec<(ImageId, bool)>,
+}
+
+impl LayerPool {
+    /// Create a new empty layer pool
+    pub fn new() -> Self {
+        Self { available: Vec::new() }
+    }
+
+    /// Take an image with the given size out of the pool, allocating a new one if there is none.
+    pub fn acquire<R: Renderer>(
+        &mut self,
+        width: usize,
+        height: usize,
+        images: &mut ImageStore<R::Image>,
+        renderer: &mut R,
+    ) -> Result<ImageId, ErrorKind> {
+        let position = self.available.iter().position(|(image_id, _)| {
+            images
+                .info(*image_id)
+                .is_some_and(|info| info.width() == width && info.height() == height)
+        });
+
+        if let Some(position) = position {
+            let (image_id, _) = self.available.swap_remove(position);
+            return Ok(image_id);
+        }
+
+        // Layers are render targets, which are stored upside down, and are drawn to with premultiplied alpha.
+        let flags = ImageFlags::FLIP_Y | ImageFlags::PREMULTIPLIED;
+        images.alloc(renderer, ImageInfo::new(flags, width, height, PixelFormat::Rgba8))
+    }
+
+    /// Return an image to the pool once its layer has been composited.
+    pub fn release(&mut self, image_id: ImageId) {
+        self.available.push((image_id, true));
+    }
+
+    /// Release the images that were not used in the most recently rendered frame. This
+    /// method should be called when all the commands have been submitted.
+    pub fn release_unused<R: Renderer>(&mut self, images: &mut ImageStore<R::Image>, renderer: &mut R) {
+        self.available.retain_mut(|(image_id, used)| {
+            if !*used {
+                images.remove(renderer, *image_id);
+            }
+
+            std::mem::replace(used, false)
+        });
+    }
+}
@@ -30,8 +30,8 @@ mod error;
 pub use error::ErrorKind;
 
 pub use text::{
-    Align, Atlas, Baseline, DrawCommand, FontId, FontMetrics, GlyphDrawCommands, Quad, RenderMode, TextContext,
-    TextMetrics,
+    Affinity, Align, Atlas, AttributedText, Baseline, DrawCommand, FontId, FontMetrics, GlyphDrawCommands, LineHeight,
+    Paragraph, ParagraphLine, Quad, RenderMode, TextContext, TextDecoration, TextMetrics,
 };
 
 use text::{GlyphAtlas, TextContextImpl};
@@ -49,20 +49,23 @@ pub use renderer::{RenderTarget, Renderer};
 use renderer::{Command, CommandType, Drawable, Params, ShaderType, SurfacelessRenderer, Vertex};
 
 pub(crate) mod geometry;
-pub use geometry::Transform2D;
 use geometry::*;
+pub use geometry::{Bounds, Transform2D};
 
 mod paint;
-pub use paint::Paint;
 use paint::{GlyphTexture, PaintFlavor, StrokeSettings};
+pub use paint::{GradientColorSpace, GradientSpread, Paint};
 
 mod path;
-use path::Convexity;
-pub use path::{Path, PathIter, Solidity, Verb};
+pub use path::{BooleanOp, Path, PathIter, PathMeasure, Solidity, Verb};
+use path::{Convexity, PathCache};
 
 mod gradient_store;
 use gradient_store::GradientStore;
 
+mod layer_pool;
+use layer_pool::LayerPool;
+
 /// Determines the fill rule used when filling paths.
 ///
 /// The fill rule defines how the interior of a shape is determined.
@@ -111,7 +114,8 @@ pub enum BlendFactor {
 pub enum CompositeOperation {
     /// Displays the source over the destination.
     SourceOver,
-    /// Displays the source in the destination, i.e. only the part of the source inside the destination is shown and the destination is transparent.
+    /// Displays the source in the destination, i.e. only the part of the source inside the destination is shown
+    /// and the destination is transparent.
     SourceIn,
     /// Only displays the part of the source that is outside the destination, which is made transparent.
     SourceOut,
@@ -123,16 +127,60 @@ pub enum CompositeOperation {
     DestinationIn,
     /// Only displays the part of the destination that is outside the source, which is made transparent.
     DestinationOut,
-    /// Displays the destination on top of the source. The part of the destination that is outside the source is not shown.
+    /// Displays the destination on top of the source. The part of the destination that is outside the source is
+    /// not shown.
     DestinationAtop,
     /// Displays the source together with the destination, the overlapping area is rendered lighter.
     Lighter,
     /// Ignores the destination and just displays the source.
     Copy,
-    /// Only the areas that exclusively belong either to the destination or the source are displayed. Overlapping parts are ignored.
+    /// Only the areas that exclusively belong either to the destination or the source are displayed. Overlapping
+    /// parts are ignored.
     Xor,
 }
 
+/// Blend modes as defined by the W3C Compositing and Blending specification.
+///
+/// A blend mode determines how the colors of the source are mixed with the colors of the destination
+/// before the result is composited according to the composite operation.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Hash)]
+pub enum BlendMode {
+    /// The source color replaces the destination color (default).
+    #[default]
+    Normal,
+    /// Multiplies the source and destination colors. The result is always at least as dark as either color.
+    Multiply,
+    /// Multiplies the complements of the source and destination colors. The result is always at least as light
+    /// as either color.
+    Screen,
+    /// Multiplies or screens the colors, depending on the destination color.
+    Overlay,
+    /// Selects the darker of the source and destination colors.
+    Darken,
+    /// Selects the lighter of the source and destination colors.
+    Lighten,
+    /// Brightens the destination color to reflect the source color.
+    ColorDodge,
+    /// Darkens the destination color to reflect the source color.
+    ColorBurn,
+    /// Multiplies or screens the colors, depending on the source color.
+    HardLight,
+    /// Darkens or lightens the colors, depending on the source color.
+    SoftLight,
+    /// Subtracts the darker of the two colors from the lighter color.
+    Difference,
+    /// Like `Difference`, but with lower contrast.
+    Exclusion,
+    /// Uses the hue of the source color with the saturation and luminosity of the destination color.
+    Hue,
+    /// Uses the saturation of the source color with the hue and luminosity of the destination color.
+    Saturation,
+    /// Uses the hue and saturation of the source color with the luminosity of the destination color.
+    Color,
+    /// Uses the luminosity of the source color with the hue and saturation of the destination color.
+    Luminosity,
+}
+
 /// Determines how a new ("source") data is displayed against an existing ("destination") data.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Hash)]
 pub struct CompositeOperationState {
@@ -140,6 +188,7 @@ pub struct CompositeOperationState {
     src_alpha: BlendFactor,
     dst_rgb: BlendFactor,
     dst_alpha: BlendFactor,
+    blend_mode: BlendMode,
 }
 
 impl CompositeOperationState {
@@ -164,6 +213,7 @@ impl CompositeOperationState {
             src_alpha: sfactor,
             dst_rgb: dfactor,
             dst_alpha: dfactor,
+            blend_mode: BlendMode::Normal,
         }
     }
 
@@ -174,8 +224,19 @@ impl CompositeOperationState {
             src_alpha: src_factor,
             dst_rgb: dst_factor,
             dst_alpha: dst_factor,
+            blend_mode: BlendMode::Normal,
         }
     }
+
+    /// Returns a copy of this state that mixes source and destination colors with the given blend mode.
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Returns the blend mode used to mix source and destination colors.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
 }
 
 impl Default for CompositeOperationState {
@@ -257,11 +318,12 @@ pub enum LineJoin {
     Bevel,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 struct State {
     composite_operation: CompositeOperationState,
     transform: Transform2D,
     scissor: Scissor,
+    clip: Option<Rc<ClipPath>>,
     alpha: f32,
 }
 
@@ -271,11 +333,34 @@ impl Default for State {
             composite_operation: CompositeOperationState::default(),
             transform: Transform2D::identity(),
             scissor: Scissor::default(),
+            clip: None,
             alpha: 1.0,
         }
     }
 }
 
+/// An offscreen layer started with `Canvas::save_layer`.
+#[derive(Debug)]
+struct Layer {
+    image: ImageId,
+    // The length of the state stack while drawing into the layer.
+    depth: usize,
+    // The area of the render target that the layer is composited to.
+    rect: Rect,
+    alpha: f32,
+    composite_operation: CompositeOperationState,
+    parent_target: RenderTarget,
+}
+
+/// A path that drawing is clipped to, intersected with the clip it was added to.
+#[derive(Debug)]
+struct ClipPath {
+    // Fill triangles of the path, already transformed to render target coordinates.
+    fill_verts: Vec<Vertex>,
+    fill_rule: FillRule,
+    parent: Option<Rc<ClipPath>>,
+}
+
 /// Main 2D drawing context.
 pub struct Canvas<T: Renderer> {
     width: u32,
@@ -286,7 +371,10 @@ pub struct Canvas<T: Renderer> {
     // Glyph atlas used for direct rendering of color glyphs, dropped after flush()
     ephemeral_glyph_atlas: Option<Rc<GlyphAtlas>>,
     current_render_target: RenderTarget,
+    // The clip that the stencil buffer of the current render target has been set up for
+    applied_clip: Option<Rc<ClipPath>>,
     state_stack: Vec<State>,
+    layers: Vec<Layer>,
     commands: Vec<Command>,
     verts: Vec<Vertex>,
     images: ImageStore<T::Image>,
@@ -295,6 +383,7 @@ pub struct Canvas<T: Renderer> {
     tess_tol: f32,
     dist_tol: f32,
     gradients: GradientStore,
+    layer_pool: LayerPool,
 }
 
 impl<T> Canvas<T>
@@ -311,7 +400,9 @@ where
             glyph_atlas: Rc::default(),
             ephemeral_glyph_atlas: None,
             current_render_target: RenderTarget::Screen,
+            applied_clip: None,
             state_stack: Vec::new(),
+            layers: Vec::new(),
             commands: Vec::new(),
             verts: Vec::new(),
             images: ImageStore::new(),
@@ -320,6 +411,7 @@ where
             tess_tol: 0.25,
             dist_tol: 0.01,
             gradients: GradientStore::new(),
+            layer_pool: LayerPool::new(),
         };
 
         canvas.save();
@@ -339,7 +431,9 @@ where
             glyph_atlas: Rc::default(),
             ephemeral_glyph_atlas: None,
             current_render_target: RenderTarget::Screen,
+            applied_clip: None,
             state_stack: Vec::new(),
+            layers: Vec::new(),
             commands: Vec::new(),
             verts: Vec::new(),
             images: ImageStore::new(),
@@ -348,6 +442,7 @@ where
             tess_tol: 0.25,
             dist_tol: 0.01,
             gradients: GradientStore::new(),
+            layer_pool: LayerPool::new(),
         };
 
         canvas.save();
@@ -417,8 +512,10 @@ where
 
     /// Tells the renderer to execute all drawing commands and clears the current internal state
     ///
-    /// Call this at the end of each frame.
+    /// Call this at the end of each frame. Layers that were saved with `save_layer()` and not restored yet
+    /// are restored and composited first.
     pub fn flush_to_surface(&mut self, surface: &T::Surface) -> T::CommandBuffer {
+        self.restore_layers();
         let command_buffer = self.renderer.render(
             surface,
             &mut self.images,
@@ -426,8 +523,11 @@ where
             std::mem::take(&mut self.commands),
         );
         self.verts.clear();
+        // The stencil buffer may be cleared between frames.
+        self.applied_clip = None;
         self.gradients
             .release_old_gradients(&mut self.images, &mut self.renderer);
+        self.layer_pool.release_unused(&mut self.images, &mut self.renderer);
         if let Some(atlas) = self.ephemeral_glyph_atlas.take() {
             atlas.clear(self);
         }
@@ -445,17 +545,102 @@ where
     ///
     /// A matching `restore()` must be used to restore the state.
     pub fn save(&mut self) {
-        let state = self.state_stack.last().map_or_else(State::default, |state| *state);
+        let state = self.state_stack.last().map_or_else(State::default, State::clone);
 
         self.state_stack.push(state);
     }
 
+    /// Pushes and saves the current render state like `save()` and redirects all drawing into an
+    /// offscreen layer.
+    ///
+    /// When the matching `restore()` is called, the layer is composited onto the previous render target
+    /// with the provided alpha and composite operation, and the current blend mode. This way overlapping shapes
+    /// in the layer are blended as a single group. Drawing into the layer starts with a global alpha of 1.0, the
+    /// default composite operation and `BlendMode::Normal`.
+    ///
+    /// Only the area of `bounds`, which are transformed by the current transform, is cleared and composited
+    /// back, or the whole render target if no bounds are given. The layer image is always as large as the
+    /// render target though, so bounds save fill rate but not memory, and every nested layer takes another
+    /// image of that size.
+    pub fn save_layer(&mut self, bounds: Option<Bounds>, alpha: f32, composite_operation: CompositeOperation) {
+        let (width, height) = (self.width(), self.height());
+        let target_rect = Rect::new(0.0, 0.0, width as f32, height as f32);
+
+        let rect = bounds.map_or(target_rect, |bounds| {
+            let transform = self.state().transform;
+            let corners = [
+                transform.transform_point(bounds.minx, bounds.miny),
+                transform.transform_point(bounds.maxx, bounds.miny),
+                transform.transform_point(bounds.maxx, bounds.maxy),
+                transform.transform_point(bounds.minx, bounds.maxy),
+            ];
+
+            // Round out to whole pixels, so that the layer is copied back without filtering.
+            let minx = corners.iter().fold(f32::MAX, |min, corner| min.min(corner.0)).floor();
+            let miny = corners.iter().fold(f32::MAX, |min, corner| min.min(corner.1)).floor();
+            let maxx = corners.iter().fold(f32::MIN, |max, corner| max.max(corner.0)).ceil();
+            let maxy = corners.iter().fold(f32::MIN, |max, corner| max.max(corner.1)).ceil();
+
+            Rect::new(minx, miny, maxx - minx, maxy - miny).intersect(target_rect)
+        });
+
+        let composite_operation = CompositeOperationState::new(composite_operation)
+            .with_blend_mode(self.state().composite_operation.blend_mode);
+
+        self.save();
+
+        let image = self
+            .layer_pool
+            .acquire(width as usize, height as usize, &mut self.images, &mut self.renderer);
+
+        let state = self.state_mut();
+
+        let Ok(image) = image else {
+            // Without a layer, fall back to applying the alpha and composite operation to every shape.
+            state.alpha *= alpha;
+            state.composite_operation = composite_operation;
+            return;
+        };
+
+        state.alpha = 1.0;
+        state.composite_operation = CompositeOperationState::default();
+
+        self.layers.push(Layer {
+            image,
+            depth: self.state_stack.len(),
+            rect,
+            alpha,
+            composite_operation,
+            parent_target: self.current_render_target,
+        });
+
+        self.set_render_target(RenderTarget::Image(image));
+        self.clear_rect(
+            rect.x as u32,
+            rect.y as u32,
+            rect.w as u32,
+            rect.h as u32,
+            Color::rgba(0, 0, 0, 0),
+        );
+    }
+
     /// Restores the previous render state
     ///
-    /// Restoring the initial/first state will just reset it to the defaults
+    /// Restoring the initial/first state will just reset it to the defaults. If the state was saved with
+    /// `save_layer()`, the layer is composited onto the previous render target.
     pub fn restore(&mut self) {
         if self.state_stack.len() > 1 {
             self.state_stack.pop();
+
+            if self
+                .layers
+                .last()
+                .is_some_and(|layer| layer.depth > self.state_stack.len())
+            {
+                if let Some(layer) = self.layers.pop() {
+                    self.composite_layer(layer);
+                }
+            }
         } else {
             self.reset();
         }
@@ -487,8 +672,22 @@ where
     }
 
     /// Sets the composite operation.
+    ///
+    /// The current blend mode is kept.
     pub fn global_composite_operation(&mut self, op: CompositeOperation) {
-        self.state_mut().composite_operation = CompositeOperationState::new(op);
+        let blend_mode = self.state().composite_operation.blend_mode;
+        self.state_mut().composite_operation = CompositeOperationState::new(op).with_blend_mode(blend_mode);
+    }
+
+    /// Sets the blend mode used to mix the colors of subsequently drawn shapes with the colors already on the
+    /// render target.
+    ///
+    /// Blending happens before the result is composited according to the composite operation. The OpenGL
+    /// and software renderers support all blend modes, the wgpu renderer falls back to `BlendMode::Normal`.
+    /// So does the OpenGL renderer on OpenGL ES 2 and WebGL 1 when drawing to a multisampled screen, which
+    /// can't be read back.
+    pub fn global_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.state_mut().composite_operation.blend_mode = blend_mode;
     }
 
     /// Sets the composite operation with custom pixel arithmetic.
@@ -504,11 +703,13 @@ where
         src_alpha: BlendFactor,
         dst_alpha: BlendFactor,
     ) {
+        let blend_mode = self.state().composite_operation.blend_mode;
         self.state_mut().composite_operation = CompositeOperationState {
             src_rgb,
             src_alpha,
             dst_rgb,
             dst_alpha,
+            blend_mode,
         }
     }
 
@@ -520,10 +721,120 @@ where
         }
     }
 
-    fn append_cmd(&mut self, cmd: Command) {
+    fn append_cmd(&mut self, mut cmd: Command) {
+        match cmd.cmd_type {
+            CommandType::ConvexFill { .. }
+            | CommandType::ConcaveFill { .. }
+            | CommandType::Stroke { .. }
+            | CommandType::StencilStroke { .. }
+            | CommandType::Triangles { .. } => {
+                if self.state().clip.is_some() {
+                    self.apply_clip();
+                    cmd.clip = true;
+                }
+            }
+            // These may switch the render target or overwrite its stencil buffer.
+            CommandType::SetRenderTarget(_)
+            | CommandType::ClearRect { .. }
+            | CommandType::RenderFilteredImage { .. } => {
+                self.applied_clip = None;
+            }
+            CommandType::ResetClip | CommandType::IntersectClip => {}
+        }
+
         self.commands.push(cmd);
     }
 
+    // Restores the states of all open layers, so that they are composited and their images returned to the pool.
+    fn restore_layers(&mut self) {
+        while !self.layers.is_empty() {
+            self.restore();
+        }
+    }
+
+    fn composite_layer(&mut self, layer: Layer) {
+        self.set_render_target(layer.parent_target);
+
+        if let (false, Ok((width, height))) = (layer.rect.is_empty(), self.image_size(layer.image)) {
+            self.save();
+
+            let state = self.state_mut();
+            state.transform = Transform2D::identity();
+            state.alpha = 1.0;
+            state.composite_operation = layer.composite_operation;
+
+            let mut path = Path::new();
+            path.rect(layer.rect.x, layer.rect.y, layer.rect.w, layer.rect.h);
+
+            let paint = Paint::image(layer.image, 0.0, 0.0, width as f32, height as f32, 0.0, layer.alpha)
+                .with_anti_alias(false);
+            self.fill_path(&path, &paint);
+
+            self.restore();
+        }
+
+        self.layer_pool.release(layer.image);
+    }
+
+    // Sets up the stencil buffer of the current render target for the clip of the current state.
+    fn apply_clip(&mut self) {
+        let Some(clip) = self.state().clip.clone() else {
+            return;
+        };
+
+        // Collect the clip paths that still need to be intersected, starting with the outermost one. If the
+        // applied clip is an ancestor of the current one, the paths added since can be intersected with it.
+        let mut pending = Vec::new();
+        let mut reset = true;
+        let mut node = Some(&clip);
+
+        while let Some(clip_path) = node {
+            if self
+                .applied_clip
+                .as_ref()
+                .is_some_and(|applied| Rc::ptr_eq(applied, clip_path))
+            {
+                reset = false;
+                break;
+            }
+
+            pending.push(clip_path.clone());
+            node = clip_path.parent.as_ref();
+        }
+
+        // A quad covering the whole render target, drawn as a triangle strip.
+        let (width, height) = (self.width() as f32, self.height() as f32);
+        let quad_offset = self.verts.len();
+        self.verts.push(Vertex::new(width, height, 0.5, 1.0));
+        self.verts.push(Vertex::new(width, 0.0, 0.5, 1.0));
+        self.verts.push(Vertex::new(0.0, height, 0.5, 1.0));
+        self.verts.push(Vertex::new(0.0, 0.0, 0.5, 1.0));
+
+        if reset {
+            let mut cmd = Command::new(CommandType::ResetClip);
+            cmd.triangles_verts = Some((quad_offset, 4));
+            self.commands.push(cmd);
+        }
+
+        for clip_path in pending.iter().rev() {
+            let mut cmd = Command::new(CommandType::IntersectClip);
+            cmd.fill_rule = clip_path.fill_rule;
+            cmd.triangles_verts = Some((quad_offset, 4));
+
+            if !clip_path.fill_verts.is_empty() {
+                cmd.drawables.push(Drawable {
+                    fill_verts: Some((self.verts.len(), clip_path.fill_verts.len())),
+                    stroke_verts: None,
+                });
+                self.verts.extend_from_slice(&clip_path.fill_verts);
+            }
+
+            self.commands.push(cmd);
+        }
+
+        self.applied_clip = Some(clip);
+    }
+
     // Images
 
     /// Allocates an empty image with the provided domensions and format.
@@ -804,6 +1115,41 @@ where
         self.state_mut().scissor = Scissor::default();
     }
 
+    // Clipping
+
+    /// Intersects the current clip with the provided path.
+    ///
+    /// The path is transformed by the current transform. Everything drawn afterwards, including text
+    /// and images, is only visible inside the clip until the state is restored.
+    pub fn clip_path(&mut self, path: &Path, fill_rule: FillRule) {
+        let transform = self.state().transform;
+
+        // The path cache saves a flattened and transformed version of the path.
+        let mut path_cache = path.cache(&transform, self.tess_tol, self.dist_tol);
+
+        // The clip is written to the stencil buffer, so there is no need for anti-aliased fringes.
+        path_cache.expand_fill(0.0, LineJoin::Miter, 2.4);
+
+        let fill_verts = path_cache
+            .contours
+            .iter()
+            .flat_map(|contour| contour.fill.iter().copied())
+            .collect();
+
+        let state = self.state_mut();
+
+        state.clip = Some(Rc::new(ClipPath {
+            fill_verts,
+            fill_rule,
+            parent: state.clip.take(),
+        }));
+    }
+
+    /// Removes the clip set with [`Self::clip_path`].
+    pub fn reset_clip(&mut self) {
+        self.state_mut().clip = None;
+    }
+
     // Paths
 
     /// Returns true if the specified point (x,y) is in the provided path, and false otherwise.
@@ -825,6 +1171,40 @@ where
         path_cache.contains_point(x, y, fill_rule)
     }
 
+    /// Returns true if the specified point (x,y) is covered by the stroke of the provided path, and false otherwise.
+    ///
+    /// The line width, caps, joins, miter limit and dash pattern of `paint` are taken into account, as well as
+    /// the current transform. Strokes thinner than a pixel are hit tested as if they were one pixel wide,
+    /// just like they are drawn.
+    pub fn contains_point_in_stroke(&self, path: &Path, paint: &Paint, x: f32, y: f32) -> bool {
+        let stroke = &paint.stroke;
+        let transform = self.state().transform;
+
+        // The path cache saves a flattened and transformed version of the path.
+        let mut path_cache = path.cache(&transform, self.tess_tol, self.dist_tol);
+
+        let mut dashed_cache = self.dashed_cache(&path_cache, stroke, &transform);
+
+        let path_cache = match &mut dashed_cache {
+            Some(dashed_cache) => dashed_cache,
+            None => &mut *path_cache,
+        };
+
+        let line_width = (stroke.line_width * transform.average_scale()).max(self.fringe_width);
+
+        path_cache.expand_stroke(
+            line_width * 0.5,
+            0.0,
+            stroke.line_cap_start,
+            stroke.line_cap_end,
+            stroke.line_join,
+            stroke.miter_limit,
+            self.tess_tol,
+        );
+
+        path_cache.stroke_contains_point(x, y)
+    }
+
     /// Return the bounding box for a Path
     pub fn path_bbox(&self, path: &Path) -> Bounds {
         let transform = self.state().transform;
@@ -931,7 +1311,7 @@ where
 
         if let PaintFlavor::Image { id, .. } = paint_flavor {
             cmd.image = Some(id);
-        } else if let Some(paint::GradientColors::MultiStop { stops }) = paint_flavor.gradient_colors() {
+        } else if let Some(paint::GradientColors::MultiStop { stops, .. }) = paint_flavor.gradient_colors() {
             cmd.image = self
                 .gradients
                 .lookup_or_add(stops, &mut self.images, &mut self.renderer)
@@ -1004,6 +1384,23 @@ where
         self.stroke_path_internal(path, &paint.flavor, paint.shape_anti_alias, &paint.stroke);
     }
 
+    // Splits the contours of the path cache into dashes if the stroke has a dash pattern. The pattern is scaled by
+    // the transform, just like the line width.
+    fn dashed_cache(
+        &self,
+        path_cache: &PathCache,
+        stroke: &StrokeSettings,
+        transform: &Transform2D,
+    ) -> Option<PathCache> {
+        if stroke.dash_array.is_empty() {
+            return None;
+        }
+
+        let scale = transform.average_scale();
+        let pattern: Vec<f32> = stroke.dash_array.iter().map(|len| len * scale).collect();
+        path_cache.dashed(&pattern, stroke.dash_offset * scale, self.dist_tol)
+    }
+
     fn stroke_path_internal(
         &mut self,
         path: &Path,
@@ -1026,6 +1423,13 @@ where
             return;
         }
 
+        let mut dashed_cache = self.dashed_cache(&path_cache, stroke, &transform);
+
+        let path_cache = match &mut dashed_cache {
+            Some(dashed_cache) => dashed_cache,
+            None => &mut *path_cache,
+        };
+
         let scissor = self.state().scissor;
 
         // Scale stroke width by current transform scale.
@@ -1098,7 +1502,7 @@ where
 
         if let PaintFlavor::Image { id, .. } = paint_flavor {
             cmd.image = Some(id);
-        } else if let Some(paint::GradientColors::MultiStop { stops }) = paint_flavor.gradient_colors() {
+        } else if let Some(paint::GradientColors::MultiStop { stops, .. }) = paint_flavor.gradient_colors() {
             cmd.image = self
                 .gradients
                 .lookup_or_add(stops, &mut self.images, &mut self.renderer)
@@ -1183,9 +1587,9 @@ where
         }
 
         cmd.triangles_verts = Some((self.verts.len(), verts.len()));
-        self.append_cmd(cmd);
-
         self.verts.extend_from_slice(&verts);
+
+        self.append_cmd(cmd);
     }
 
     // Text
@@ -1231,6 +1635,17 @@ where
             })
     }
 
+    /// Returns information on how the provided attributed text will be drawn.
+    pub fn measure_attributed_text(&self, x: f32, y: f32, text: &AttributedText) -> Result<TextMetrics, ErrorKind> {
+        let scale = self.font_scale() * self.device_px_ratio;
+
+        text.shape(x * scale, y * scale, &mut self.text_context.borrow_mut(), scale)
+            .map(|mut metrics| {
+                metrics.scale(1.0 / scale);
+                metrics
+            })
+    }
+
     /// Returns font metrics for a particular Paint.
     pub fn measure_font(&self, paint: &Paint) -> Result<FontMetrics, ErrorKind> {
         let scale = self.font_scale() * self.device_px_ratio;
@@ -1257,6 +1672,24 @@ where
             .break_text(max_width, text, &text_settings)
     }
 
+    /// Returns the byte length of the longest start of the text that fits inside `max_width` together with an
+    /// ellipsis ("…") after it, or the length of the whole text if it fits without one.
+    ///
+    /// The text is only cut between grapheme clusters, and whitespace before the ellipsis is left out.
+    pub fn truncate_text<S: AsRef<str>>(&self, max_width: f32, text: S, paint: &Paint) -> Result<usize, ErrorKind> {
+        let scale = self.font_scale() * self.device_px_ratio;
+
+        let mut text_settings = paint.text.clone();
+        text_settings.font_size *= scale;
+        text_settings.letter_spacing *= scale;
+
+        let max_width = max_width * scale;
+
+        self.text_context
+            .borrow_mut()
+            .truncate_text(max_width, text, &text_settings)
+    }
+
     /// Returnes a list of ranges representing each line of text that will fit inside `max_width`
     pub fn break_text_vec<S: AsRef<str>>(
         &self,
@@ -1288,6 +1721,22 @@ where
         self.draw_text(x, y, text.as_ref(), paint, RenderMode::Fill)
     }
 
+    /// Fills the provided string with the specified Paint, cut off with an ellipsis ("…") if it's wider than
+    /// `max_width`. See [`Self::truncate_text()`].
+    pub fn fill_text_truncated<S: AsRef<str>>(
+        &mut self,
+        x: f32,
+        y: f32,
+        text: S,
+        max_width: f32,
+        paint: &Paint,
+    ) -> Result<TextMetrics, ErrorKind> {
+        let text = text.as_ref();
+        let byte_index = self.truncate_text(max_width, text, paint)?;
+
+        self.draw_text(x, y, &text::ellipsize(text, byte_index), paint, RenderMode::Fill)
+    }
+
     /// Strokes the provided string with the specified Paint.
     pub fn stroke_text<S: AsRef<str>>(
         &mut self,
@@ -1299,6 +1748,45 @@ where
         self.draw_text(x, y, text.as_ref(), paint, RenderMode::Stroke)
     }
 
+    /// Fills the attributed text, each range with its own paint.
+    pub fn fill_attributed_text(&mut self, x: f32, y: f32, text: &AttributedText) -> Result<TextMetrics, ErrorKind> {
+        self.draw_attributed_text(x, y, text, RenderMode::Fill)
+    }
+
+    /// Strokes the attributed text, each range with its own paint.
+    pub fn stroke_attributed_text(&mut self, x: f32, y: f32, text: &AttributedText) -> Result<TextMetrics, ErrorKind> {
+        self.draw_attributed_text(x, y, text, RenderMode::Stroke)
+    }
+
+    /// Breaks the text into lines that fit inside `max_width` and positions them below each other, `line_height`
+    /// apart. Pass `f32::INFINITY` as `max_width` to only break lines at line feeds.
+    ///
+    /// The paragraph is measured with the sizes in the paint, like [`TextContext::layout_paragraph()`], so it
+    /// can be drawn with any transform.
+    pub fn layout_paragraph<S: AsRef<str>>(
+        &self,
+        text: S,
+        paint: &Paint,
+        max_width: f32,
+        line_height: LineHeight,
+    ) -> Result<Paragraph, ErrorKind> {
+        self.text_context
+            .borrow_mut()
+            .layout_paragraph(text, &paint.text, max_width, line_height)
+    }
+
+    /// Fills the lines of a paragraph with the specified Paint, with the top left corner of the paragraph at
+    /// `x` and `y`. The fonts and sizes are the ones the paragraph was laid out with.
+    pub fn fill_paragraph(&mut self, x: f32, y: f32, paragraph: &Paragraph, paint: &Paint) -> Result<(), ErrorKind> {
+        self.draw_paragraph(x, y, paragraph, paint, RenderMode::Fill)
+    }
+
+    /// Strokes the lines of a paragraph with the specified Paint, with the top left corner of the paragraph at
+    /// `x` and `y`. The fonts and sizes are the ones the paragraph was laid out with.
+    pub fn stroke_paragraph(&mut self, x: f32, y: f32, paragraph: &Paragraph, paint: &Paint) -> Result<(), ErrorKind> {
+        self.draw_paragraph(x, y, paragraph, paint, RenderMode::Stroke)
+    }
+
     /// Dispatch an explicit set of `GlyphDrawCommands` to the renderer. Use this only if you are
     /// using a custom font rasterizer/layout.
     pub fn draw_glyph_commands(&mut self, draw_commands: GlyphDrawCommands, paint: &Paint, scale: f32) {
@@ -1363,9 +1851,6 @@ where
         let scale = self.font_scale() * self.device_px_ratio;
         let invscale = 1.0 / scale;
 
-        let mut stroke = paint.stroke.clone();
-        stroke.line_width *= scale;
-
         let mut text_settings = paint.text.clone();
         text_settings.font_size *= scale;
         text_settings.letter_spacing *= scale;
@@ -1380,19 +1865,81 @@ where
         )?;
         //let layout = self.layout_text(x, y, text, &paint)?;
 
+        self.draw_glyphs(&layout, text_settings.font_size, paint, render_mode)?;
+
+        layout.scale(invscale);
+
+        Ok(layout)
+    }
+
+    fn draw_attributed_text(
+        &mut self,
+        x: f32,
+        y: f32,
+        text: &AttributedText,
+        render_mode: RenderMode,
+    ) -> Result<TextMetrics, ErrorKind> {
+        let scale = self.font_scale() * self.device_px_ratio;
+
+        let mut layout = text.shape(x * scale, y * scale, &mut self.text_context.borrow_mut(), scale)?;
+
+        for (range, paint) in text.spans() {
+            let mut span_layout = layout.clone();
+            span_layout.glyphs.retain(|glyph| range.contains(&glyph.byte_index));
+
+            self.draw_glyphs(&span_layout, paint.text.font_size * scale, paint, render_mode)?;
+        }
+
+        layout.scale(1.0 / scale);
+
+        Ok(layout)
+    }
+
+    fn draw_paragraph(
+        &mut self,
+        x: f32,
+        y: f32,
+        paragraph: &Paragraph,
+        paint: &Paint,
+        render_mode: RenderMode,
+    ) -> Result<(), ErrorKind> {
+        let scale = self.font_scale() * self.device_px_ratio;
+
+        for line in paragraph.lines() {
+            let layout = paragraph.line_layout(line, x, y, scale);
+            self.draw_glyphs(&layout, paragraph.font_size * scale, paint, render_mode)?;
+        }
+
+        Ok(())
+    }
+
+    // Draws glyphs that are laid out and sized for the current font scale.
+    fn draw_glyphs(
+        &mut self,
+        layout: &TextMetrics,
+        font_size: f32,
+        paint: &Paint,
+        render_mode: RenderMode,
+    ) -> Result<(), ErrorKind> {
+        let scale = self.font_scale() * self.device_px_ratio;
+        let invscale = 1.0 / scale;
+
+        let mut stroke = paint.stroke.clone();
+        stroke.line_width *= scale;
+
         // TODO: Early out if text is outside the canvas bounds, or maybe even check for each character in layout.
 
         let bitmap_glyphs = layout.has_bitmap_glyphs();
-        let need_direct_rendering = text_settings.font_size > 92.0;
+        let need_direct_rendering = font_size > 92.0;
 
         if need_direct_rendering && !bitmap_glyphs {
             text::render_direct(
                 self,
-                &layout,
+                layout,
                 &paint.flavor,
                 paint.shape_anti_alias,
                 &stroke,
-                text_settings.font_size,
+                font_size,
                 render_mode,
                 invscale,
             )?;
@@ -1403,14 +1950,25 @@ where
                 self.glyph_atlas.clone()
             };
 
-            let draw_commands =
-                atlas.render_atlas(self, &layout, text_settings.font_size, stroke.line_width, render_mode)?;
+            let draw_commands = atlas.render_atlas(self, layout, font_size, stroke.line_width, render_mode)?;
             self.draw_glyph_commands(draw_commands, paint, scale);
         }
 
-        layout.scale(invscale);
+        if !paint.text.text_decoration.is_empty() {
+            let mut text_settings = paint.text.clone();
+            text_settings.font_size = font_size;
+            text_settings.letter_spacing *= scale;
 
-        Ok(layout)
+            let mut path = text::decoration_path(&self.text_context.borrow(), layout, &text_settings)?;
+            path.transform(&Transform2D::scaling(invscale, invscale));
+
+            match render_mode {
+                RenderMode::Fill => self.fill_path(&path, &paint.clone().with_fill_rule(FillRule::NonZero)),
+                RenderMode::Stroke => self.stroke_path(&path, paint),
+            }
+        }
+
+        Ok(())
     }
 
     fn render_triangles(
@@ -1439,7 +1997,7 @@ where
 
         if let &PaintFlavor::Image { id, .. } = paint_flavor {
             cmd.image = Some(id);
-        } else if let Some(paint::GradientColors::MultiStop { stops }) = paint_flavor.gradient_colors() {
+        } else if let Some(paint::GradientColors::MultiStop { stops, .. }) = paint_flavor.gradient_colors() {
             cmd.image = self
                 .gradients
                 .lookup_or_add(stops, &mut self.images, &mut self.renderer)
@@ -1447,9 +2005,9 @@ where
         }
 
         cmd.triangles_verts = Some((self.verts.len(), verts.len()));
-        self.append_cmd(cmd);
-
         self.verts.extend_from_slice(verts);
+
+        self.append_cmd(cmd);
     }
 
     fn font_scale(&self) -> f32 {
@@ -1498,13 +2056,18 @@ where
 {
     /// Tells the renderer to execute all drawing commands and clears the current internal state
     ///
-    /// Call this at the end of each frame.
+    /// Call this at the end of each frame. Layers that were saved with `save_layer()` and not restored yet
+    /// are restored and composited first.
     pub fn flush(&mut self) {
+        self.restore_layers();
         self.renderer
             .render_surfaceless(&mut self.images, &self.verts, std::mem::take(&mut self.commands));
         self.verts.clear();
+        // The stencil buffer may be cleared between frames.
+        self.applied_clip = None;
         self.gradients
             .release_old_gradients(&mut self.images, &mut self.renderer);
+        self.layer_pool.release_unused(&mut self.images, &mut self.renderer);
         if let Some(atlas) = self.ephemeral_glyph_atlas.take() {
             atlas.clear(self);
         }
@@ -1635,3 +2198,39 @@ fn test_image_blit_fast_path() {
         })
     ));
 }
+
+#[test]
+fn test_layer_images_are_reused() {
+    use renderer::{Command, CommandType};
+
+    let renderer = RecordingRenderer::default();
+    let recorded_commands = renderer.last_commands.clone();
+    let mut canvas = Canvas::new(renderer).unwrap();
+    canvas.set_size(100, 100, 1.);
+
+    let mut layer_image = || {
+        canvas.save_layer(None, 0.5, CompositeOperation::SourceOver);
+        canvas.restore();
+        canvas.flush_to_surface(&());
+
+        recorded_commands
+            .borrow()
+            .iter()
+            .find_map(|command| match command {
+                Command {
+                    cmd_type: CommandType::SetRenderTarget(RenderTarget::Image(image)),
+                    ..
+                } => Some(*image),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let image = layer_image();
+    assert_eq!(layer_image(), image);
+
+    // Images that are not used for a frame are released.
+    assert!(canvas.image_info(image).is_ok());
+    canvas.flush_to_surface(&());
+    assert!(canvas.image_info(image).is_err());
+}
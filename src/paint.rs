@@ -3,7 +3,11 @@
 
 use std::rc::Rc;
 
-use crate::{geometry::Position, Align, Baseline, Color, FillRule, FontId, ImageId, LineCap, LineJoin};
+use crate::{
+    color::{linear_srgb_to_oklab, linear_to_srgb, oklab_to_linear_srgb, srgb_to_linear},
+    geometry::Position,
+    Align, Baseline, Color, FillRule, FontId, ImageId, LineCap, LineJoin, TextDecoration,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -35,6 +39,7 @@ impl PartialOrd for GradientStop {
 pub struct MultiStopGradient {
     shared_stops: Rc<[GradientStop]>,
     tint: f32,
+    color_space: GradientColorSpace,
 }
 
 impl MultiStopGradient {
@@ -49,6 +54,10 @@ impl MultiStopGradient {
         stop
     }
 
+    pub(crate) fn color_space(&self) -> GradientColorSpace {
+        self.color_space
+    }
+
     pub(crate) fn pairs(&self) -> impl Iterator<Item = [GradientStop; 2]> + '_ {
         self.shared_stops.as_ref().windows(2).map(move |pair| {
             let mut stops = [pair[0], pair[1]];
@@ -69,9 +78,11 @@ impl PartialOrd for MultiStopGradient {
 
 impl Ord for MultiStopGradient {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if (&other.shared_stops, other.tint) < (&self.shared_stops, self.tint) {
+        if (&other.shared_stops, other.tint, other.color_space) < (&self.shared_stops, self.tint, self.color_space) {
             std::cmp::Ordering::Less
-        } else if (&self.shared_stops, self.tint) < (&other.shared_stops, other.tint) {
+        } else if (&self.shared_stops, self.tint, self.color_space)
+            < (&other.shared_stops, other.tint, other.color_space)
+        {
             std::cmp::Ordering::Greater
         } else {
             std::cmp::Ordering::Equal
@@ -79,17 +90,108 @@ impl Ord for MultiStopGradient {
     }
 }
 
+/// Determines how a gradient continues beyond its start and end.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GradientSpread {
+    /// The colors at the ends extend outwards (default).
+    #[default]
+    Pad,
+    /// The gradient repeats.
+    Repeat,
+    /// The gradient repeats, with every other repetition mirrored.
+    Reflect,
+}
+
+/// The color space in which the colors of a gradient are interpolated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GradientColorSpace {
+    /// Interpolates the gamma encoded sRGB components (default).
+    #[default]
+    Srgb,
+    /// Interpolates the linear light sRGB components. This avoids dark midpoints between saturated colors.
+    LinearSrgb,
+    /// Interpolates in the perceptually uniform OKLab color space.
+    Oklab,
+}
+
+impl GradientColorSpace {
+    /// Converts gamma encoded sRGB components to this color space.
+    pub(crate) fn encode(self, rgb: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Srgb => rgb,
+            Self::LinearSrgb => rgb.map(srgb_to_linear),
+            Self::Oklab => linear_srgb_to_oklab(rgb.map(srgb_to_linear)),
+        }
+    }
+
+    /// Converts components in this color space to gamma encoded sRGB.
+    pub(crate) fn decode(self, color: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Srgb => color,
+            Self::LinearSrgb => color.map(|c| linear_to_srgb(c.clamp(0.0, 1.0))),
+            Self::Oklab => oklab_to_linear_srgb(color).map(|c| linear_to_srgb(c.clamp(0.0, 1.0))),
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GradientColors {
-    TwoStop { start_color: Color, end_color: Color },
-    MultiStop { stops: MultiStopGradient },
+    TwoStop {
+        start_color: Color,
+        end_color: Color,
+        spread: GradientSpread,
+        color_space: GradientColorSpace,
+    },
+    MultiStop {
+        stops: MultiStopGradient,
+        spread: GradientSpread,
+    },
 }
 impl GradientColors {
+    fn two_stop(start_color: Color, end_color: Color) -> Self {
+        Self::TwoStop {
+            start_color,
+            end_color,
+            spread: GradientSpread::default(),
+            color_space: GradientColorSpace::default(),
+        }
+    }
+
+    pub(crate) fn spread(&self) -> GradientSpread {
+        match self {
+            Self::TwoStop { spread, .. } | Self::MultiStop { spread, .. } => *spread,
+        }
+    }
+
+    fn set_spread(&mut self, value: GradientSpread) {
+        match self {
+            Self::TwoStop { spread, .. } | Self::MultiStop { spread, .. } => *spread = value,
+        }
+    }
+
+    pub(crate) fn color_space(&self) -> GradientColorSpace {
+        match self {
+            Self::TwoStop { color_space, .. } => *color_space,
+            Self::MultiStop { stops, .. } => stops.color_space,
+        }
+    }
+
+    fn set_color_space(&mut self, value: GradientColorSpace) {
+        match self {
+            Self::TwoStop { color_space, .. } => *color_space = value,
+            Self::MultiStop { stops, .. } => stops.color_space = value,
+        }
+    }
+
     fn mul_alpha(&mut self, a: f32) {
         match self {
-            Self::TwoStop { start_color, end_color } => {
+            Self::TwoStop {
+                start_color, end_color, ..
+            } => {
                 start_color.a *= a;
                 end_color.a *= a;
             }
@@ -105,17 +207,11 @@ impl GradientColors {
         let mut stops = stops.into_iter();
         let Some(first_stop) = stops.next() else {
             // No stops, we use black.
-            return Self::TwoStop {
-                start_color: Color::black(),
-                end_color: Color::black(),
-            };
+            return Self::two_stop(Color::black(), Color::black());
         };
         let Some(second_stop) = stops.next() else {
             // One stop devolves to a solid color fill (but using the gradient shader variation).
-            return Self::TwoStop {
-                start_color: first_stop.1,
-                end_color: first_stop.1,
-            };
+            return Self::two_stop(first_stop.1, first_stop.1);
         };
 
         let maybe_third_stop = stops.next();
@@ -123,10 +219,7 @@ impl GradientColors {
         if maybe_third_stop.is_none() && first_stop.0 <= 0.0 && second_stop.0 >= 1.0 {
             // Two stops takes the classic gradient path, so long as the stop positions are at
             // the extents (if the stop positions are inset then we'll fill to them).
-            return Self::TwoStop {
-                start_color: first_stop.1,
-                end_color: second_stop.1,
-            };
+            return Self::two_stop(first_stop.1, second_stop.1);
         }
 
         // Actual multistop gradient. We copy out the stops and then use a stop with a
@@ -142,7 +235,9 @@ impl GradientColors {
             stops: MultiStopGradient {
                 shared_stops: out_stops,
                 tint: 1.0,
+                color_space: GradientColorSpace::default(),
             },
+            spread: GradientSpread::default(),
         }
     }
 }
@@ -179,6 +274,18 @@ pub enum PaintFlavor {
         out_radius: f32,
         colors: GradientColors,
     },
+    ConicGradient {
+        center: Position,
+        start_angle: f32,
+        colors: GradientColors,
+    },
+    FocalGradient {
+        focal: Position,
+        focal_radius: f32,
+        center: Position,
+        radius: f32,
+        colors: GradientColors,
+    },
 }
 
 // Convenience method to fetch the GradientColors out of a PaintFlavor
@@ -200,6 +307,12 @@ impl PaintFlavor {
             Self::RadialGradient { colors, .. } => {
                 colors.mul_alpha(a);
             }
+            Self::ConicGradient { colors, .. } => {
+                colors.mul_alpha(a);
+            }
+            Self::FocalGradient { colors, .. } => {
+                colors.mul_alpha(a);
+            }
         }
     }
 
@@ -208,6 +321,19 @@ impl PaintFlavor {
             Self::LinearGradient { colors, .. } => Some(colors),
             Self::BoxGradient { colors, .. } => Some(colors),
             Self::RadialGradient { colors, .. } => Some(colors),
+            Self::ConicGradient { colors, .. } => Some(colors),
+            Self::FocalGradient { colors, .. } => Some(colors),
+            _ => None,
+        }
+    }
+
+    fn gradient_colors_mut(&mut self) -> Option<&mut GradientColors> {
+        match self {
+            Self::LinearGradient { colors, .. } => Some(colors),
+            Self::BoxGradient { colors, .. } => Some(colors),
+            Self::RadialGradient { colors, .. } => Some(colors),
+            Self::ConicGradient { colors, .. } => Some(colors),
+            Self::FocalGradient { colors, .. } => Some(colors),
             _ => None,
         }
     }
@@ -244,6 +370,8 @@ pub struct StrokeSettings {
     pub(crate) line_cap_start: LineCap,
     pub(crate) line_cap_end: LineCap,
     pub(crate) line_join: LineJoin,
+    pub(crate) dash_array: Vec<f32>,
+    pub(crate) dash_offset: f32,
 }
 
 impl Default for StrokeSettings {
@@ -255,6 +383,8 @@ impl Default for StrokeSettings {
             line_cap_start: LineCap::default(),
             line_cap_end: LineCap::default(),
             line_join: LineJoin::default(),
+            dash_array: Vec::new(),
+            dash_offset: 0.0,
         }
     }
 }
@@ -268,6 +398,8 @@ pub struct TextSettings {
     pub(crate) letter_spacing: f32,
     pub(crate) text_baseline: Baseline,
     pub(crate) text_align: Align,
+    pub(crate) text_decoration: TextDecoration,
+    pub(crate) text_decoration_skip_ink: bool,
 }
 
 impl Default for TextSettings {
@@ -278,6 +410,8 @@ impl Default for TextSettings {
             letter_spacing: 0.0,
             text_baseline: Baseline::default(),
             text_align: Align::default(),
+            text_decoration: TextDecoration::default(),
+            text_decoration_skip_ink: true,
         }
     }
 }
@@ -412,7 +546,7 @@ impl Paint {
         Self::with_flavor(PaintFlavor::LinearGradient {
             start: Position { x: start_x, y: start_y },
             end: Position { x: end_x, y: end_y },
-            colors: GradientColors::TwoStop { start_color, end_color },
+            colors: GradientColors::two_stop(start_color, end_color),
         })
     }
     /// Creates and returns a linear gradient paint with two or more stops.
@@ -497,10 +631,7 @@ impl Paint {
             height,
             radius,
             feather,
-            colors: GradientColors::TwoStop {
-                start_color: inner_color,
-                end_color: outer_color,
-            },
+            colors: GradientColors::two_stop(inner_color, outer_color),
         })
     }
 
@@ -541,10 +672,7 @@ impl Paint {
             center: Position { x: cx, y: cy },
             in_radius,
             out_radius,
-            colors: GradientColors::TwoStop {
-                start_color: inner_color,
-                end_color: outer_color,
-            },
+            colors: GradientColors::two_stop(inner_color, outer_color),
         })
     }
 
@@ -592,6 +720,228 @@ impl Paint {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    /// Creates and returns a radial gradient with a focal point, like `createRadialGradient()` of the HTML canvas
+    /// or an SVG radial gradient with `fx`, `fy` and `fr`.
+    ///
+    /// The gradient starts at the circle around (`fx`,`fy`) with radius `fr` and ends at the circle around
+    /// (`cx`,`cy`) with radius `r`. Every point is colored by the largest circle interpolated between the two
+    /// that passes through it. Points outside of the cone spanned by the two circles are not painted.
+    /// The gradient is transformed by the current transform when it is passed to `fill_path()` or `stroke_path()`.
+    ///
+    /// # Example
+    /// ```
+    /// use femtovg::{Paint, Path, Color, Canvas, renderer::Void};
+    ///
+    /// let mut canvas = Canvas::new(Void).expect("Cannot create canvas");
+    ///
+    /// let sphere = Paint::focal_radial_gradient(
+    ///    35.0,
+    ///    35.0,
+    ///    0.0,
+    ///    50.0,
+    ///    50.0,
+    ///    40.0,
+    ///    Color::rgb(255, 255, 255),
+    ///    Color::rgb(0, 0, 160),
+    /// );
+    ///
+    /// let mut path = Path::new();
+    /// path.circle(50.0, 50.0, 40.0);
+    /// canvas.fill_path(&path, &sphere);
+    /// ```
+    pub fn focal_radial_gradient(
+        fx: f32,
+        fy: f32,
+        fr: f32,
+        cx: f32,
+        cy: f32,
+        r: f32,
+        inner_color: Color,
+        outer_color: Color,
+    ) -> Self {
+        Self::with_flavor(PaintFlavor::FocalGradient {
+            focal: Position { x: fx, y: fy },
+            focal_radius: fr,
+            center: Position { x: cx, y: cy },
+            radius: r,
+            colors: GradientColors::two_stop(inner_color, outer_color),
+        })
+    }
+
+    /// Creates and returns a multi-stop radial gradient with a focal point.
+    ///
+    /// The gradient starts at the circle around (`fx`,`fy`) with radius `fr` and ends at the circle around
+    /// (`cx`,`cy`) with radius `r`, see [`Paint::focal_radial_gradient`]. The stop offsets are fractions of the
+    /// way from the start circle to the end circle.
+    ///
+    /// # Example
+    /// ```
+    /// use femtovg::{Paint, Path, Color, Canvas, renderer::Void};
+    ///
+    /// let mut canvas = Canvas::new(Void).expect("Cannot create canvas");
+    ///
+    /// let sphere = Paint::focal_radial_gradient_stops(
+    ///    35.0,
+    ///    35.0,
+    ///    0.0,
+    ///    50.0,
+    ///    50.0,
+    ///    40.0,
+    ///    [
+    ///         (0.0, Color::rgb(255, 255, 255)),
+    ///         (0.3, Color::rgb(80, 80, 255)),
+    ///         (1.0, Color::rgb(0, 0, 80))
+    ///    ]
+    /// );
+    ///
+    /// let mut path = Path::new();
+    /// path.circle(50.0, 50.0, 40.0);
+    /// canvas.fill_path(&path, &sphere);
+    /// ```
+    pub fn focal_radial_gradient_stops(
+        fx: f32,
+        fy: f32,
+        fr: f32,
+        cx: f32,
+        cy: f32,
+        r: f32,
+        stops: impl IntoIterator<Item = (f32, Color)>,
+    ) -> Self {
+        Self::with_flavor(PaintFlavor::FocalGradient {
+            focal: Position { x: fx, y: fy },
+            focal_radius: fr,
+            center: Position { x: cx, y: cy },
+            radius: r,
+            colors: GradientColors::from_stops(stops),
+        })
+    }
+
+    /// Creates and returns a conic gradient.
+    ///
+    /// Parameters (`cx`,`cy`) specify the center, the colors sweep clockwise around it starting at `start_angle`
+    /// (in radians, 0 pointing along the positive x axis). `start_color` specifies the color at the start angle
+    /// and `end_color` the color after a full turn.
+    /// The gradient is transformed by the current transform when it is passed to `fill_path()` or `stroke_path()`.
+    ///
+    /// # Example
+    /// ```
+    /// use femtovg::{Paint, Path, Color, Canvas, renderer::Void};
+    ///
+    /// let mut canvas = Canvas::new(Void).expect("Cannot create canvas");
+    ///
+    /// let bg = Paint::conic_gradient(
+    ///    50.0,
+    ///    50.0,
+    ///    -std::f32::consts::FRAC_PI_2,
+    ///    Color::rgb(0, 160, 255),
+    ///    Color::rgb(0, 40, 120),
+    /// );
+    ///
+    /// let mut path = Path::new();
+    /// path.circle(50.0, 50.0, 40.0);
+    /// canvas.fill_path(&path, &bg);
+    /// ```
+    pub fn conic_gradient(cx: f32, cy: f32, start_angle: f32, start_color: Color, end_color: Color) -> Self {
+        Self::with_flavor(PaintFlavor::ConicGradient {
+            center: Position { x: cx, y: cy },
+            start_angle,
+            colors: GradientColors::two_stop(start_color, end_color),
+        })
+    }
+
+    /// Creates and returns a multi-stop conic gradient.
+    ///
+    /// Parameters (`cx`,`cy`) specify the center and `start_angle` the angle (in radians, 0 pointing along the
+    /// positive x axis) at which the clockwise sweep starts. The stop offsets are fractions of a full turn.
+    ///
+    /// The gradient is transformed by the current transform when it is passed to `fill_path()` or `stroke_path()`.
+    ///
+    /// # Example
+    /// ```
+    /// use femtovg::{Paint, Path, Color, Canvas, renderer::Void};
+    ///
+    /// let mut canvas = Canvas::new(Void).expect("Cannot create canvas");
+    ///
+    /// let color_wheel = Paint::conic_gradient_stops(
+    ///    50.0,
+    ///    50.0,
+    ///    0.0,
+    ///    [
+    ///         (0.0, Color::rgb(255, 0, 0)),
+    ///         (1.0 / 3.0, Color::rgb(0, 255, 0)),
+    ///         (2.0 / 3.0, Color::rgb(0, 0, 255)),
+    ///         (1.0, Color::rgb(255, 0, 0))
+    ///    ]
+    /// );
+    ///
+    /// let mut path = Path::new();
+    /// path.circle(50.0, 50.0, 40.0);
+    /// canvas.fill_path(&path, &color_wheel);
+    /// ```
+    pub fn conic_gradient_stops(
+        cx: f32,
+        cy: f32,
+        start_angle: f32,
+        stops: impl IntoIterator<Item = (f32, Color)>,
+    ) -> Self {
+        Self::with_flavor(PaintFlavor::ConicGradient {
+            center: Position { x: cx, y: cy },
+            start_angle,
+            colors: GradientColors::from_stops(stops),
+        })
+    }
+
+    /// Returns how the gradient of the paint continues beyond its start and end. Paints without a gradient
+    /// return `GradientSpread::Pad`.
+    #[inline]
+    pub fn gradient_spread(&self) -> GradientSpread {
+        self.flavor
+            .gradient_colors()
+            .map_or(GradientSpread::Pad, GradientColors::spread)
+    }
+
+    /// Sets how the gradient of the paint continues beyond its start and end. This has no effect on paints
+    /// without a gradient, or on conic gradients, which always cover a full turn.
+    #[inline]
+    pub fn set_gradient_spread(&mut self, spread: GradientSpread) {
+        if let Some(colors) = self.flavor.gradient_colors_mut() {
+            colors.set_spread(spread);
+        }
+    }
+
+    /// Returns the paint with the gradient spread set to the specified value.
+    #[inline]
+    pub fn with_gradient_spread(mut self, spread: GradientSpread) -> Self {
+        self.set_gradient_spread(spread);
+        self
+    }
+
+    /// Returns the color space in which the colors of the gradient of the paint are interpolated. Paints without a
+    /// gradient return `GradientColorSpace::Srgb`.
+    #[inline]
+    pub fn gradient_color_space(&self) -> GradientColorSpace {
+        self.flavor
+            .gradient_colors()
+            .map_or(GradientColorSpace::Srgb, GradientColors::color_space)
+    }
+
+    /// Sets the color space in which the colors of the gradient of the paint are interpolated. This has no effect
+    /// on paints without a gradient.
+    #[inline]
+    pub fn set_gradient_color_space(&mut self, color_space: GradientColorSpace) {
+        if let Some(colors) = self.flavor.gradient_colors_mut() {
+            colors.set_color_space(color_space);
+        }
+    }
+
+    /// Returns the paint with the gradient color space set to the specified value.
+    #[inline]
+    pub fn with_gradient_color_space(mut self, color_space: GradientColorSpace) -> Self {
+        self.set_gradient_color_space(color_space);
+        self
+    }
+
     /// Sets the color of the paint.
     pub fn set_color(&mut self, color: Color) {
         self.flavor = PaintFlavor::Color(color);
@@ -752,6 +1102,49 @@ impl Paint {
         self
     }
 
+    /// Returns the current dash pattern. An empty pattern means solid lines.
+    #[inline]
+    pub fn dash_array(&self) -> &[f32] {
+        &self.stroke.dash_array
+    }
+
+    /// Sets the dash pattern used when stroking paths.
+    ///
+    /// The pattern alternates between the lengths of dashes and gaps. If it has an odd number of elements it is
+    /// repeated to yield an even number, so `[5.0]` is the same as `[5.0, 5.0]`. Zero length dashes are drawn
+    /// as dots when combined with round or square line caps. Patterns with negative or non-finite lengths, or
+    /// without any non-zero gap, are drawn as solid lines.
+    #[inline]
+    pub fn set_dash_array(&mut self, dashes: &[f32]) {
+        self.stroke.dash_array = dashes.to_vec();
+    }
+
+    /// Returns the paint with the dash pattern set to the specified value.
+    #[inline]
+    pub fn with_dash_array(mut self, dashes: &[f32]) -> Self {
+        self.set_dash_array(dashes);
+        self
+    }
+
+    /// Returns the current dash offset.
+    #[inline]
+    pub fn dash_offset(&self) -> f32 {
+        self.stroke.dash_offset
+    }
+
+    /// Sets the distance into the dash pattern at which each sub-path starts.
+    #[inline]
+    pub fn set_dash_offset(&mut self, offset: f32) {
+        self.stroke.dash_offset = offset;
+    }
+
+    /// Returns the paint with the dash offset set to the specified value.
+    #[inline]
+    pub fn with_dash_offset(mut self, offset: f32) -> Self {
+        self.set_dash_offset(offset);
+        self
+    }
+
     /// Sets the font.
     pub fn set_font(&mut self, font_ids: &[FontId]) {
         self.text.font_ids = Default::default();
@@ -844,6 +1237,45 @@ impl Paint {
         self
     }
 
+    /// Returns the lines drawn along text.
+    #[inline]
+    pub fn text_decoration(&self) -> TextDecoration {
+        self.text.text_decoration
+    }
+
+    /// Sets the lines drawn along text. Their position and thickness are taken from the font.
+    #[inline]
+    pub fn set_text_decoration(&mut self, decoration: TextDecoration) {
+        self.text.text_decoration = decoration;
+    }
+
+    /// Returns the paint with the lines drawn along text set to the specified value.
+    #[inline]
+    pub fn with_text_decoration(mut self, decoration: TextDecoration) -> Self {
+        self.set_text_decoration(decoration);
+        self
+    }
+
+    /// Returns whether underlines and overlines leave gaps where they would cross glyphs.
+    #[inline]
+    pub fn text_decoration_skip_ink(&self) -> bool {
+        self.text.text_decoration_skip_ink
+    }
+
+    /// Sets whether underlines and overlines leave gaps where they would cross glyphs, for example the
+    /// descenders of "g" and "y". This is enabled by default.
+    #[inline]
+    pub fn set_text_decoration_skip_ink(&mut self, skip_ink: bool) {
+        self.text.text_decoration_skip_ink = skip_ink;
+    }
+
+    /// Returns the paint with skipping glyphs in underlines and overlines set to the specified value.
+    #[inline]
+    pub fn with_text_decoration_skip_ink(mut self, skip_ink: bool) -> Self {
+        self.set_text_decoration_skip_ink(skip_ink);
+        self
+    }
+
     /// Returns the current fill rule for filling paths.
     #[inline]
     pub fn fill_rule(&self) -> FillRule {
@@ -4,12 +4,31 @@ use std::{
     slice,
 };
 
-use crate::geometry::{Position, Transform2D, Vector};
+use crate::geometry::{Bounds, Position, Transform2D, Vector};
 use rustybuzz::ttf_parser;
 
 mod cache;
 pub use cache::{Convexity, PathCache};
 
+mod interop;
+
+mod measure;
+pub use measure::PathMeasure;
+
+mod offset;
+
+mod ops;
+pub use ops::BooleanOp;
+
+mod simplify;
+
+mod stroke;
+
+mod svg;
+
+// Curves are flattened with the tolerance the canvas uses at a scale of one by path operations.
+const TESS_TOL: f32 = 0.25;
+
 // Length proportional to radius of a cubic bezier handle for 90deg arcs.
 const KAPPA90: f32 = 0.552_284_8; // 0.552_284_749_3;
 
@@ -32,6 +51,8 @@ pub enum PackedVerb {
     MoveTo,
     LineTo,
     BezierTo,
+    QuadTo,
+    ConicTo,
     Solid,
     Hole,
     Close,
@@ -39,7 +60,10 @@ pub enum PackedVerb {
 
 /// A verb describes how to interpret one or more points to continue the countour
 /// of a [`Path`].
+///
+/// More verbs may be added in the future, so matches on verbs need a wildcard arm.
 #[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
 pub enum Verb {
     /// Terminates the current sub-path and defines the new current point by the
     /// given x/y f32 coordinates.
@@ -50,6 +74,13 @@ pub enum Verb {
     /// Describes that the contour of the path should continue as a cubie bezier segment from the
     /// current point via two control points (as f32 pairs) to the point in the last f32 pair.
     BezierTo(f32, f32, f32, f32, f32, f32),
+    /// Describes that the contour of the path should continue as a quadratic bezier segment from the
+    /// current point via a control point (as f32 pair) to the point in the last f32 pair.
+    QuadTo(f32, f32, f32, f32),
+    /// Describes that the contour of the path should continue as a rational quadratic bezier (conic)
+    /// segment from the current point via a control point (as f32 pair) to the point in the second f32
+    /// pair, with the weight of the control point in the last f32.
+    ConicTo(f32, f32, f32, f32, f32),
     /// Sets the current sub-path winding to be solid.
     Solid,
     /// Sets the current sub-path winding to be hole.
@@ -64,13 +95,15 @@ impl Verb {
             Self::MoveTo(..) => 1,
             Self::LineTo(..) => 1,
             Self::BezierTo(..) => 3,
+            Self::QuadTo(..) => 2,
+            Self::ConicTo(..) => 2,
             Self::Solid => 0,
             Self::Hole => 0,
             Self::Close => 0,
         }
     }
 
-    fn from_packed(packed: &PackedVerb, coords: &[Position]) -> Self {
+    fn from_packed(packed: &PackedVerb, coords: &[Position], weights: &[f32]) -> Self {
         match *packed {
             PackedVerb::MoveTo => Self::MoveTo(coords[0].x, coords[0].y),
             PackedVerb::LineTo => Self::LineTo(coords[0].x, coords[0].y),
@@ -82,6 +115,8 @@ impl Verb {
                 coords[2].x,
                 coords[2].y,
             ),
+            PackedVerb::QuadTo => Self::QuadTo(coords[0].x, coords[0].y, coords[1].x, coords[1].y),
+            PackedVerb::ConicTo => Self::ConicTo(coords[0].x, coords[0].y, coords[1].x, coords[1].y, weights[0]),
             PackedVerb::Solid => Self::Solid,
             PackedVerb::Hole => Self::Hole,
             PackedVerb::Close => Self::Close,
@@ -96,6 +131,9 @@ impl Verb {
 pub struct Path {
     verbs: Vec<PackedVerb>,
     coords: Vec<Position>,
+    // The weights of the conic segments, kept apart from the coordinates as they aren't transformed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    weights: Vec<f32>,
     last_pos: Position,
     dist_tol: f32,
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -113,7 +151,9 @@ impl Path {
 
     /// Returns the memory size in bytes used by the path.
     pub fn size(&self) -> usize {
-        std::mem::size_of::<PackedVerb>() * self.verbs.len() + std::mem::size_of::<f32>() * self.coords.len()
+        std::mem::size_of::<PackedVerb>() * self.verbs.len()
+            + std::mem::size_of::<f32>() * self.coords.len()
+            + std::mem::size_of::<f32>() * self.weights.len()
     }
 
     /// Checks if the path is empty (contains no verbs).
@@ -131,6 +171,7 @@ impl Path {
         PathIter {
             verbs: self.verbs.iter(),
             coords: &self.coords,
+            weights: &self.weights,
         }
     }
 
@@ -183,13 +224,23 @@ impl Path {
 
     /// Adds a quadratic bezier segment from the last point in the path via a control point to the specified point.
     pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
-        let pos0 = self.last_pos;
-        let cpos = Position { x: cx, y: cy };
-        let pos = Position { x, y };
-        let pos1 = pos0 + (cpos - pos0) * (2.0 / 3.0);
-        let pos2 = pos + (cpos - pos) * (2.0 / 3.0);
+        self.append(&[PackedVerb::QuadTo], &[Position { x: cx, y: cy }, Position { x, y }]);
+    }
+
+    /// Adds a conic segment, a quadratic bezier segment with a `weight` for its control point, from the last
+    /// point in the path via a control point to the specified point.
+    ///
+    /// Conics describe conic sections exactly: a weight below one gives an elliptical arc, one a parabola like
+    /// [`Self::quad_to`], and above one a hyperbola. For example, a quarter circle has its control point at the
+    /// corner and a weight of `FRAC_1_SQRT_2`. Weights that aren't positive and finite add a line instead.
+    pub fn conic_to(&mut self, cx: f32, cy: f32, x: f32, y: f32, weight: f32) {
+        if !(weight > 0.0 && weight.is_finite()) {
+            self.line_to(x, y);
+            return;
+        }
 
-        self.append(&[PackedVerb::BezierTo], &[pos1, pos2, pos]);
+        self.weights.push(weight);
+        self.append(&[PackedVerb::ConicTo], &[Position { x: cx, y: cy }, Position { x, y }]);
     }
 
     /// Closes the current sub-path with a line segment.
@@ -321,6 +372,116 @@ impl Path {
         self.arc(cpos.x, cpos.y, radius, a0 + PI / 2.0, a1 + PI / 2.0, dir);
     }
 
+    /// Adds an elliptical arc segment from the last point in the path to the specified point, with the
+    /// semantics of the SVG `A` path command. The ellipse has the radii `rx` and `ry` and is rotated by
+    /// `x_axis_rotation` (in radians). Of the four arcs that connect the points, `large_arc` selects one
+    /// spanning more than 180 degrees and `sweep` one drawn in the direction of increasing angles.
+    ///
+    /// Radii too small to reach the end point are scaled up, negative radii are treated as positive, and
+    /// an arc with a zero radius is a straight line.
+    #[allow(clippy::too_many_arguments)]
+    pub fn elliptical_arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        x_axis_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        x: f32,
+        y: f32,
+    ) {
+        if self.verbs.is_empty() {
+            return;
+        }
+
+        let pos0 = self.last_pos;
+        let pos = Position { x, y };
+
+        // Handle degenerate cases: an arc to the current point is omitted, one without radius is a
+        // straight line.
+        if pos0.x == pos.x && pos0.y == pos.y {
+            return;
+        }
+        if rx.abs() < self.dist_tol || ry.abs() < self.dist_tol || !rx.is_finite() || !ry.is_finite() {
+            return self.line_to(x, y);
+        }
+
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        let (sin_phi, cos_phi) = x_axis_rotation.sin_cos();
+
+        // Compute the center, see https://www.w3.org/TR/SVG2/implnote.html#ArcConversionEndpointToCenter
+        let half = (pos0 - pos) * 0.5;
+        let x1 = cos_phi * half.x + sin_phi * half.y;
+        let y1 = -sin_phi * half.x + cos_phi * half.y;
+
+        // Scale up radii that are too small to reach the end point.
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+
+        let mid = Position {
+            x: (pos0.x + pos.x) * 0.5,
+            y: (pos0.y + pos.y) * 0.5,
+        };
+        let cpos = mid
+            + Vector {
+                x: cos_phi * cx1 - sin_phi * cy1,
+                y: sin_phi * cx1 + cos_phi * cy1,
+            };
+
+        let a0 = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let a1 = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+        let mut da = a1 - a0;
+        if sweep && da < 0.0 {
+            da += PI * 2.0;
+        } else if !sweep && da > 0.0 {
+            da -= PI * 2.0;
+        }
+
+        // Maps a point on the unit circle onto the ellipse.
+        let map = |v: Vector| {
+            cpos + Vector {
+                x: cos_phi * rx * v.x - sin_phi * ry * v.y,
+                y: sin_phi * rx * v.x + cos_phi * ry * v.y,
+            }
+        };
+
+        // Split arc into max 90 degree segments, ignoring rounding errors of exact quarter arcs.
+        let ndivs = ((da.abs() / (PI * 0.5) - 1e-3).ceil() as usize).max(1);
+        let seg_da = da / ndivs as f32;
+        let kappa = 4.0 / 3.0 * (seg_da / 4.0).tan();
+
+        let mut commands = Vec::with_capacity(ndivs);
+        let mut coords = Vec::with_capacity(ndivs * 3);
+
+        for i in 0..ndivs {
+            let a = a0 + seg_da * i as f32;
+            let b = a + seg_da;
+            let (va, vb) = (Vector::from_angle(a), Vector::from_angle(b));
+            let end = if i + 1 == ndivs { pos } else { map(vb) };
+
+            commands.push(PackedVerb::BezierTo);
+            coords.extend_from_slice(&[
+                map(va - va.orthogonal() * kappa),
+                map(vb + vb.orthogonal() * kappa),
+                end,
+            ]);
+        }
+
+        self.append(&commands, &coords);
+    }
+
     /// Creates a new rectangle shaped sub-path.
     pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
         self.append(
@@ -351,6 +512,7 @@ impl Path {
     }
 
     /// Creates a new rounded rectangle shaped sub-path with varying radii for each corner.
+    #[allow(clippy::too_many_arguments)]
     pub fn rounded_rect_varying(
         &mut self,
         x: f32,
@@ -492,6 +654,232 @@ impl Path {
         self.ellipse(cx, cy, r, r);
     }
 
+    // Path utilities
+
+    /// Applies `transform` to all points of the path.
+    pub fn transform(&mut self, transform: &Transform2D) {
+        for pos in self.coords.iter_mut().chain([&mut self.last_pos]) {
+            let (x, y) = transform.transform_point(pos.x, pos.y);
+            *pos = Position { x, y };
+        }
+
+        *self.cache.get_mut() = None;
+    }
+
+    /// Returns a copy of the path with `transform` applied to all its points.
+    pub fn transformed(&self, transform: &Transform2D) -> Self {
+        let mut path = self.clone();
+        path.transform(transform);
+        path
+    }
+
+    /// Returns the bounding box of all points of the path, including the control points of curves.
+    ///
+    /// This is cheaper to compute than [`Self::bounds`], but may be larger. An empty path has the default
+    /// bounds.
+    pub fn control_bounds(&self) -> Bounds {
+        if self.coords.is_empty() {
+            return Bounds::default();
+        }
+
+        self.coords
+            .iter()
+            .fold(Bounds::EMPTY, |bounds, pos| bounds.including(*pos))
+    }
+
+    /// Returns the tight bounding box of the path, which encloses its curves but not necessarily
+    /// their control points.
+    ///
+    /// An empty path has the default bounds.
+    pub fn bounds(&self) -> Bounds {
+        if self.coords.is_empty() {
+            return Bounds::default();
+        }
+
+        let mut bounds = Bounds::EMPTY;
+        let mut last = Position::default();
+
+        for verb in self.verbs() {
+            match verb {
+                Verb::MoveTo(x, y) | Verb::LineTo(x, y) => {
+                    last = Position { x, y };
+                    bounds = bounds.including(last);
+                }
+                Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                    let xs = [last.x, c1x, c2x, x];
+                    let ys = [last.y, c1y, c2y, y];
+
+                    for t in cubic_extrema(xs).chain(cubic_extrema(ys)) {
+                        let mt = 1.0 - t;
+                        let weights = [mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t];
+                        let at = |coords: [f32; 4]| (0..4).map(|i| coords[i] * weights[i]).sum();
+                        bounds = bounds.including(Position { x: at(xs), y: at(ys) });
+                    }
+
+                    last = Position { x, y };
+                    bounds = bounds.including(last);
+                }
+                Verb::QuadTo(cx, cy, x, y) | Verb::ConicTo(cx, cy, x, y, _) => {
+                    let weight = if let Verb::ConicTo(.., weight) = verb {
+                        weight
+                    } else {
+                        1.0
+                    };
+                    let (p1, p2) = (Position { x: cx, y: cy }, Position { x, y });
+
+                    for t in conic_extrema(last.x, cx, x, weight).chain(conic_extrema(last.y, cy, y, weight)) {
+                        bounds = bounds.including(conic_point(last, p1, p2, weight, t));
+                    }
+
+                    last = p2;
+                    bounds = bounds.including(last);
+                }
+                Verb::Solid | Verb::Hole | Verb::Close => {}
+            }
+        }
+
+        bounds
+    }
+
+    /// Reverses the direction of all sub-paths.
+    ///
+    /// Each sub-path starts at the point it ended at before, and stays closed if it was. Solidity markers are
+    /// kept as they are.
+    pub fn reverse(&mut self) {
+        let mut reversed = Self {
+            dist_tol: self.dist_tol,
+            ..Default::default()
+        };
+
+        let mut verbs = self.verbs().peekable();
+        let mut last = Position::default();
+
+        while verbs.peek().is_some() {
+            // Collect one sub-path, up to the next move.
+            let mut start = last;
+            let mut segments = Vec::new();
+            let mut closed = false;
+            let mut solidity = Vec::new();
+
+            if let Some(Verb::MoveTo(x, y)) = verbs.peek().copied() {
+                verbs.next();
+                start = Position { x, y };
+            }
+            last = start;
+
+            // Each segment is reversed to end where it started.
+            while let Some(verb) = verbs.next_if(|verb| !matches!(verb, Verb::MoveTo(..))) {
+                let (x, y) = (last.x, last.y);
+                let segment = match verb {
+                    Verb::LineTo(x, y) | Verb::QuadTo(_, _, x, y) | Verb::ConicTo(_, _, x, y, _) => {
+                        last = Position { x, y };
+                        verb
+                    }
+                    Verb::BezierTo(.., x, y) => {
+                        last = Position { x, y };
+                        verb
+                    }
+                    Verb::Close => {
+                        closed = true;
+                        continue;
+                    }
+                    Verb::Solid | Verb::Hole => {
+                        solidity.push(verb);
+                        continue;
+                    }
+                    Verb::MoveTo(..) => unreachable!(),
+                };
+
+                segments.push(match segment {
+                    Verb::BezierTo(c1x, c1y, c2x, c2y, ..) => Verb::BezierTo(c2x, c2y, c1x, c1y, x, y),
+                    Verb::QuadTo(cx, cy, ..) => Verb::QuadTo(cx, cy, x, y),
+                    Verb::ConicTo(cx, cy, _, _, weight) => Verb::ConicTo(cx, cy, x, y, weight),
+                    _ => Verb::LineTo(x, y),
+                });
+            }
+
+            reversed.move_to(last.x, last.y);
+            for verb in segments.into_iter().rev() {
+                reversed.push_verb(verb);
+            }
+            if closed {
+                reversed.close();
+            }
+            for verb in solidity {
+                reversed.push_verb(verb);
+            }
+
+            // Sub-paths without a move continue from where the previous one ended.
+            last = reversed.last_pos;
+        }
+
+        *self = reversed;
+    }
+
+    /// Appends all sub-paths of `other` to this path.
+    pub fn extend(&mut self, other: &Self) {
+        self.verbs.extend_from_slice(&other.verbs);
+        self.coords.extend_from_slice(&other.coords);
+        self.weights.extend_from_slice(&other.weights);
+
+        if !other.coords.is_empty() {
+            self.last_pos = other.last_pos;
+        }
+
+        *self.cache.get_mut() = None;
+    }
+
+    /// Returns the verbs of the path with conics approximated by quadratic bezier segments, within the
+    /// distance tolerance of the path.
+    fn verbs_without_conics(&self) -> impl Iterator<Item = Verb> + '_ {
+        let mut last = Position::default();
+
+        self.verbs().flat_map(move |verb| {
+            let quads = match verb {
+                Verb::ConicTo(cx, cy, x, y, weight) => conic_to_quads(
+                    last,
+                    Position { x: cx, y: cy },
+                    Position { x, y },
+                    weight,
+                    self.dist_tol,
+                ),
+                _ => Vec::new(),
+            };
+
+            match verb {
+                Verb::MoveTo(x, y)
+                | Verb::LineTo(x, y)
+                | Verb::BezierTo(.., x, y)
+                | Verb::QuadTo(.., x, y)
+                | Verb::ConicTo(_, _, x, y, _) => last = Position { x, y },
+                Verb::Solid | Verb::Hole | Verb::Close => {}
+            }
+
+            if quads.is_empty() {
+                vec![verb]
+            } else {
+                quads
+                    .into_iter()
+                    .map(|(c, p)| Verb::QuadTo(c.x, c.y, p.x, p.y))
+                    .collect()
+            }
+        })
+    }
+
+    /// Appends a verb to the path.
+    fn push_verb(&mut self, verb: Verb) {
+        match verb {
+            Verb::MoveTo(x, y) => self.move_to(x, y),
+            Verb::LineTo(x, y) => self.line_to(x, y),
+            Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => self.bezier_to(c1x, c1y, c2x, c2y, x, y),
+            Verb::QuadTo(cx, cy, x, y) => self.quad_to(cx, cy, x, y),
+            Verb::ConicTo(cx, cy, x, y, weight) => self.conic_to(cx, cy, x, y, weight),
+            Verb::Solid => self.solidity(Solidity::Solid),
+            Verb::Hole => self.solidity(Solidity::Hole),
+            Verb::Close => self.close(),
+        }
+    }
+
     /// Appends a slice of verbs and coordinates to the path.
     fn append(&mut self, verbs: &[PackedVerb], coords: &[Position]) {
         if !coords.is_empty() {
@@ -507,6 +895,7 @@ impl Path {
 pub struct PathIter<'a> {
     verbs: slice::Iter<'a, PackedVerb>,
     coords: &'a [Position],
+    weights: &'a [f32],
 }
 
 impl Iterator for PathIter<'_> {
@@ -514,9 +903,12 @@ impl Iterator for PathIter<'_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(verb) = self.verbs.next() {
-            let verb = Verb::from_packed(verb, self.coords);
+            let verb = Verb::from_packed(verb, self.coords, self.weights);
             let num_coords = verb.num_coordinates();
             self.coords = &self.coords[num_coords..];
+            if let Verb::ConicTo(..) = verb {
+                self.weights = &self.weights[1..];
+            }
             Some(verb)
         } else {
             None
@@ -545,3 +937,81 @@ impl ttf_parser::OutlineBuilder for Path {
         self.close();
     }
 }
+
+/// Returns the parameters in (0, 1) at which a cubic bezier with the given coordinates along one axis has an
+/// extremum.
+fn cubic_extrema([p0, p1, p2, p3]: [f32; 4]) -> impl Iterator<Item = f32> {
+    // The derivative, divided by three, is a*t^2 + b*t + c.
+    unit_roots(-p0 + 3.0 * p1 - 3.0 * p2 + p3, 2.0 * (p0 - 2.0 * p1 + p2), p1 - p0)
+}
+
+/// Returns the parameters in (0, 1) at which a conic with the given coordinates along one axis and weight has
+/// an extremum.
+fn conic_extrema(p0: f32, p1: f32, p2: f32, weight: f32) -> impl Iterator<Item = f32> {
+    // The numerator of the derivative is proportional to a*t^2 + b*t + c.
+    let (p20, wp10) = (p2 - p0, weight * (p1 - p0));
+    unit_roots(weight * p20 - p20, p20 - 2.0 * wp10, wp10)
+}
+
+/// Returns the roots of a*t^2 + b*t + c in (0, 1).
+fn unit_roots(a: f32, b: f32, c: f32) -> impl Iterator<Item = f32> {
+    let roots = if a.abs() < 1e-12 {
+        [(b.abs() > 1e-12).then(|| -c / b), None]
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            [None, None]
+        } else {
+            let sqrt = discriminant.sqrt();
+            [Some((-b + sqrt) / (2.0 * a)), Some((-b - sqrt) / (2.0 * a))]
+        }
+    };
+
+    roots.into_iter().flatten().filter(|t| *t > 0.0 && *t < 1.0)
+}
+
+/// Returns the point at parameter `t` of a conic.
+fn conic_point(p0: Position, p1: Position, p2: Position, weight: f32, t: f32) -> Position {
+    let mt = 1.0 - t;
+    let (a, b, c) = (mt * mt, 2.0 * weight * mt * t, t * t);
+    let denom = a + b + c;
+
+    Position {
+        x: (a * p0.x + b * p1.x + c * p2.x) / denom,
+        y: (a * p0.y + b * p1.y + c * p2.y) / denom,
+    }
+}
+
+/// Approximates a conic with quadratic bezier segments, returned as control and end points, that deviate from
+/// it by no more than `tolerance`.
+fn conic_to_quads(p0: Position, p1: Position, p2: Position, weight: f32, tolerance: f32) -> Vec<(Position, Position)> {
+    // How far the quadratic bezier with the same points is from the conic, see "Conic Sections" by Tom Sederberg.
+    let error = |p0: Position, p1: Position, p2: Position, weight: f32| {
+        let a = weight - 1.0;
+        let k = a / (4.0 * (2.0 + a));
+        ((p0 - p1) + (p2 - p1)).mag2().sqrt() * k.abs()
+    };
+
+    let mut quads = Vec::new();
+    let mut stack = vec![(p0, p1, p2, weight, 0)];
+
+    while let Some((p0, p1, p2, weight, level)) = stack.pop() {
+        if level >= 10 || error(p0, p1, p2, weight) <= tolerance {
+            quads.push((p1, p2));
+            continue;
+        }
+
+        // Splitting in the middle gives two conics with the same weight.
+        let scale = weight / (1.0 + weight);
+        let c0 = p0 + (p1 - p0) * scale;
+        let c1 = p2 + (p1 - p2) * scale;
+        let mid = c0 + (c1 - c0) * 0.5;
+        let weight = ((1.0 + weight) * 0.5).sqrt();
+
+        // The second half is pushed first so that the first one comes out first.
+        stack.push((mid, c1, p2, weight, level + 1));
+        stack.push((p0, c0, mid, weight, level + 1));
+    }
+
+    quads
+}
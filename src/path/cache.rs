@@ -10,6 +10,9 @@ use crate::{
 
 use super::Verb;
 
+// Dash patterns that would split a path into more dashes than this are stroked solid instead.
+const MAX_DASH_COUNT: usize = 1_000_000;
+
 bitflags! {
     #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub struct PointFlags: u8 {
@@ -186,6 +189,26 @@ impl PathCache {
                         // );
                     }
                 }
+                Verb::QuadTo(cx, cy, x, y) | Verb::ConicTo(cx, cy, x, y, _) => {
+                    if let Some(last) = cache.points.last().copied() {
+                        let weight = if let Verb::ConicTo(.., weight) = verb {
+                            weight
+                        } else {
+                            1.0
+                        };
+                        let (cx, cy) = transform.transform_point(cx, cy);
+                        let (x, y) = transform.transform_point(x, y);
+
+                        cache.tesselate_conic(
+                            [last.pos.x, last.pos.y, cx, cy, x, y],
+                            weight,
+                            0,
+                            PointFlags::CORNER,
+                            tess_tol,
+                            dist_tol,
+                        );
+                    }
+                }
                 Verb::Close => {
                     if let Some(contour) = cache.contours.last_mut() {
                         contour.closed = true;
@@ -236,28 +259,176 @@ impl PathCache {
                 }
             }
 
-            for i in 0..contour.point_count() {
-                let p1 = points.get(i).copied().unwrap();
+            update_segments(points, bounds);
 
-                let p0 = if i == 0 {
-                    points.last_mut().unwrap()
+            true
+        });
+
+        cache
+    }
+
+    /// Splits the contours into dashes, returning `None` if `pattern` has negative or non-finite lengths,
+    /// doesn't describe any gaps or would split the contours into more than `MAX_DASH_COUNT` dashes.
+    ///
+    /// The pattern alternates between the lengths of dashes and gaps and is repeated if it has an odd number of
+    /// elements, matching the HTML canvas `setLineDash()`. Each contour starts the pattern anew, shifted by
+    /// `offset`. Lengths are in the same space as the cached points.
+    pub(crate) fn dashed(&self, pattern: &[f32], offset: f32, dist_tol: f32) -> Option<Self> {
+        if pattern.iter().any(|len| !len.is_finite() || *len < 0.0) {
+            return None;
+        }
+
+        // An odd pattern is repeated to make it even.
+        let pattern_len = if pattern.len() % 2 == 1 {
+            pattern.len() * 2
+        } else {
+            pattern.len()
+        };
+        let dash_len = |index: usize| pattern[index % pattern.len()];
+
+        // Without gaps the dashes would join into the solid contours.
+        if (1..pattern_len).step_by(2).all(|index| dash_len(index) == 0.0) {
+            return None;
+        }
+
+        let total: f32 = (0..pattern_len).map(dash_len).sum();
+
+        // Tiny patterns, for example scaled down by the transform, are stroked solid instead of allocating
+        // a dash for every few points.
+        let length: f32 = self
+            .contours
+            .iter()
+            .map(|contour| {
+                let points = &self.points[contour.point_range.clone()];
+                let segment_count = if contour.closed { points.len() } else { points.len() - 1 };
+                points[..segment_count].iter().map(|point| point.len).sum::<f32>()
+            })
+            .sum();
+
+        if length / total * pattern_len as f32 > MAX_DASH_COUNT as f32 {
+            return None;
+        }
+
+        let mut cache = Self::default();
+
+        for contour in &self.contours {
+            let points = &self.points[contour.point_range.clone()];
+
+            // Find where the offset puts us in the pattern.
+            let mut index = 0;
+            let mut remaining = dash_len(0);
+            let mut skip = offset.rem_euclid(total);
+
+            while skip > 0.0 && skip >= remaining {
+                skip -= remaining;
+                index = (index + 1) % pattern_len;
+                remaining = dash_len(index);
+            }
+
+            remaining -= skip;
+
+            let starts_on = index % 2 == 0;
+            let mut dashes: Vec<Vec<Point>> = Vec::new();
+            let mut dash = if starts_on { vec![points[0]] } else { Vec::new() };
+            let mut split = false;
+
+            let segment_count = if contour.closed { points.len() } else { points.len() - 1 };
+
+            for i in 0..segment_count {
+                let p0 = points[i];
+                let p1 = points[(i + 1) % points.len()];
+                let len = p0.len;
+                let mut pos = 0.0;
+
+                while len - pos > remaining {
+                    pos += remaining;
+                    split = true;
+
+                    let point = Point {
+                        pos: p0.pos + p0.dpos * pos,
+                        dpos: p0.dpos,
+                        flags: PointFlags::CORNER,
+                        ..Default::default()
+                    };
+
+                    // Either ends the current dash or starts a new one.
+                    dash.push(point);
+
+                    if index % 2 == 0 {
+                        dashes.push(std::mem::take(&mut dash));
+                    }
+
+                    index = (index + 1) % pattern_len;
+                    remaining = dash_len(index);
+                }
+
+                remaining -= len - pos;
+
+                if index % 2 == 0 {
+                    dash.push(p1);
+                }
+            }
+
+            if !split {
+                // The whole contour is a single dash.
+                if starts_on {
+                    cache.push_contour(points, contour.closed);
+                }
+                continue;
+            }
+
+            if index % 2 == 0 && !dash.is_empty() {
+                if contour.closed && starts_on {
+                    // The last dash runs into the first one, join them so they get a proper line join.
+                    dash.extend_from_slice(&dashes[0][1..]);
+                    dashes[0] = dash;
                 } else {
-                    points.get_mut(i - 1).unwrap()
-                };
+                    dashes.push(dash);
+                }
+            }
 
-                p0.dpos = p1.pos - p0.pos;
-                p0.len = p0.dpos.normalize();
+            for dash in &mut dashes {
+                dash.dedup_by(|p1, p0| p1.approx_eq(p0, dist_tol));
 
-                bounds.minx = bounds.minx.min(p0.pos.x);
-                bounds.miny = bounds.miny.min(p0.pos.y);
-                bounds.maxx = bounds.maxx.max(p0.pos.x);
-                bounds.maxy = bounds.maxy.max(p0.pos.y);
+                // Zero length dashes turn into dots when drawn with round or square caps, which requires a tiny
+                // segment in the direction of the contour.
+                if dash.len() == 1 {
+                    let mut point = dash[0];
+                    point.pos = point.pos + point.dpos * 1e-3;
+                    dash.push(point);
+                }
+
+                cache.push_contour(dash, false);
             }
+        }
 
-            true
-        });
+        Some(cache)
+    }
 
-        cache
+    /// Returns the flattened positions of each contour.
+    pub(crate) fn contour_positions(&self) -> impl Iterator<Item = impl Iterator<Item = Position> + '_> + '_ {
+        self.contours
+            .iter()
+            .map(|contour| self.points[contour.point_range.clone()].iter().map(|point| point.pos))
+    }
+
+    /// Returns whether each contour is closed.
+    pub(crate) fn contours_closed(&self) -> impl Iterator<Item = bool> + '_ {
+        self.contours.iter().map(|contour| contour.closed)
+    }
+
+    fn push_contour(&mut self, points: &[Point], closed: bool) {
+        let start = self.points.len();
+        self.points.extend_from_slice(points);
+
+        let point_range = start..self.points.len();
+        update_segments(&mut self.points[point_range.clone()], &mut self.bounds);
+
+        self.contours.push(Contour {
+            point_range,
+            closed,
+            ..Default::default()
+        });
     }
 
     fn add_contour(&mut self) {
@@ -360,6 +531,60 @@ impl PathCache {
         );
     }
 
+    /// Flattens a conic, which is a quadratic bezier if the weight is one, given by its start, control and end
+    /// points.
+    fn tesselate_conic(
+        &mut self,
+        [x1, y1, x2, y2, x3, y3]: [f32; 6],
+        weight: f32,
+        level: usize,
+        flags: PointFlags,
+        tess_tol: f32,
+        dist_tol: f32,
+    ) {
+        if level > 10 {
+            return;
+        }
+
+        // The curve is farthest from its chord in the middle, at weight / (1 + weight) of the distance of the
+        // control point.
+        let dx = x3 - x1;
+        let dy = y3 - y1;
+        let d = ((x2 - x3) * dy - (y2 - y3) * dx).abs() * weight / (1.0 + weight);
+
+        if 4.0 * d * d < tess_tol * (dx * dx + dy * dy) {
+            self.add_point(x3, y3, flags, dist_tol);
+            return;
+        }
+
+        // Both halves are conics with the same weight.
+        let scale = weight / (1.0 + weight);
+        let x12 = x1 + (x2 - x1) * scale;
+        let y12 = y1 + (y2 - y1) * scale;
+        let x23 = x3 + (x2 - x3) * scale;
+        let y23 = y3 + (y2 - y3) * scale;
+        let x123 = (x12 + x23) * 0.5;
+        let y123 = (y12 + y23) * 0.5;
+        let weight = ((1.0 + weight) * 0.5).sqrt();
+
+        self.tesselate_conic(
+            [x1, y1, x12, y12, x123, y123],
+            weight,
+            level + 1,
+            PointFlags::empty(),
+            tess_tol,
+            dist_tol,
+        );
+        self.tesselate_conic(
+            [x123, y123, x23, y23, x3, y3],
+            weight,
+            level + 1,
+            flags,
+            tess_tol,
+            dist_tol,
+        );
+    }
+
     // fn tesselate_bezier_afd(
     //     &mut self,
     //     x1: f32,
@@ -732,6 +957,28 @@ impl PathCache {
         }
     }
 
+    /// Returns true if the point (x,y) lies on one of the triangles created by `expand_stroke()`.
+    pub(crate) fn stroke_contains_point(&self, x: f32, y: f32) -> bool {
+        self.stroke_strips().any(|strip| {
+            strip.windows(3).any(|triangle| {
+                let [d0, d1, d2] = [0, 1, 2].map(|i| {
+                    let (a, b) = (&triangle[i], &triangle[(i + 1) % 3]);
+                    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+                });
+
+                // Inside (or on the edge of) the triangle, whichever way it winds.
+                let has_negative = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+                let has_positive = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+                !(has_negative && has_positive)
+            })
+        })
+    }
+
+    /// Returns the triangle strips created by `expand_stroke()`.
+    pub(crate) fn stroke_strips(&self) -> impl Iterator<Item = &[Vertex]> {
+        self.contours.iter().map(|contour| &contour.stroke[..])
+    }
+
     fn calculate_joins(&mut self, stroke_width: f32, line_join: LineJoin, miter_limit: f32) {
         let inv_stroke_width = if stroke_width > 0.0 { 1.0 / stroke_width } else { 0.0 };
 
@@ -908,6 +1155,27 @@ impl PathCache {
     }
 }
 
+/// Calculates the direction and length of each segment of a contour and grows `bounds` to include its points.
+fn update_segments(points: &mut [Point], bounds: &mut Bounds) {
+    for i in 0..points.len() {
+        let p1 = points[i];
+
+        let p0 = if i == 0 {
+            points.last_mut().unwrap()
+        } else {
+            &mut points[i - 1]
+        };
+
+        p0.dpos = p1.pos - p0.pos;
+        p0.len = p0.dpos.normalize();
+
+        bounds.minx = bounds.minx.min(p0.pos.x);
+        bounds.miny = bounds.miny.min(p0.pos.y);
+        bounds.maxx = bounds.maxx.max(p0.pos.x);
+        bounds.maxy = bounds.maxy.max(p0.pos.y);
+    }
+}
+
 fn curve_divisions(radius: f32, arc: f32, tol: f32) -> u32 {
     let da = (radius / (radius + tol)).acos() * 2.0;
 
@@ -1124,6 +1392,117 @@ mod tests {
 
         assert_eq!(path_cache.contours[0].convexity, Convexity::Concave);
     }
+
+    fn dash_lengths(cache: &PathCache) -> Vec<f32> {
+        cache
+            .contours
+            .iter()
+            .map(|contour| {
+                let points = &cache.points[contour.point_range.clone()];
+                points.windows(2).map(|pair| pair[0].len).sum::<f32>()
+            })
+            .collect()
+    }
+
+    fn assert_lengths(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 0.01, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn dashed_line() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(50.0, 0.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+
+        let dashed = cache.dashed(&[10.0, 5.0], 0.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[10.0, 10.0, 10.0, 5.0]);
+        assert!(dashed.contours.iter().all(|contour| !contour.closed));
+
+        let dashed = cache.dashed(&[10.0, 5.0], 5.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[5.0, 10.0, 10.0, 10.0]);
+
+        let dashed = cache.dashed(&[10.0, 5.0], -5.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[10.0, 10.0, 10.0]);
+
+        // Odd patterns are repeated.
+        let dashed = cache.dashed(&[10.0], 0.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[10.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn dashed_closed_contour_joins_first_and_last_dash() {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 10.0, 10.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+
+        // The last dash starts at 32 and continues through the start of the contour.
+        let dashed = cache.dashed(&[12.0, 4.0], 0.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[20.0, 12.0]);
+
+        // A dash longer than the contour keeps it closed.
+        let dashed = cache.dashed(&[50.0, 5.0], 0.0, 0.01).unwrap();
+        assert_eq!(dashed.contours.len(), 1);
+        assert!(dashed.contours[0].closed);
+    }
+
+    #[test]
+    fn dashed_curve() {
+        let mut path = Path::new();
+        path.circle(0.0, 0.0, 10.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+        let circumference: f32 = cache.points.iter().map(|point| point.len).sum();
+
+        let dashed = cache.dashed(&[circumference / 8.0], 0.0, 0.01).unwrap();
+        assert_lengths(&dash_lengths(&dashed), &[circumference / 8.0; 4]);
+    }
+
+    #[test]
+    fn zero_length_dashes_are_dots() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(30.0, 0.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+        let dashed = cache.dashed(&[0.0, 10.0], 0.0, 0.01).unwrap();
+
+        assert_eq!(dashed.contours.len(), 3);
+        assert!(dashed.contours.iter().all(|contour| contour.point_count() == 2));
+    }
+
+    #[test]
+    fn too_many_dashes_are_solid() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(1000.0, 0.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+
+        assert!(cache.dashed(&[1e-4], 0.0, 0.01).is_none());
+        assert!(cache.dashed(&[1e-2, 1e-2], 0.0, 0.01).is_some());
+    }
+
+    #[test]
+    fn invalid_dash_patterns() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(30.0, 0.0);
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+
+        assert!(cache.dashed(&[0.0, 0.0], 0.0, 0.01).is_none());
+        assert!(cache.dashed(&[5.0, 0.0], 0.0, 0.01).is_none());
+        assert!(cache.dashed(&[5.0, 0.0, 3.0, 0.0], 0.0, 0.01).is_none());
+        assert!(cache.dashed(&[5.0, 0.0, 3.0], 0.0, 0.01).is_some());
+        assert!(cache.dashed(&[5.0, -1.0], 0.0, 0.01).is_none());
+        assert!(cache.dashed(&[5.0, f32::NAN], 0.0, 0.01).is_none());
+    }
 }
 
 /*
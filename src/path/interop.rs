@@ -0,0 +1,233 @@
+use super::{Path, Verb};
+
+impl FromIterator<Verb> for Path {
+    fn from_iter<I: IntoIterator<Item = Verb>>(iter: I) -> Self {
+        let mut path = Self::new();
+
+        for verb in iter {
+            path.push_verb(verb);
+        }
+
+        path
+    }
+}
+
+// Solid and hole markers have no equivalent in kurbo or lyon, so they are dropped when converting to
+// their paths. Neither has conics, so those are approximated with quadratic bezier segments.
+
+#[cfg(feature = "kurbo")]
+mod kurbo_conversions {
+    use kurbo::{BezPath, PathEl, Point};
+
+    use super::{Path, Verb};
+
+    impl From<&BezPath> for Path {
+        fn from(bez_path: &BezPath) -> Self {
+            let mut path = Self::new();
+
+            for element in bez_path.elements() {
+                match *element {
+                    PathEl::MoveTo(p) => path.move_to(p.x as f32, p.y as f32),
+                    PathEl::LineTo(p) => path.line_to(p.x as f32, p.y as f32),
+                    PathEl::QuadTo(c, p) => path.quad_to(c.x as f32, c.y as f32, p.x as f32, p.y as f32),
+                    PathEl::CurveTo(c1, c2, p) => path.bezier_to(
+                        c1.x as f32,
+                        c1.y as f32,
+                        c2.x as f32,
+                        c2.y as f32,
+                        p.x as f32,
+                        p.y as f32,
+                    ),
+                    PathEl::ClosePath => path.close(),
+                }
+            }
+
+            path
+        }
+    }
+
+    impl From<BezPath> for Path {
+        fn from(bez_path: BezPath) -> Self {
+            Self::from(&bez_path)
+        }
+    }
+
+    impl From<&Path> for BezPath {
+        fn from(path: &Path) -> Self {
+            let point = |x: f32, y: f32| Point::new(x as f64, y as f64);
+
+            path.verbs_without_conics()
+                .filter_map(|verb| match verb {
+                    Verb::MoveTo(x, y) => Some(PathEl::MoveTo(point(x, y))),
+                    Verb::LineTo(x, y) => Some(PathEl::LineTo(point(x, y))),
+                    Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                        Some(PathEl::CurveTo(point(c1x, c1y), point(c2x, c2y), point(x, y)))
+                    }
+                    Verb::QuadTo(cx, cy, x, y) => Some(PathEl::QuadTo(point(cx, cy), point(x, y))),
+                    Verb::Close => Some(PathEl::ClosePath),
+                    Verb::ConicTo(..) | Verb::Solid | Verb::Hole => None,
+                })
+                .collect()
+        }
+    }
+
+    impl From<Path> for BezPath {
+        fn from(path: Path) -> Self {
+            Self::from(&path)
+        }
+    }
+}
+
+#[cfg(feature = "lyon")]
+mod lyon_conversions {
+    use lyon_path::{math::point, PathEvent};
+
+    use super::{Path, Verb};
+
+    impl From<&lyon_path::Path> for Path {
+        fn from(lyon_path: &lyon_path::Path) -> Self {
+            let mut path = Self::new();
+
+            for event in lyon_path.iter() {
+                match event {
+                    PathEvent::Begin { at } => path.move_to(at.x, at.y),
+                    PathEvent::Line { to, .. } => path.line_to(to.x, to.y),
+                    PathEvent::Quadratic { ctrl, to, .. } => path.quad_to(ctrl.x, ctrl.y, to.x, to.y),
+                    PathEvent::Cubic { ctrl1, ctrl2, to, .. } => {
+                        path.bezier_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y);
+                    }
+                    PathEvent::End { close: true, .. } => path.close(),
+                    PathEvent::End { close: false, .. } => {}
+                }
+            }
+
+            path
+        }
+    }
+
+    impl From<lyon_path::Path> for Path {
+        fn from(lyon_path: lyon_path::Path) -> Self {
+            Self::from(&lyon_path)
+        }
+    }
+
+    impl From<&Path> for lyon_path::Path {
+        fn from(path: &Path) -> Self {
+            let mut builder = Self::builder();
+
+            // Lyon requires each sub-path to be begun and ended explicitly. Segments following a close
+            // without a move start a new sub-path at the current point.
+            let mut current = point(0.0, 0.0);
+            let mut open = false;
+
+            for verb in path.verbs_without_conics() {
+                match verb {
+                    Verb::MoveTo(x, y) => {
+                        if open {
+                            builder.end(false);
+                        }
+                        current = point(x, y);
+                        builder.begin(current);
+                        open = true;
+                        continue;
+                    }
+                    Verb::Close => {
+                        if open {
+                            builder.end(true);
+                            open = false;
+                        }
+                        continue;
+                    }
+                    Verb::ConicTo(..) | Verb::Solid | Verb::Hole => continue,
+                    Verb::LineTo(..) | Verb::BezierTo(..) | Verb::QuadTo(..) => {}
+                }
+
+                if !open {
+                    builder.begin(current);
+                    open = true;
+                }
+
+                match verb {
+                    Verb::LineTo(x, y) => {
+                        current = point(x, y);
+                        builder.line_to(current);
+                    }
+                    Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                        current = point(x, y);
+                        builder.cubic_bezier_to(point(c1x, c1y), point(c2x, c2y), current);
+                    }
+                    Verb::QuadTo(cx, cy, x, y) => {
+                        current = point(x, y);
+                        builder.quadratic_bezier_to(point(cx, cy), current);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+
+            if open {
+                builder.end(false);
+            }
+
+            builder.build()
+        }
+    }
+
+    impl From<Path> for lyon_path::Path {
+        fn from(path: Path) -> Self {
+            Self::from(&path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Path {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(10.0, 0.0);
+        path.bezier_to(10.0, 5.0, 5.0, 10.0, 0.0, 10.0);
+        path.close();
+        path.move_to(20.0, 20.0);
+        path.line_to(30.0, 20.0);
+        path
+    }
+
+    #[test]
+    fn from_iter() {
+        let path = sample();
+        let collected: Path = path.verbs().collect();
+        assert_eq!(collected.to_svg_path_data(), path.to_svg_path_data());
+    }
+
+    #[cfg(feature = "kurbo")]
+    #[test]
+    fn kurbo_round_trip() {
+        let bez_path = kurbo::BezPath::from(&sample());
+        assert_eq!(bez_path.to_svg(), "M0,0 L10,0 C10,5 5,10 0,10 Z M20,20 L30,20");
+        assert_eq!(Path::from(bez_path).to_svg_path_data(), sample().to_svg_path_data());
+
+        let mut quad = kurbo::BezPath::new();
+        quad.move_to((0.0, 0.0));
+        quad.quad_to((15.0, 15.0), (30.0, 0.0));
+        assert_eq!(Path::from(quad).to_svg_path_data(), "M0 0 Q15 15 30 0");
+    }
+
+    #[cfg(feature = "lyon")]
+    #[test]
+    fn lyon_round_trip() {
+        let lyon_path = lyon_path::Path::from(&sample());
+        assert_eq!(lyon_path.iter().count(), 7);
+        assert_eq!(Path::from(lyon_path).to_svg_path_data(), sample().to_svg_path_data());
+
+        // Segments after a close start a new sub-path at the current point.
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(10.0, 0.0);
+        path.close();
+        path.line_to(10.0, 10.0);
+        let lyon_path = lyon_path::Path::from(path);
+        assert_eq!(Path::from(&lyon_path).to_svg_path_data(), "M0 0 L10 0 Z M10 0 L10 10");
+    }
+}
@@ -0,0 +1,526 @@
+use super::{conic_to_quads, Path, Verb};
+use crate::geometry::{Position, Vector};
+
+// Number of intervals a cubic bezier segment is divided into for its arc length table.
+const CUBIC_INTERVALS: usize = 16;
+
+// Abscissae and weights of the 5 point Gauss-Legendre quadrature on [-1, 1].
+const GAUSS_LEGENDRE: [(f32, f32); 5] = [
+    (0.0, 0.568_888_9),
+    (-0.538_469_3, 0.478_628_67),
+    (0.538_469_3, 0.478_628_67),
+    (-0.906_179_85, 0.236_926_88),
+    (0.906_179_85, 0.236_926_88),
+];
+
+#[derive(Copy, Clone, Debug)]
+enum Curve {
+    Line(Position, Position),
+    Cubic(Position, Position, Position, Position),
+}
+
+impl Curve {
+    /// Returns the cubic bezier describing the same curve as a quadratic one.
+    fn from_quad(p0: Position, c: Position, p2: Position) -> Self {
+        Self::Cubic(p0, p0 + (c - p0) * (2.0 / 3.0), p2 + (c - p2) * (2.0 / 3.0), p2)
+    }
+
+    fn point(&self, t: f32) -> Position {
+        match *self {
+            Self::Line(p0, p1) => p0 + (p1 - p0) * t,
+            Self::Cubic(p0, p1, p2, p3) => {
+                let mt = 1.0 - t;
+                let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                Position {
+                    x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+                    y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+                }
+            }
+        }
+    }
+
+    fn derivative(&self, t: f32) -> Vector {
+        match *self {
+            Self::Line(p0, p1) => p1 - p0,
+            Self::Cubic(p0, p1, p2, p3) => {
+                let mt = 1.0 - t;
+                (p1 - p0) * (3.0 * mt * mt) + (p2 - p1) * (6.0 * mt * t) + (p3 - p2) * (3.0 * t * t)
+            }
+        }
+    }
+
+    /// Returns the arc length between `t0` and `t1`.
+    fn length(&self, t0: f32, t1: f32) -> f32 {
+        match *self {
+            Self::Line(p0, p1) => (p1 - p0).mag2().sqrt() * (t1 - t0),
+            Self::Cubic(..) => {
+                let half = (t1 - t0) * 0.5;
+                let mid = (t0 + t1) * 0.5;
+                GAUSS_LEGENDRE
+                    .iter()
+                    .map(|(x, w)| w * self.derivative(mid + half * x).mag2().sqrt())
+                    .sum::<f32>()
+                    * half
+            }
+        }
+    }
+
+    /// Returns the part of the curve between `t0` and `t1`.
+    fn split(&self, t0: f32, t1: f32) -> Self {
+        match *self {
+            Self::Line(..) => Self::Line(self.point(t0), self.point(t1)),
+            Self::Cubic(..) => {
+                // The control points follow from the derivatives at the ends of the part.
+                let scale = (t1 - t0) / 3.0;
+                let (p0, p3) = (self.point(t0), self.point(t1));
+                Self::Cubic(
+                    p0,
+                    p0 + self.derivative(t0) * scale,
+                    p3 - self.derivative(t1) * scale,
+                    p3,
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Segment {
+    curve: Curve,
+    // Distance of the start of the segment from the start of the contour.
+    offset: f32,
+    // Arc length from the start of the segment at the end of each interval, the last one being the length of the
+    // segment.
+    lengths: Vec<f32>,
+}
+
+impl Segment {
+    fn new(curve: Curve, offset: f32) -> Self {
+        let lengths = match curve {
+            Curve::Line(..) => vec![curve.length(0.0, 1.0)],
+            Curve::Cubic(..) => {
+                let mut length = 0.0;
+                (0..CUBIC_INTERVALS)
+                    .map(|i| {
+                        let t0 = i as f32 / CUBIC_INTERVALS as f32;
+                        length += curve.length(t0, t0 + 1.0 / CUBIC_INTERVALS as f32);
+                        length
+                    })
+                    .collect()
+            }
+        };
+
+        Self { curve, offset, lengths }
+    }
+
+    fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the curve parameter at the given distance from the start of the segment.
+    fn t_at(&self, distance: f32) -> f32 {
+        let length = self.length();
+        if distance <= 0.0 || length <= 0.0 {
+            return 0.0;
+        }
+        if distance >= length {
+            return 1.0;
+        }
+
+        let intervals = self.lengths.len() as f32;
+        let interval = self.lengths.partition_point(|len| *len < distance);
+        let interval_start = if interval == 0 { 0.0 } else { self.lengths[interval - 1] };
+        let t0 = interval as f32 / intervals;
+
+        // Interpolate within the interval and refine with Newton's method.
+        let fraction = (distance - interval_start) / (self.lengths[interval] - interval_start);
+        let mut t = t0 + fraction / intervals;
+
+        if let Curve::Cubic(..) = self.curve {
+            for _ in 0..3 {
+                let speed = self.curve.derivative(t).mag2().sqrt();
+                if speed <= f32::EPSILON {
+                    break;
+                }
+                let error = interval_start + self.curve.length(t0, t) - distance;
+                t = (t - error / speed).clamp(t0, t0 + 1.0 / intervals);
+            }
+        }
+
+        t
+    }
+
+    fn tangent_at(&self, t: f32) -> Vector {
+        let mut tangent = self.curve.derivative(t);
+
+        // The derivative vanishes where a control point coincides with an end point, look a little further in.
+        if tangent.mag2() <= f32::EPSILON {
+            tangent = self.curve.derivative(t.clamp(1e-3, 1.0 - 1e-3));
+        }
+        if tangent.mag2() <= f32::EPSILON {
+            if let Curve::Cubic(p0, _, _, p3) = self.curve {
+                tangent = p3 - p0;
+            }
+        }
+
+        tangent.normalize();
+        tangent
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Contour {
+    segments: Vec<Segment>,
+    length: f32,
+    closed: bool,
+}
+
+impl Contour {
+    fn push(&mut self, curve: Curve) {
+        let segment = Segment::new(curve, self.length);
+        if segment.length() > 0.0 {
+            self.length += segment.length();
+            self.segments.push(segment);
+        }
+    }
+
+    /// Returns the segment containing the given distance from the start of the contour.
+    fn segment_at(&self, distance: f32) -> &Segment {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.offset + segment.length() < distance);
+        &self.segments[index.min(self.segments.len() - 1)]
+    }
+}
+
+/// Measures the length of a [`Path`] and finds positions and directions along it, created by
+/// [`Path::measure`].
+///
+/// Distances are measured along the contours of the path, in the order in which they were added. A
+/// closed contour includes the line segment back to its first point. Like when drawing the path, segments
+/// added after closing a contour without moving continue it from its last point, before that line.
+#[derive(Clone, Debug)]
+pub struct PathMeasure {
+    contours: Vec<Contour>,
+    length: f32,
+}
+
+impl PathMeasure {
+    fn new(path: &Path) -> Self {
+        let mut contours = Vec::new();
+        let mut contour: Option<Contour> = None;
+        let mut start = Position::default();
+        let mut last = Position::default();
+
+        // A closed contour ends with the line from its last point back to its start, after all segments drawn
+        // after the close, like `PathCache` closes it.
+        let mut finish = |contour: &mut Option<Contour>, last: Position, start: Position| {
+            if let Some(mut contour) = contour.take() {
+                if contour.closed {
+                    contour.push(Curve::Line(last, start));
+                }
+                if !contour.segments.is_empty() {
+                    contours.push(contour);
+                }
+            }
+        };
+
+        for verb in path.verbs() {
+            match verb {
+                Verb::MoveTo(x, y) => {
+                    finish(&mut contour, last, start);
+                    start = Position { x, y };
+                    last = start;
+                    contour = Some(Contour {
+                        segments: Vec::new(),
+                        length: 0.0,
+                        closed: false,
+                    });
+                }
+                Verb::LineTo(x, y) => {
+                    let pos = Position { x, y };
+                    if let Some(contour) = &mut contour {
+                        contour.push(Curve::Line(last, pos));
+                    }
+                    last = pos;
+                }
+                Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                    let pos = Position { x, y };
+                    if let Some(contour) = &mut contour {
+                        let (c1, c2) = (Position { x: c1x, y: c1y }, Position { x: c2x, y: c2y });
+                        contour.push(Curve::Cubic(last, c1, c2, pos));
+                    }
+                    last = pos;
+                }
+                Verb::QuadTo(cx, cy, x, y) => {
+                    let pos = Position { x, y };
+                    if let Some(contour) = &mut contour {
+                        contour.push(Curve::from_quad(last, Position { x: cx, y: cy }, pos));
+                    }
+                    last = pos;
+                }
+                Verb::ConicTo(cx, cy, x, y, weight) => {
+                    let pos = Position { x, y };
+                    if let Some(contour) = &mut contour {
+                        // Conics are measured as quadratic bezier segments within the distance tolerance.
+                        let quads = conic_to_quads(last, Position { x: cx, y: cy }, pos, weight, path.dist_tol);
+                        let mut from = last;
+                        for (c, to) in quads {
+                            contour.push(Curve::from_quad(from, c, to));
+                            from = to;
+                        }
+                    }
+                    last = pos;
+                }
+                Verb::Close => {
+                    if let Some(contour) = &mut contour {
+                        contour.closed = true;
+                    }
+                }
+                Verb::Solid | Verb::Hole => {}
+            }
+        }
+
+        finish(&mut contour, last, start);
+
+        let length = contours.iter().map(|contour| contour.length).sum();
+
+        Self { contours, length }
+    }
+
+    /// Returns the total length of all contours of the path.
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Returns the length of each contour of the path. Contours without length are skipped.
+    pub fn contour_lengths(&self) -> impl Iterator<Item = f32> + '_ {
+        self.contours.iter().map(|contour| contour.length)
+    }
+
+    /// Returns the segment at the given distance from the start of the path, along with the distance from
+    /// the start of the segment.
+    fn segment_at(&self, distance: f32) -> Option<(&Segment, f32)> {
+        let mut distance = distance.clamp(0.0, self.length);
+
+        for (i, contour) in self.contours.iter().enumerate() {
+            if distance <= contour.length || i + 1 == self.contours.len() {
+                let segment = contour.segment_at(distance);
+                return Some((segment, distance - segment.offset));
+            }
+            distance -= contour.length;
+        }
+
+        None
+    }
+
+    /// Returns the point at the given distance from the start of the path, or `None` if the path has no
+    /// length. Distances outside of the path are clamped to its ends.
+    pub fn position_at(&self, distance: f32) -> Option<(f32, f32)> {
+        let (segment, distance) = self.segment_at(distance)?;
+        let pos = segment.curve.point(segment.t_at(distance));
+        Some((pos.x, pos.y))
+    }
+
+    /// Returns the unit length direction of the path at the given distance from its start, or `None` if
+    /// the path has no length. Distances outside of the path are clamped to its ends.
+    pub fn tangent_at(&self, distance: f32) -> Option<(f32, f32)> {
+        let (segment, distance) = self.segment_at(distance)?;
+        let tangent = segment.tangent_at(segment.t_at(distance));
+        Some((tangent.x, tangent.y))
+    }
+
+    /// Returns the part of the path between the distances `start` and `end` from its start, for example
+    /// to trim a stroke. Each contour within the range becomes a separate sub-path, a closed contour that
+    /// lies entirely in the range stays closed.
+    pub fn segment(&self, start: f32, end: f32) -> Path {
+        let mut path = Path::new();
+        let mut offset = 0.0;
+
+        for contour in &self.contours {
+            let (from, to) = (start - offset, end - offset);
+            offset += contour.length;
+
+            if to <= 0.0 || from >= contour.length || from >= to {
+                continue;
+            }
+
+            let mut first = true;
+
+            for segment in &contour.segments {
+                let (seg_from, seg_to) = (from - segment.offset, to - segment.offset);
+                if seg_to <= 0.0 || seg_from >= segment.length() {
+                    continue;
+                }
+
+                let t0 = segment.t_at(seg_from);
+                let t1 = segment.t_at(seg_to);
+                let curve = if t0 <= 0.0 && t1 >= 1.0 {
+                    segment.curve
+                } else {
+                    segment.curve.split(t0, t1)
+                };
+
+                let (Curve::Line(p0, _) | Curve::Cubic(p0, ..)) = curve;
+                if first {
+                    path.move_to(p0.x, p0.y);
+                    first = false;
+                }
+
+                match curve {
+                    Curve::Line(_, p1) => path.line_to(p1.x, p1.y),
+                    Curve::Cubic(_, c1, c2, p3) => path.bezier_to(c1.x, c1.y, c2.x, c2.y, p3.x, p3.y),
+                }
+            }
+
+            if contour.closed && from <= 0.0 && to >= contour.length {
+                path.close();
+            }
+        }
+
+        path
+    }
+}
+
+impl Path {
+    /// Returns a [`PathMeasure`] to measure the length of the path and find points along it.
+    ///
+    /// Bezier segments are measured directly rather than from a flattened approximation.
+    pub fn measure(&self) -> PathMeasure {
+        PathMeasure::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geometry::Transform2D, path::PathCache};
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(30.0, 0.0);
+        path.line_to(30.0, 40.0);
+
+        let measure = path.measure();
+        assert_eq!(measure.length(), 70.0);
+        assert_close(measure.position_at(10.0).unwrap(), (10.0, 0.0));
+        assert_close(measure.position_at(50.0).unwrap(), (30.0, 20.0));
+        assert_close(measure.tangent_at(50.0).unwrap(), (0.0, 1.0));
+        // Out of range distances are clamped.
+        assert_close(measure.position_at(-5.0).unwrap(), (0.0, 0.0));
+        assert_close(measure.position_at(100.0).unwrap(), (30.0, 40.0));
+
+        assert!(Path::new().measure().position_at(0.0).is_none());
+    }
+
+    #[test]
+    fn closed_contours() {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 10.0, 20.0);
+        path.move_to(100.0, 0.0);
+        path.line_to(110.0, 0.0);
+
+        let measure = path.measure();
+        assert_eq!(measure.contour_lengths().collect::<Vec<_>>(), [60.0, 10.0]);
+        assert_eq!(measure.length(), 70.0);
+
+        // The closing segment runs from (10, 0) back to the start.
+        assert_close(measure.position_at(55.0).unwrap(), (5.0, 0.0));
+        assert_close(measure.tangent_at(55.0).unwrap(), (-1.0, 0.0));
+        // The second contour.
+        assert_close(measure.position_at(65.0).unwrap(), (105.0, 0.0));
+    }
+
+    #[test]
+    fn segments_after_close() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(10.0, 0.0);
+        path.line_to(10.0, 10.0);
+        path.close();
+        path.line_to(20.0, 10.0);
+
+        // Like when drawing, the line continues from (10, 10) and the contour is closed from its end.
+        let measure = path.measure();
+        assert_eq!(measure.contour_lengths().count(), 1);
+        assert!((measure.length() - (30.0 + 500f32.sqrt())).abs() < 1e-4);
+        assert_close(measure.position_at(25.0).unwrap(), (15.0, 10.0));
+        assert_close(measure.position_at(measure.length()).unwrap(), (0.0, 0.0));
+
+        let cache = PathCache::new(path.verbs(), &Transform2D::identity(), 0.25, 0.01);
+        let drawn: f32 = cache
+            .contour_positions()
+            .map(|contour| {
+                let points: Vec<_> = contour.collect();
+                (0..points.len())
+                    .map(|i| (points[(i + 1) % points.len()] - points[i]).mag2().sqrt())
+                    .sum::<f32>()
+            })
+            .sum();
+        assert!((measure.length() - drawn).abs() < 1e-4);
+    }
+
+    #[test]
+    fn curves() {
+        let mut path = Path::new();
+        path.circle(0.0, 0.0, 10.0);
+
+        let measure = path.measure();
+        let circumference = 2.0 * std::f32::consts::PI * 10.0;
+        // The bezier approximation of a circle is slightly longer.
+        assert!((measure.length() - circumference).abs() < circumference * 1e-3);
+
+        // A quarter of the way around the circle, which starts on the left and runs through the bottom.
+        assert_close(measure.position_at(measure.length() * 0.25).unwrap(), (0.0, 10.0));
+        assert_close(measure.tangent_at(measure.length() * 0.25).unwrap(), (1.0, 0.0));
+
+        let (x, y) = measure.position_at(measure.length() * 0.125).unwrap();
+        assert!((x.hypot(y) - 10.0).abs() < 0.01);
+        assert!((x + 7.071).abs() < 0.05 && (y - 7.071).abs() < 0.05);
+
+        // A straight line as a bezier with unevenly spaced control points.
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.bezier_to(0.0, 0.0, 1.0, 0.0, 10.0, 0.0);
+        let measure = path.measure();
+        assert!((measure.length() - 10.0).abs() < 1e-3);
+        assert_close(measure.position_at(2.5).unwrap(), (2.5, 0.0));
+        assert_close(measure.tangent_at(0.0).unwrap(), (1.0, 0.0));
+    }
+
+    #[test]
+    fn segment() {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 10.0, 10.0);
+        path.move_to(0.0, 20.0);
+        path.bezier_to(10.0, 30.0, 20.0, 30.0, 30.0, 20.0);
+
+        let measure = path.measure();
+        let lengths: Vec<_> = measure.contour_lengths().collect();
+
+        // The whole path is reproduced, with the rectangle still closed.
+        let whole = measure.segment(0.0, measure.length());
+        assert_eq!(whole.verbs().filter(|verb| matches!(verb, Verb::Close)).count(), 1);
+        assert!((whole.measure().length() - measure.length()).abs() < 1e-3);
+
+        // From the middle of the rectangle's second side into the middle of the curve.
+        let part = measure.segment(15.0, lengths[0] + lengths[1] * 0.5);
+        let part_measure = part.measure();
+        assert!((part_measure.length() - (25.0 + lengths[1] * 0.5)).abs() < 1e-2);
+        assert_close(part_measure.position_at(0.0).unwrap(), (5.0, 10.0));
+        assert_close(part_measure.position_at(part_measure.length()).unwrap(), (15.0, 27.5));
+        assert_eq!(part.verbs().filter(|verb| matches!(verb, Verb::MoveTo(..))).count(), 2);
+        assert_eq!(part.verbs().filter(|verb| matches!(verb, Verb::Close)).count(), 0);
+
+        assert!(measure.segment(10.0, 5.0).is_empty());
+    }
+}
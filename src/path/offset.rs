@@ -0,0 +1,140 @@
+use super::{
+    ops::{combine, Point},
+    stroke::strip_polygons,
+    BooleanOp, Path, PathCache, TESS_TOL,
+};
+use crate::{geometry::Transform2D, FillRule, LineCap, LineJoin};
+
+impl Path {
+    /// Returns the outline of the path grown by `distance`, or shrunk if `distance` is negative.
+    ///
+    /// Closed contours are filled with the non-zero fill rule and the outline of that area is moved
+    /// outwards or inwards. Where it moves away from a corner, the corner is filled in with `join`,
+    /// limited by `miter_limit` like the joins of strokes. Open contours are outlined at `distance` on
+    /// both sides with butt ends, no matter the sign of `distance`.
+    ///
+    /// Curves are flattened first, so the result consists of straight line segments only. Like the
+    /// result of [`Path::boolean_op`], it doesn't overlap itself, so parts that shrink away or grow
+    /// into each other are handled cleanly.
+    pub fn offset(&self, distance: f32, join: LineJoin, miter_limit: f32) -> Self {
+        let mut cache = PathCache::new(self.verbs(), &Transform2D::identity(), TESS_TOL, self.dist_tol);
+        let closed: Vec<bool> = cache.contours_closed().collect();
+
+        let area: Vec<Vec<Point>> = cache
+            .contour_positions()
+            .zip(&closed)
+            .filter(|(_, closed)| **closed)
+            .map(|(contour, _)| contour.map(|pos| [pos.x as f64, pos.y as f64]).collect())
+            .collect();
+
+        if distance == 0.0 {
+            return combine([area, Vec::new()], BooleanOp::Union, FillRule::NonZero);
+        }
+
+        // Stroking the contours with twice the distance covers everything within the distance of them.
+        cache.expand_stroke(
+            distance.abs(),
+            0.0,
+            LineCap::Butt,
+            LineCap::Butt,
+            join,
+            miter_limit,
+            TESS_TOL,
+        );
+
+        let (mut closed_strokes, mut open_strokes) = (Vec::new(), Vec::new());
+        for (strip, closed) in cache.stroke_strips().zip(&closed) {
+            let strokes = if *closed {
+                &mut closed_strokes
+            } else {
+                &mut open_strokes
+            };
+            strokes.extend(strip_polygons(strip));
+        }
+
+        // The contours may wind either way while the strokes always wind the same way, so they are kept
+        // apart to not cancel out.
+        if distance > 0.0 {
+            closed_strokes.extend(open_strokes);
+            return combine([area, closed_strokes], BooleanOp::Union, FillRule::NonZero);
+        }
+
+        let inset = combine([area, closed_strokes], BooleanOp::Difference, FillRule::NonZero);
+
+        if open_strokes.is_empty() {
+            inset
+        } else {
+            let outlines = combine([open_strokes, Vec::new()], BooleanOp::Union, FillRule::NonZero);
+            inset.boolean_op(&outlines, BooleanOp::Union, FillRule::NonZero)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::ops::tests::{area, contour_count};
+
+    fn square() -> Path {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 100.0, 100.0);
+        path
+    }
+
+    #[test]
+    fn outset() {
+        let miter = square().offset(10.0, LineJoin::Miter, 10.0);
+        assert!((area(&miter) - 120.0 * 120.0).abs() < 0.01);
+        assert_eq!(contour_count(&miter), 1);
+
+        let bevel = square().offset(10.0, LineJoin::Bevel, 10.0);
+        assert!((area(&bevel) - (120.0 * 120.0 - 4.0 * 50.0)).abs() < 0.01);
+
+        // Round corners are flattened, so they cover a little less than quarter circles.
+        let round = square().offset(10.0, LineJoin::Round, 10.0);
+        let expected = 100.0 * 100.0 + 4.0 * 100.0 * 10.0 + std::f32::consts::PI * 100.0;
+        assert!(area(&round) < expected && area(&round) > 0.99 * expected);
+    }
+
+    #[test]
+    fn inset() {
+        let inset = square().offset(-10.0, LineJoin::Round, 10.0);
+        assert!((area(&inset) - 80.0 * 80.0).abs() < 0.01);
+
+        // Shrinking by more than half the size leaves nothing.
+        assert_eq!(contour_count(&square().offset(-60.0, LineJoin::Miter, 10.0)), 0);
+    }
+
+    #[test]
+    fn ring() {
+        let mut path = square();
+        path.rect(30.0, 30.0, 40.0, 40.0);
+        path.solidity(crate::Solidity::Hole);
+
+        // Growing the ring shrinks its hole, until it closes.
+        let outset = path.offset(10.0, LineJoin::Miter, 10.0);
+        assert!((area(&outset) - (120.0 * 120.0 - 20.0 * 20.0)).abs() < 0.01);
+        assert_eq!(contour_count(&outset), 2);
+
+        let closed = path.offset(25.0, LineJoin::Miter, 10.0);
+        assert_eq!(contour_count(&closed), 1);
+
+        let inset = path.offset(-10.0, LineJoin::Miter, 10.0);
+        assert!((area(&inset) - (80.0 * 80.0 - 60.0 * 60.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn open_contour() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(100.0, 0.0);
+        path.line_to(100.0, 100.0);
+
+        // Both signs outline the line on both sides.
+        for distance in [5.0, -5.0] {
+            let outline = path.offset(distance, LineJoin::Miter, 10.0);
+            assert!((area(&outline) - (2.0 * 100.0 * 10.0)).abs() < 0.01);
+            assert_eq!(contour_count(&outline), 1);
+        }
+    }
+}
@@ -0,0 +1,665 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Path, PathCache, TESS_TOL};
+use crate::{geometry::Transform2D, FillRule, Solidity};
+
+/// A boolean operation combining the areas of two paths, see [`Path::boolean_op`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BooleanOp {
+    /// The area covered by either path.
+    Union,
+    /// The area covered by both paths.
+    Intersection,
+    /// The area covered by the first path but not by the second one.
+    Difference,
+    /// The area covered by exactly one of the paths.
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::Xor => a != b,
+        }
+    }
+}
+
+pub(super) type Point = [f64; 2];
+
+fn sub(a: Point, b: Point) -> Point {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn cross(a: Point, b: Point) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+#[derive(Copy, Clone)]
+struct Edge {
+    from: Point,
+    to: Point,
+    operand: usize,
+}
+
+impl Path {
+    /// Combines the area of this path with the area of `other`, returning the outline of the result.
+    ///
+    /// The interior of both paths is determined with `fill_rule`. Curves are flattened first, so the
+    /// result consists of straight line segments only. Its contours don't overlap each other and
+    /// are marked with [`Solidity::Solid`] or [`Solidity::Hole`], so the result fills correctly with
+    /// either fill rule.
+    pub fn boolean_op(&self, other: &Self, op: BooleanOp, fill_rule: FillRule) -> Self {
+        let [a, b] = [self, other].map(|path| {
+            let cache = PathCache::new(path.verbs(), &Transform2D::identity(), TESS_TOL, path.dist_tol);
+            cache
+                .contour_positions()
+                .map(|contour| contour.map(|pos| [pos.x as f64, pos.y as f64]).collect())
+                .collect()
+        });
+
+        combine([a, b], op, fill_rule)
+    }
+}
+
+/// Combines the areas of two sets of polygons, which are implicitly closed.
+pub(super) fn combine(operands: [Vec<Vec<Point>>; 2], op: BooleanOp, fill_rule: FillRule) -> Path {
+    let mut edges = Vec::new();
+
+    for (operand, polygons) in operands.iter().enumerate() {
+        for points in polygons {
+            for (i, &from) in points.iter().enumerate() {
+                let to = points[(i + 1) % points.len()];
+                if from != to {
+                    edges.push(Edge { from, to, operand });
+                }
+            }
+        }
+    }
+
+    // Tolerances are relative to the magnitude of the coordinates.
+    let extent = edges
+        .iter()
+        .flat_map(|edge| [edge.from, edge.to])
+        .fold(1.0f64, |extent, p| extent.max(p[0].abs()).max(p[1].abs()));
+
+    let edges = split_edges(&edges, extent * 1e-7);
+    let grid = Grid::new(&edges);
+
+    let inside = |winding: i32| match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    };
+    let inside_at = |[a, b]: [i32; 2]| op.apply(inside(a), inside(b));
+
+    // Keep the edges separating the inside of the result from the outside, oriented to have the
+    // inside on their left.
+    let eps = extent * 1e-6;
+
+    let mut seen = HashSet::new();
+    let mut boundary = Vec::new();
+
+    for edge in &edges {
+        let d = sub(edge.to, edge.from);
+        let len = dot(d, d).sqrt();
+        let normal = [-d[1] / len * eps, d[0] / len * eps];
+        let mid = [(edge.from[0] + edge.to[0]) * 0.5, (edge.from[1] + edge.to[1]) * 0.5];
+
+        let sides = [
+            [mid[0] + normal[0], mid[1] + normal[1]],
+            [mid[0] - normal[0], mid[1] - normal[1]],
+        ];
+        let [left, right] = if grid.cell(sides[0]) == grid.cell(sides[1]) {
+            grid.winding_numbers(&edges, sides)
+        } else {
+            sides.map(|p| grid.winding_numbers(&edges, [p])[0])
+        }
+        .map(inside_at);
+
+        if left == right {
+            continue;
+        }
+
+        let (from, to) = if left {
+            (edge.from, edge.to)
+        } else {
+            (edge.to, edge.from)
+        };
+
+        // Coincident edges are only needed once.
+        let key = [from, to].map(|p| p.map(f64::to_bits));
+        if seen.insert(key) {
+            boundary.push((from, to));
+        }
+    }
+
+    let mut result = Path::new();
+
+    for contour in chain_contours(&boundary) {
+        let contour = remove_collinear(contour);
+        if contour.len() < 3 {
+            continue;
+        }
+
+        let area: f64 = (0..contour.len())
+            .map(|i| cross(contour[i], contour[(i + 1) % contour.len()]))
+            .sum();
+
+        result.move_to(contour[0][0] as f32, contour[0][1] as f32);
+        for p in &contour[1..] {
+            result.line_to(p[0] as f32, p[1] as f32);
+        }
+        result.close();
+        // With the inside on the left, outer contours run counter-clockwise (in y-up coordinates).
+        result.solidity(if area > 0.0 { Solidity::Solid } else { Solidity::Hole });
+    }
+
+    result
+}
+
+/// Sorts edges into the cells of a grid covering them, so that only edges close to each other need to be tested
+/// against each other.
+struct Grid {
+    min: Point,
+    cell_size: f64,
+    dims: [usize; 2],
+    /// The columns and rows of the cells covered by the bounds of each edge.
+    ranges: Vec<[[usize; 2]; 2]>,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(edges: &[Edge]) -> Self {
+        let (min, max) =
+            edges
+                .iter()
+                .flat_map(|edge| [edge.from, edge.to])
+                .fold(([f64::MAX; 2], [f64::MIN; 2]), |(min, max), p| {
+                    (
+                        [min[0].min(p[0]), min[1].min(p[1])],
+                        [max[0].max(p[0]), max[1].max(p[1])],
+                    )
+                });
+        let size = [max[0] - min[0], max[1] - min[1]];
+
+        // Cells about the size of an average edge, but not many more of them than there are edges.
+        let mean_length = edges
+            .iter()
+            .map(|edge| (edge.to[0] - edge.from[0]).abs().max((edge.to[1] - edge.from[1]).abs()))
+            .sum::<f64>()
+            / edges.len().max(1) as f64;
+        let cell_size = mean_length
+            .max((size[0] * size[1] / (4 * edges.len().max(1)) as f64).sqrt())
+            .max(size[0].max(size[1]) / 4096.0)
+            .max(f64::MIN_POSITIVE);
+        let dims = size.map(|size| (size / cell_size) as usize + 1);
+
+        let mut grid = Self {
+            min,
+            cell_size,
+            dims,
+            ranges: Vec::with_capacity(edges.len()),
+            cells: vec![Vec::new(); dims[0] * dims[1]],
+        };
+
+        for (i, edge) in edges.iter().enumerate() {
+            let range = [0, 1].map(|axis| {
+                let (a, b) = (grid.index(edge.from, axis), grid.index(edge.to, axis));
+                [a.min(b), a.max(b)]
+            });
+            for row in range[1][0]..=range[1][1] {
+                for column in range[0][0]..=range[0][1] {
+                    grid.cells[row * dims[0] + column].push(i);
+                }
+            }
+            grid.ranges.push(range);
+        }
+
+        grid
+    }
+
+    fn index(&self, p: Point, axis: usize) -> usize {
+        // Negative values saturate to zero.
+        (((p[axis] - self.min[axis]) / self.cell_size) as usize).min(self.dims[axis] - 1)
+    }
+
+    fn cell(&self, p: Point) -> [usize; 2] {
+        [self.index(p, 0), self.index(p, 1)]
+    }
+
+    /// Returns the winding numbers of the edges of both operands around each of the points, which must lie
+    /// in the same cell.
+    fn winding_numbers<const N: usize>(&self, edges: &[Edge], points: [Point; N]) -> [[i32; 2]; N] {
+        let cell = self.cell(points[0]);
+
+        // Count the edges crossing rays from the points in the direction leaving the grid through the
+        // fewest cells, rotating everything so that the rays point to the right.
+        let (axis, forward) = [(0, true), (0, false), (1, true), (1, false)]
+            .into_iter()
+            .min_by_key(|&(axis, forward)| {
+                if forward {
+                    self.dims[axis] - cell[axis]
+                } else {
+                    cell[axis] + 1
+                }
+            })
+            .unwrap();
+        let rotate: fn(Point) -> Point = match (axis, forward) {
+            (0, true) => |p| p,
+            (0, false) => |p| [-p[0], -p[1]],
+            (_, true) => |p| [p[1], -p[0]],
+            (_, false) => |p| [-p[1], p[0]],
+        };
+
+        let steps: Box<dyn Iterator<Item = usize>> = if forward {
+            Box::new(cell[axis]..self.dims[axis])
+        } else {
+            Box::new((0..=cell[axis]).rev())
+        };
+
+        let points = points.map(rotate);
+        let mut windings = [[0; 2]; N];
+
+        for step in steps {
+            let mut visited = cell;
+            visited[axis] = step;
+
+            for &i in &self.cells[visited[1] * self.dims[0] + visited[0]] {
+                // Edges covering several cells are counted in the first one the rays pass.
+                let [start, end] = self.ranges[i][axis];
+                let first = if forward {
+                    start.max(cell[axis])
+                } else {
+                    end.min(cell[axis])
+                };
+                if first != step {
+                    continue;
+                }
+
+                let edge = &edges[i];
+                let (a, b) = (rotate(edge.from), rotate(edge.to));
+
+                for (p, winding) in points.iter().zip(&mut windings) {
+                    let is_left = cross(sub(b, a), sub(*p, a));
+
+                    if a[1] <= p[1] {
+                        if b[1] > p[1] && is_left > 0.0 {
+                            winding[edge.operand] += 1;
+                        }
+                    } else if b[1] <= p[1] && is_left < 0.0 {
+                        winding[edge.operand] -= 1;
+                    }
+                }
+            }
+        }
+
+        windings
+    }
+}
+
+/// Splits the edges at all their intersections with each other, so that edges only meet at their ends.
+///
+/// Intersections of nearly parallel edges are imprecise, so points closer than `tolerance` to a previous
+/// one are merged with it.
+fn split_edges(edges: &[Edge], tolerance: f64) -> Vec<Edge> {
+    // Split points for each edge, as parameter along the edge and the point itself. Intersections are computed
+    // once and shared by both edges, so that they end up with identical end points.
+    let mut splits: Vec<Vec<(f64, Point)>> = vec![Vec::new(); edges.len()];
+
+    let project = |edge: &Edge, p: Point| {
+        let d = sub(edge.to, edge.from);
+        dot(sub(p, edge.from), d) / dot(d, d)
+    };
+
+    let grid = Grid::new(edges);
+
+    for (cell, indices) in grid.cells.iter().enumerate() {
+        for (k, &i) in indices.iter().enumerate() {
+            let (a, b) = (edges[i].from, edges[i].to);
+            let r = sub(b, a);
+
+            for &j in &indices[k + 1..] {
+                // Test each pair only in the first cell they share.
+                let ([columns_i, rows_i], [columns_j, rows_j]) = (grid.ranges[i], grid.ranges[j]);
+                let first_shared = rows_i[0].max(rows_j[0]) * grid.dims[0] + columns_i[0].max(columns_j[0]);
+                if first_shared != cell {
+                    continue;
+                }
+
+                let (c, d) = (edges[j].from, edges[j].to);
+
+                if a[0].max(b[0]) < c[0].min(d[0])
+                    || c[0].max(d[0]) < a[0].min(b[0])
+                    || a[1].max(b[1]) < c[1].min(d[1])
+                    || c[1].max(d[1]) < a[1].min(b[1])
+                {
+                    continue;
+                }
+
+                let s = sub(d, c);
+                let denom = cross(r, s);
+                let ca = sub(c, a);
+
+                if denom.abs() > 1e-12 * dot(r, r).sqrt() * dot(s, s).sqrt() {
+                    let t = cross(ca, s) / denom;
+                    let u = cross(ca, r) / denom;
+
+                    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                        // Prefer exact end points over computed intersections.
+                        let p = match (t, u) {
+                            (0.0, _) => a,
+                            (1.0, _) => b,
+                            (_, 0.0) => c,
+                            (_, 1.0) => d,
+                            _ => [a[0] + r[0] * t, a[1] + r[1] * t],
+                        };
+                        splits[i].push((t, p));
+                        splits[j].push((u, p));
+                    }
+                } else if cross(ca, r).abs() <= 1e-12 * dot(r, r) {
+                    // Collinear edges overlap where their end points lie on each other.
+                    for p in [c, d] {
+                        let t = project(&edges[i], p);
+                        if t > 0.0 && t < 1.0 {
+                            splits[i].push((t, p));
+                        }
+                    }
+                    for p in [a, b] {
+                        let u = project(&edges[j], p);
+                        if u > 0.0 && u < 1.0 {
+                            splits[j].push((u, p));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut welder = Welder::new(tolerance);
+
+    // The end points of the edges take precedence over the computed intersections.
+    for edge in edges {
+        welder.weld(edge.from);
+        welder.weld(edge.to);
+    }
+
+    let mut result = Vec::with_capacity(edges.len());
+
+    for (edge, mut points) in edges.iter().zip(splits) {
+        points.retain(|(t, _)| *t > 0.0 && *t < 1.0);
+        points.sort_by(|(t0, _), (t1, _)| t0.total_cmp(t1));
+
+        let mut from = welder.weld(edge.from);
+        for to in points.into_iter().map(|(_, p)| p).chain([edge.to]) {
+            let to = welder.weld(to);
+            if from != to {
+                result.push(Edge {
+                    from,
+                    to,
+                    operand: edge.operand,
+                });
+                from = to;
+            }
+        }
+    }
+
+    result
+}
+
+/// Merges points lying within a tolerance of each other.
+struct Welder {
+    tolerance: f64,
+    cells: HashMap<[i64; 2], Vec<Point>>,
+}
+
+impl Welder {
+    fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the first point welded that lies within the tolerance of `p`, or `p` itself.
+    fn weld(&mut self, p: Point) -> Point {
+        let cell = p.map(|v| (v / self.tolerance).floor() as i64);
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let neighbor = self.cells.get(&[cell[0] + dx, cell[1] + dy]);
+                if let Some(&q) = neighbor
+                    .into_iter()
+                    .flatten()
+                    .find(|q| (q[0] - p[0]).hypot(q[1] - p[1]) <= self.tolerance)
+                {
+                    return q;
+                }
+            }
+        }
+
+        self.cells.entry(cell).or_default().push(p);
+        p
+    }
+}
+
+/// Links the directed boundary edges into closed contours.
+fn chain_contours(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let key = |p: Point| p.map(f64::to_bits);
+
+    let mut outgoing: HashMap<[u64; 2], Vec<usize>> = HashMap::new();
+    for (i, (from, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*from)).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+
+        let (start, mut current) = edges[first];
+        let mut direction = sub(current, start);
+        let mut contour = vec![start];
+
+        while current != start {
+            contour.push(current);
+
+            // Where several contours touch, continue with the edge turning right the most, which is the
+            // next edge around the inside on the left.
+            let back = [-direction[0], -direction[1]];
+            let clockwise_angle = |i: &usize| {
+                let out = sub(edges[*i].1, edges[*i].0);
+                let angle = -cross(back, out).atan2(dot(back, out));
+                if angle <= 0.0 {
+                    angle + std::f64::consts::TAU
+                } else {
+                    angle
+                }
+            };
+
+            let Some(next) = outgoing
+                .get(&key(current))
+                .and_then(|candidates| {
+                    candidates
+                        .iter()
+                        .filter(|i| !used[**i])
+                        .min_by(|i0, i1| clockwise_angle(i0).total_cmp(&clockwise_angle(i1)))
+                })
+                .copied()
+            else {
+                break;
+            };
+
+            used[next] = true;
+            direction = sub(edges[next].1, edges[next].0);
+            current = edges[next].1;
+        }
+
+        contours.push(contour);
+    }
+
+    contours
+}
+
+/// Removes points that lie on the straight line between their neighbours.
+fn remove_collinear(mut contour: Vec<Point>) -> Vec<Point> {
+    let mut i = 0;
+
+    while contour.len() >= 3 && i < contour.len() {
+        let prev = contour[(i + contour.len() - 1) % contour.len()];
+        let next = contour[(i + 1) % contour.len()];
+        let (d0, d1) = (sub(contour[i], prev), sub(next, contour[i]));
+
+        if cross(d0, d1).abs() <= 1e-9 * dot(d0, d0).sqrt() * dot(d1, d1).sqrt() && dot(d0, d1) > 0.0 {
+            contour.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+
+    contour
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::Verb;
+
+    /// Returns the filled area of the path, counting holes negative.
+    pub(in crate::path) fn area(path: &Path) -> f32 {
+        let mut area = 0.0;
+        let mut contour = Vec::new();
+
+        for verb in path.verbs() {
+            match verb {
+                Verb::MoveTo(x, y) => {
+                    contour.clear();
+                    contour.push((x, y));
+                }
+                Verb::LineTo(x, y) => contour.push((x, y)),
+                Verb::Solid | Verb::Hole => {
+                    let signed: f32 = (0..contour.len())
+                        .map(|i| {
+                            let ((x0, y0), (x1, y1)) = (contour[i], contour[(i + 1) % contour.len()]);
+                            x0 * y1 - x1 * y0
+                        })
+                        .sum::<f32>()
+                        .abs()
+                        * 0.5;
+                    area += if matches!(verb, Verb::Solid) { signed } else { -signed };
+                }
+                _ => {}
+            }
+        }
+
+        area
+    }
+
+    pub(in crate::path) fn contour_count(path: &Path) -> usize {
+        path.verbs().filter(|verb| matches!(verb, Verb::MoveTo(..))).count()
+    }
+
+    fn squares() -> (Path, Path) {
+        let mut a = Path::new();
+        a.rect(0.0, 0.0, 20.0, 20.0);
+        let mut b = Path::new();
+        b.rect(10.0, 10.0, 20.0, 20.0);
+        (a, b)
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let (a, b) = squares();
+
+        let union = a.boolean_op(&b, BooleanOp::Union, FillRule::NonZero);
+        assert_eq!(area(&union), 700.0);
+        assert_eq!(contour_count(&union), 1);
+        // The union is an octagon-like shape with 8 corners.
+        assert_eq!(union.verbs().filter(|verb| matches!(verb, Verb::LineTo(..))).count(), 7);
+
+        let intersection = a.boolean_op(&b, BooleanOp::Intersection, FillRule::NonZero);
+        assert_eq!(area(&intersection), 100.0);
+        assert_eq!(contour_count(&intersection), 1);
+
+        let difference = a.boolean_op(&b, BooleanOp::Difference, FillRule::NonZero);
+        assert_eq!(area(&difference), 300.0);
+
+        let xor = a.boolean_op(&b, BooleanOp::Xor, FillRule::NonZero);
+        assert_eq!(area(&xor), 600.0);
+        // The two L-shapes touch at two corners.
+        assert_eq!(contour_count(&xor), 2);
+    }
+
+    #[test]
+    fn difference_with_hole() {
+        let mut a = Path::new();
+        a.rect(0.0, 0.0, 30.0, 30.0);
+        let mut b = Path::new();
+        b.rect(10.0, 10.0, 10.0, 10.0);
+
+        let difference = a.boolean_op(&b, BooleanOp::Difference, FillRule::NonZero);
+        assert_eq!(area(&difference), 800.0);
+        assert_eq!(contour_count(&difference), 2);
+        assert_eq!(difference.verbs().filter(|verb| matches!(verb, Verb::Hole)).count(), 1);
+
+        assert!(a
+            .boolean_op(&b, BooleanOp::Difference, FillRule::NonZero)
+            .boolean_op(&a, BooleanOp::Intersection, FillRule::NonZero)
+            .verbs()
+            .any(|verb| matches!(verb, Verb::Hole)));
+    }
+
+    #[test]
+    fn shared_edges() {
+        let mut a = Path::new();
+        a.rect(0.0, 0.0, 10.0, 10.0);
+        let mut b = Path::new();
+        b.rect(10.0, 0.0, 10.0, 10.0);
+
+        let union = a.boolean_op(&b, BooleanOp::Union, FillRule::NonZero);
+        assert_eq!(area(&union), 200.0);
+        assert_eq!(union.verbs().filter(|verb| matches!(verb, Verb::LineTo(..))).count(), 3);
+
+        assert!(a.boolean_op(&b, BooleanOp::Intersection, FillRule::NonZero).is_empty());
+        assert!(a.boolean_op(&a, BooleanOp::Xor, FillRule::NonZero).is_empty());
+    }
+
+    #[test]
+    fn fill_rules() {
+        // Two nested squares with the same winding.
+        let mut a = Path::new();
+        a.rect(0.0, 0.0, 30.0, 30.0);
+        a.rect(10.0, 10.0, 10.0, 10.0);
+        let empty = Path::new();
+
+        let non_zero = a.boolean_op(&empty, BooleanOp::Union, FillRule::NonZero);
+        assert_eq!(area(&non_zero), 900.0);
+
+        let even_odd = a.boolean_op(&empty, BooleanOp::Union, FillRule::EvenOdd);
+        assert_eq!(area(&even_odd), 800.0);
+    }
+
+    #[test]
+    fn curves() {
+        let mut a = Path::new();
+        a.circle(0.0, 0.0, 10.0);
+        let mut b = Path::new();
+        b.rect(0.0, -20.0, 20.0, 40.0);
+
+        let half = a.boolean_op(&b, BooleanOp::Difference, FillRule::NonZero);
+        let expected = std::f32::consts::PI * 50.0;
+        // Flattening cuts off a little of the curved side.
+        assert!(area(&half) < expected && area(&half) > expected * 0.97);
+    }
+}
@@ -0,0 +1,445 @@
+use super::{Path, Verb};
+use crate::geometry::{Position, Vector};
+
+// Polylines turning by more than this angle at a point get a corner there when fitting curves (cos 60°).
+const CORNER_COS: f32 = 0.5;
+
+// Number of Newton iterations to improve the parameters of the points before splitting a curve fit.
+const FIT_ITERATIONS: usize = 4;
+
+impl Path {
+    /// Returns a copy of the path with runs of line segments reduced to fewer points, using the
+    /// Ramer-Douglas-Peucker algorithm.
+    ///
+    /// No point of the original lines is further than `tolerance` from the simplified ones. Curves
+    /// and the points they connect to are kept as they are.
+    pub fn simplify(&self, tolerance: f32) -> Self {
+        map_polylines(self, |points, closed, path| {
+            let mut keep = vec![false; points.len()];
+            keep[0] = true;
+            keep[points.len() - 1] = true;
+            mark_farthest(points, tolerance * tolerance, &mut keep);
+
+            // Closing the sub-path adds the last segment back to its start.
+            let end = points.len() - usize::from(closed);
+            for (point, _) in points[..end].iter().zip(keep).skip(1).filter(|(_, keep)| *keep) {
+                path.line_to(point.x, point.y);
+            }
+        })
+    }
+
+    /// Returns a copy of the path with runs of line segments replaced by smooth cubic bezier curves,
+    /// for example to turn the points of a freehand stroke into a compact path.
+    ///
+    /// The curves pass within `tolerance` of the original points. Where the lines turn sharply, the
+    /// curves keep a corner. Single line segments stay straight and existing curves are kept as they
+    /// are. Noisy input is best simplified with [`Path::simplify`] first.
+    pub fn fit_curves(&self, tolerance: f32) -> Self {
+        map_polylines(self, |points, closed, path| {
+            let corner = |i: usize, prev: usize, next: usize| {
+                let (mut a, mut b) = (points[i] - points[prev], points[next] - points[i]);
+                a.normalize();
+                b.normalize();
+                a.dot(b) < CORNER_COS
+            };
+
+            // A closed polyline continues smoothly through its first point unless there's a corner.
+            let last = points.len() - 1;
+            let smooth_start = (closed && !corner(0, last - 1, 1)).then(|| {
+                let mut tangent = points[1] - points[last - 1];
+                tangent.normalize();
+                tangent
+            });
+
+            let mut start = 0;
+            for end in 1..=last {
+                if end < last && !corner(end, end - 1, end + 1) {
+                    continue;
+                }
+
+                let piece = &points[start..=end];
+                if piece.len() == 2 {
+                    // Closing the sub-path adds the last segment back to its start.
+                    if !(closed && end == last) {
+                        path.line_to(piece[1].x, piece[1].y);
+                    }
+                } else {
+                    let mut tangents = [piece[1] - piece[0], piece[piece.len() - 2] - piece[piece.len() - 1]];
+                    for tangent in &mut tangents {
+                        tangent.normalize();
+                    }
+                    if let Some(tangent) = smooth_start {
+                        if start == 0 {
+                            tangents[0] = tangent;
+                        }
+                        if end == last {
+                            tangents[1] = tangent * -1.0;
+                        }
+                    }
+
+                    fit_cubics(piece, tangents, tolerance * tolerance, path);
+                }
+
+                start = end;
+            }
+        })
+    }
+}
+
+/// Copies the verbs of the path, passing the points of each run of line segments, starting with the point
+/// before the first segment, to `polyline` to add the segments in their place. The run of a closed sub-path
+/// of only lines ends with its first point again and is flagged as closed.
+fn map_polylines(path: &Path, mut polyline: impl FnMut(&[Position], bool, &mut Path)) -> Path {
+    let mut result = Path::new();
+    result.set_distance_tolerance(path.dist_tol);
+
+    let mut points: Vec<Position> = Vec::new();
+    let mut start = Position::default();
+    // Whether the current run began at the start of the sub-path.
+    let mut from_start = true;
+
+    let mut flush = |points: &mut Vec<Position>, closed: bool, result: &mut Path| {
+        // Consecutive duplicate points would have no direction.
+        points.dedup_by(|b, a| Position::equals(*a, *b, 1e-6));
+        if points.len() >= 2 {
+            polyline(points, closed && points.len() > 2, result);
+        }
+        points.clear();
+    };
+
+    for verb in path.verbs() {
+        match verb {
+            Verb::MoveTo(x, y) => {
+                flush(&mut points, false, &mut result);
+                start = Position { x, y };
+                from_start = true;
+                points.push(start);
+                result.move_to(x, y);
+                continue;
+            }
+            Verb::LineTo(x, y) => {
+                if points.is_empty() {
+                    points.push(result.last_pos);
+                }
+                points.push(Position { x, y });
+                continue;
+            }
+            Verb::Close => {
+                let closed = from_start && !points.is_empty();
+                if closed {
+                    points.push(start);
+                }
+                flush(&mut points, closed, &mut result);
+            }
+            _ => flush(&mut points, false, &mut result),
+        }
+
+        result.push_verb(verb);
+        from_start = false;
+    }
+
+    flush(&mut points, false, &mut result);
+
+    result
+}
+
+/// Marks the point farthest from the line between the first and last point to be kept, if it's further than
+/// the square root of `tolerance2`, and continues with both halves.
+fn mark_farthest(points: &[Position], tolerance2: f32, keep: &mut [bool]) {
+    if points.len() < 3 {
+        return;
+    }
+
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (index, distance2) = points[1..points.len() - 1]
+        .iter()
+        .map(|point| Position::segment_distance(*point, first, last))
+        .enumerate()
+        .fold((0, 0.0), |max, (i, d)| if d > max.1 { (i + 1, d) } else { max });
+
+    if distance2 > tolerance2 {
+        keep[index] = true;
+        mark_farthest(&points[..=index], tolerance2, &mut keep[..=index]);
+        mark_farthest(&points[index..], tolerance2, &mut keep[index..]);
+    }
+}
+
+/// Fits cubic bezier curves to the points, leaving the first and last point in the direction of the unit
+/// length `tangents`, using the algorithm by Philip J. Schneider from Graphics Gems.
+fn fit_cubics(points: &[Position], tangents: [Vector; 2], tolerance2: f32, path: &mut Path) {
+    let mut params = chord_length_params(points);
+    let mut curve = fit_cubic(points, &params, tangents);
+    let (mut error, mut split) = max_error(points, &params, &curve);
+
+    // Before splitting, the fit is improved by moving the parameters of the points to where they're closest to
+    // the curve.
+    for _ in 0..FIT_ITERATIONS {
+        if error <= tolerance2 {
+            break;
+        }
+
+        params = points
+            .iter()
+            .zip(&params)
+            .map(|(point, t)| newton_step(&curve, *point, *t).clamp(0.0, 1.0))
+            .collect();
+        curve = fit_cubic(points, &params, tangents);
+        (error, split) = max_error(points, &params, &curve);
+    }
+
+    if error <= tolerance2 || points.len() <= 2 {
+        let [_, c1, c2, end] = curve;
+        path.bezier_to(c1.x, c1.y, c2.x, c2.y, end.x, end.y);
+        return;
+    }
+
+    // Split at the point with the largest error, continuing smoothly through it.
+    let mut center = points[split - 1] - points[split + 1];
+    center.normalize();
+
+    fit_cubics(&points[..=split], [tangents[0], center], tolerance2, path);
+    fit_cubics(&points[split..], [center * -1.0, tangents[1]], tolerance2, path);
+}
+
+/// Returns the parameters of the points proportional to the distance along them.
+fn chord_length_params(points: &[Position]) -> Vec<f32> {
+    let mut length = 0.0;
+    let mut params: Vec<f32> = std::iter::once(0.0)
+        .chain(points.windows(2).map(|pair| {
+            length += (pair[1] - pair[0]).mag2().sqrt();
+            length
+        }))
+        .collect();
+
+    if length > 0.0 {
+        for t in &mut params {
+            *t /= length;
+        }
+    }
+
+    params
+}
+
+/// Fits a cubic bezier to the points at the given parameters with the least squared error, keeping its ends at
+/// the first and last point and its control points in the direction of the tangents.
+fn fit_cubic(points: &[Position], params: &[f32], tangents: [Vector; 2]) -> [Position; 4] {
+    let (first, last) = (points[0], points[points.len() - 1]);
+
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+
+    for (point, &t) in points.iter().zip(params) {
+        let [_, b1, b2, b3] = bernstein(t);
+        let a = [tangents[0] * b1, tangents[1] * b2];
+
+        c[0][0] += a[0].dot(a[0]);
+        c[0][1] += a[0].dot(a[1]);
+        c[1][1] += a[1].dot(a[1]);
+
+        let rest = (*point - first) - (last - first) * (b2 + b3);
+        x[0] += a[0].dot(rest);
+        x[1] += a[1].dot(rest);
+    }
+    c[1][0] = c[0][1];
+
+    let det = c[0][0] * c[1][1] - c[0][1] * c[1][0];
+    let (alpha0, alpha1) = if det.abs() > f32::EPSILON {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / det,
+            (c[0][0] * x[1] - c[1][0] * x[0]) / det,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Control points on the wrong side or too close to the ends fall back to a third of the distance between
+    // the ends.
+    let length = (last - first).mag2().sqrt();
+    let epsilon = 1e-6 * length;
+    let (alpha0, alpha1) = if alpha0 < epsilon || alpha1 < epsilon {
+        (length / 3.0, length / 3.0)
+    } else {
+        (alpha0, alpha1)
+    };
+
+    [first, first + tangents[0] * alpha0, last + tangents[1] * alpha1, last]
+}
+
+/// Returns the largest squared distance of the points from the curve, and the index of that point.
+fn max_error(points: &[Position], params: &[f32], curve: &[Position; 4]) -> (f32, usize) {
+    let mut max = (0.0, points.len() / 2);
+
+    for (i, (point, &t)) in points.iter().zip(params).enumerate().take(points.len() - 1).skip(1) {
+        let distance2 = (cubic_point(curve, t) - *point).mag2();
+        if distance2 >= max.0 {
+            max = (distance2, i);
+        }
+    }
+
+    max
+}
+
+/// Moves the parameter `t` closer to the point on the curve nearest to `point` with a step of Newton's method.
+fn newton_step(curve: &[Position; 4], point: Position, t: f32) -> f32 {
+    let [p0, p1, p2, p3] = *curve;
+    let d1 = [(p1 - p0) * 3.0, (p2 - p1) * 3.0, (p3 - p2) * 3.0];
+    let d2 = [(d1[1] - d1[0]) * 2.0, (d1[2] - d1[1]) * 2.0];
+
+    let mt = 1.0 - t;
+    let first = d1[0] * (mt * mt) + d1[1] * (2.0 * mt * t) + d1[2] * (t * t);
+    let second = d2[0] * mt + d2[1] * t;
+    let diff = cubic_point(curve, t) - point;
+
+    let numerator = diff.dot(first);
+    let denominator = first.dot(first) + diff.dot(second);
+
+    if denominator.abs() <= f32::EPSILON {
+        t
+    } else {
+        t - numerator / denominator
+    }
+}
+
+fn bernstein(t: f32) -> [f32; 4] {
+    let mt = 1.0 - t;
+    [mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t]
+}
+
+fn cubic_point(curve: &[Position; 4], t: f32) -> Position {
+    let weights = bernstein(t);
+    let origin = Position::default();
+    curve
+        .iter()
+        .zip(weights)
+        .fold(origin, |sum, (point, weight)| sum + (*point - origin) * weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::measure::PathMeasure;
+
+    /// Returns the largest distance of the points from the path, sampled along it.
+    fn max_distance(points: &[(f32, f32)], path: &Path) -> f32 {
+        let measure: PathMeasure = path.measure();
+        let samples: Vec<(f32, f32)> = (0..=2000)
+            .filter_map(|i| measure.position_at(measure.length() * i as f32 / 2000.0))
+            .collect();
+
+        points
+            .iter()
+            .map(|(x, y)| {
+                samples
+                    .iter()
+                    .map(|(sx, sy)| (sx - x).hypot(sy - y))
+                    .fold(f32::MAX, f32::min)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    fn sine() -> (Vec<(f32, f32)>, Path) {
+        let points: Vec<(f32, f32)> = (0..=200)
+            .map(|i| {
+                let x = i as f32;
+                (x, (x * 0.05).sin() * 30.0)
+            })
+            .collect();
+
+        let mut path = Path::new();
+        path.move_to(points[0].0, points[0].1);
+        for (x, y) in &points[1..] {
+            path.line_to(*x, *y);
+        }
+
+        (points, path)
+    }
+
+    #[test]
+    fn simplify() {
+        let (points, path) = sine();
+
+        let simplified = path.simplify(0.5);
+        let count = simplified.verbs().count();
+        assert!(count < 30, "{count} verbs");
+        assert!(max_distance(&points, &simplified) <= 0.5 + 0.05);
+
+        // Straight lines collapse to their ends.
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(5.0, 0.0);
+        path.line_to(10.0, 0.0);
+        path.line_to(10.0, 10.0);
+        path.close();
+        assert_eq!(path.simplify(0.1).to_svg_path_data(), "M0 0 L10 0 L10 10 Z");
+    }
+
+    #[test]
+    fn fit_curves() {
+        let (points, path) = sine();
+
+        let fitted = path.fit_curves(0.5);
+        let count = fitted.verbs().filter(|verb| matches!(verb, Verb::BezierTo(..))).count();
+        assert!(count > 0 && count < 10, "{count} curves");
+        assert_eq!(fitted.verbs().count(), count + 1);
+        assert!(max_distance(&points, &fitted) <= 0.5 + 0.05);
+    }
+
+    #[test]
+    fn fit_curves_keeps_corners_and_curves() {
+        // A square made of many points keeps its corners and straight sides.
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        for (x, y) in [
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 5.0),
+            (10.0, 10.0),
+            (5.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 5.0),
+        ] {
+            path.line_to(x, y);
+        }
+        path.close();
+        path.move_to(20.0, 0.0);
+        path.bezier_to(20.0, 10.0, 30.0, 10.0, 30.0, 0.0);
+        path.line_to(40.0, 0.0);
+
+        let fitted = path.fit_curves(0.1);
+        let corners = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        assert!(max_distance(&corners, &fitted) < 0.05);
+        assert_eq!(
+            fitted.verbs().filter(|verb| matches!(verb, Verb::BezierTo(..))).count(),
+            5
+        );
+        assert!(fitted.to_svg_path_data().ends_with("M20 0 C20 10 30 10 30 0 L40 0"));
+    }
+
+    #[test]
+    fn fit_closed_curve() {
+        // A circle of points closes smoothly.
+        let points: Vec<(f32, f32)> = (0..64)
+            .map(|i| {
+                let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+                (angle.cos() * 50.0, angle.sin() * 50.0)
+            })
+            .collect();
+
+        let mut path = Path::new();
+        path.move_to(points[0].0, points[0].1);
+        for (x, y) in &points[1..] {
+            path.line_to(*x, *y);
+        }
+        path.close();
+
+        let fitted = path.fit_curves(0.25);
+        assert!(max_distance(&points, &fitted) <= 0.3);
+
+        let measure = fitted.measure();
+        let (end, start) = (
+            measure.tangent_at(measure.length()).unwrap(),
+            measure.tangent_at(0.0).unwrap(),
+        );
+        assert!((end.0 - start.0).abs() < 1e-3 && (end.1 - start.1).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,189 @@
+use super::{
+    ops::{combine, Point},
+    BooleanOp, Path, PathCache, TESS_TOL,
+};
+use crate::{geometry::Transform2D, FillRule, Paint, Vertex};
+
+impl Path {
+    /// Converts the stroke of the path into a path outlining it, using the line width, caps, join,
+    /// miter limit and dash pattern of `paint`.
+    ///
+    /// Filling the result matches stroking the path with `paint` at a scale of one, apart from
+    /// antialiasing. The joins and caps are the ones [`Canvas::stroke_path`](crate::Canvas::stroke_path)
+    /// draws, so round ones are flattened into line segments. Like the result of
+    /// [`Path::boolean_op`], the outline doesn't overlap itself and fills correctly with either fill
+    /// rule.
+    pub fn stroke_to_path(&self, paint: &Paint) -> Self {
+        let stroke = &paint.stroke;
+
+        if stroke.line_width <= 0.0 {
+            return Self::new();
+        }
+
+        let mut cache = PathCache::new(self.verbs(), &Transform2D::identity(), TESS_TOL, self.dist_tol);
+
+        if !stroke.dash_array.is_empty() {
+            if let Some(dashed) = cache.dashed(&stroke.dash_array, stroke.dash_offset, self.dist_tol) {
+                cache = dashed;
+            }
+        }
+
+        cache.expand_stroke(
+            stroke.line_width * 0.5,
+            0.0,
+            stroke.line_cap_start,
+            stroke.line_cap_end,
+            stroke.line_join,
+            stroke.miter_limit,
+            TESS_TOL,
+        );
+
+        let polygons = cache.stroke_strips().flat_map(strip_polygons).collect();
+
+        combine([polygons, Vec::new()], BooleanOp::Union, FillRule::NonZero)
+    }
+}
+
+/// Splits a triangle strip created by `PathCache::expand_stroke()` into polygons whose union covers the
+/// same area.
+///
+/// Consecutive triangles of a strip share an edge in opposite directions, so a run of triangles winding
+/// the same way adds up to the polygon around it. The strip is cut where it folds over, so that all
+/// polygons wind the same way.
+pub(super) fn strip_polygons(strip: &[Vertex]) -> Vec<Vec<Point>> {
+    let points: Vec<Point> = strip.iter().map(|vertex| [vertex.x as f64, vertex.y as f64]).collect();
+    let mut polygons = Vec::new();
+    let mut start = 0;
+    let mut winding = 0.0;
+
+    for i in 0..points.len().saturating_sub(2) {
+        let [a, b, c] = [points[i], points[i + 1], points[i + 2]];
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        let mut area = if (i - start) % 2 == 0 { area } else { -area };
+
+        if area * winding < 0.0 {
+            polygons.extend(ribbon(&points[start..i + 2], winding));
+            start = i;
+            winding = 0.0;
+            area = -area;
+        }
+
+        if winding == 0.0 {
+            winding = area;
+        }
+    }
+
+    if points.len() >= 3 {
+        polygons.extend(ribbon(&points[start..], winding));
+    }
+
+    polygons
+}
+
+/// Returns the outline of a triangle strip winding the same way throughout, oriented to have positive area.
+fn ribbon(strip: &[Point], winding: f64) -> Option<Vec<Point>> {
+    if winding == 0.0 {
+        return None;
+    }
+
+    // The first vertex, then every other vertex up one side of the strip and back down the other.
+    let mut polygon: Vec<Point> = std::iter::once(strip[0])
+        .chain(strip.iter().skip(1).step_by(2).copied())
+        .chain(strip.iter().skip(2).step_by(2).rev().copied())
+        .collect();
+
+    if winding < 0.0 {
+        polygon.reverse();
+    }
+
+    Some(polygon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        path::ops::tests::{area, contour_count},
+        LineCap, LineJoin, Verb,
+    };
+
+    fn line() -> Path {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(100.0, 0.0);
+        path
+    }
+
+    #[test]
+    fn caps() {
+        let paint = Paint::default().with_line_width(10.0);
+        let outline = line().stroke_to_path(&paint);
+        assert!((area(&outline) - 1000.0).abs() < 0.01);
+        // A rectangle.
+        assert_eq!(
+            outline.verbs().filter(|verb| matches!(verb, Verb::LineTo(..))).count(),
+            3
+        );
+
+        let outline = line().stroke_to_path(&paint.clone().with_line_cap(LineCap::Square));
+        assert!((area(&outline) - 1100.0).abs() < 0.01);
+
+        // Two half circles are added, slightly smaller than the round caps due to flattening.
+        let outline = line().stroke_to_path(&paint.with_line_cap(LineCap::Round));
+        let expected = 1000.0 + std::f32::consts::PI * 25.0;
+        assert!(area(&outline) < expected && area(&outline) > expected * 0.99);
+    }
+
+    #[test]
+    fn joins() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(100.0, 0.0);
+        path.line_to(100.0, 100.0);
+
+        let paint = Paint::default().with_line_width(10.0);
+        let miter = area(&path.stroke_to_path(&paint.clone().with_line_join(LineJoin::Miter)));
+        let round = area(&path.stroke_to_path(&paint.clone().with_line_join(LineJoin::Round)));
+        let bevel = area(&path.stroke_to_path(&paint.with_line_join(LineJoin::Bevel)));
+
+        // Two bands of 100 x 10 that overlap in a 5 x 5 square, plus the outer corner.
+        assert!((miter - 2000.0).abs() < 0.01);
+        assert!((bevel - (2000.0 - 12.5)).abs() < 0.01);
+        assert!(round > bevel && round < miter);
+    }
+
+    #[test]
+    fn closed_contour_has_hole() {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 100.0, 100.0);
+
+        let outline = path.stroke_to_path(&Paint::default().with_line_width(10.0));
+        assert!((area(&outline) - (110.0 * 110.0 - 90.0 * 90.0)).abs() < 0.01);
+        assert_eq!(outline.verbs().filter(|verb| matches!(verb, Verb::Hole)).count(), 1);
+    }
+
+    #[test]
+    fn dashes() {
+        let mut paint = Paint::default().with_line_width(10.0);
+        paint.set_dash_array(&[10.0, 10.0]);
+
+        let outline = line().stroke_to_path(&paint);
+        assert!((area(&outline) - 500.0).abs() < 0.01);
+        assert_eq!(contour_count(&outline), 5);
+    }
+
+    #[test]
+    fn dense_polyline() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        for i in 1..400 {
+            let x = i as f32 * 0.5;
+            path.line_to(x, (x * 0.1).sin() * 40.0);
+        }
+
+        // The round joins overlap each other many times, but the outline is a single contour.
+        let outline = path.stroke_to_path(&Paint::default().with_line_width(8.0).with_line_join(LineJoin::Round));
+        assert_eq!(contour_count(&outline), 1);
+        assert!(area(&outline) > 0.0);
+    }
+}
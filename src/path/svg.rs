@@ -0,0 +1,447 @@
+use std::fmt::Write;
+
+use super::{PackedVerb, Path, Verb};
+use crate::{geometry::Position, ErrorKind};
+
+impl Path {
+    /// Parses the path data of an SVG `<path>` element, as found in its `d` attribute.
+    ///
+    /// All commands are supported in their absolute and relative forms, including the smooth
+    /// curve (`S`, `T`) and elliptical arc (`A`) commands. Quadratic curves are kept as
+    /// [`Verb::QuadTo`] segments, and arcs are converted to cubic bezier segments.
+    ///
+    /// Returns [`ErrorKind::SvgPathDataParseError`] with the byte offset of the offending input
+    /// if the path data is malformed.
+    pub fn from_svg_path_data(data: &str) -> Result<Self, ErrorKind> {
+        let mut parser = Parser {
+            data: data.as_bytes(),
+            pos: 0,
+        };
+
+        let mut path = Self::new();
+
+        let mut current = Position::default();
+        let mut start = Position::default();
+        // Reflected for the following S or T command respectively.
+        let mut last_cubic_ctrl = None;
+        let mut last_quad_ctrl = None;
+        let mut command = None;
+
+        loop {
+            parser.skip_separators();
+
+            let Some(&c) = parser.data.get(parser.pos) else {
+                break;
+            };
+
+            let command_start = parser.pos;
+            if c.is_ascii_alphabetic() {
+                command = Some(c);
+                parser.pos += 1;
+            }
+
+            // Coordinates without a command, for example after a close.
+            let Some(cmd) = command else {
+                return Err(parser.error());
+            };
+            let relative = cmd.is_ascii_lowercase();
+            let offset = |p: Position| {
+                if relative {
+                    Position {
+                        x: current.x + p.x,
+                        y: current.y + p.y,
+                    }
+                } else {
+                    p
+                }
+            };
+
+            // Commands other than a move have to continue a sub-path.
+            if path.verbs.is_empty() && !matches!(cmd, b'M' | b'm') {
+                return Err(ErrorKind::SvgPathDataParseError(command_start));
+            }
+
+            // Drawing after a close starts a new sub-path at the start of the closed one.
+            if matches!(path.verbs.last(), Some(PackedVerb::Close)) && !matches!(cmd, b'M' | b'm' | b'Z' | b'z') {
+                path.move_to(start.x, start.y);
+            }
+
+            let mut cubic_ctrl = None;
+            let mut quad_ctrl = None;
+
+            match cmd.to_ascii_uppercase() {
+                b'M' => {
+                    let p = offset(parser.point()?);
+                    path.move_to(p.x, p.y);
+                    current = p;
+                    start = p;
+                    // Subsequent coordinate pairs are implicit line commands.
+                    command = Some(if relative { b'l' } else { b'L' });
+                }
+                b'L' => {
+                    current = offset(parser.point()?);
+                    path.line_to(current.x, current.y);
+                }
+                b'H' => {
+                    let x = parser.number()?;
+                    current.x = if relative { current.x + x } else { x };
+                    path.line_to(current.x, current.y);
+                }
+                b'V' => {
+                    let y = parser.number()?;
+                    current.y = if relative { current.y + y } else { y };
+                    path.line_to(current.x, current.y);
+                }
+                b'C' => {
+                    let c1 = offset(parser.point()?);
+                    let c2 = offset(parser.point()?);
+                    current = offset(parser.point()?);
+                    path.bezier_to(c1.x, c1.y, c2.x, c2.y, current.x, current.y);
+                    cubic_ctrl = Some(c2);
+                }
+                b'S' => {
+                    let c1 = reflect(last_cubic_ctrl, current);
+                    let c2 = offset(parser.point()?);
+                    current = offset(parser.point()?);
+                    path.bezier_to(c1.x, c1.y, c2.x, c2.y, current.x, current.y);
+                    cubic_ctrl = Some(c2);
+                }
+                b'Q' => {
+                    let c = offset(parser.point()?);
+                    current = offset(parser.point()?);
+                    path.quad_to(c.x, c.y, current.x, current.y);
+                    quad_ctrl = Some(c);
+                }
+                b'T' => {
+                    let c = reflect(last_quad_ctrl, current);
+                    current = offset(parser.point()?);
+                    path.quad_to(c.x, c.y, current.x, current.y);
+                    quad_ctrl = Some(c);
+                }
+                b'A' => {
+                    let rx = parser.number()?;
+                    let ry = parser.number()?;
+                    let x_axis_rotation = parser.number()?;
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    current = offset(parser.point()?);
+                    path.elliptical_arc_to(
+                        rx,
+                        ry,
+                        x_axis_rotation.to_radians(),
+                        large_arc,
+                        sweep,
+                        current.x,
+                        current.y,
+                    );
+                }
+                b'Z' => {
+                    path.close();
+                    current = start;
+                    command = None;
+                }
+                _ => return Err(ErrorKind::SvgPathDataParseError(command_start)),
+            }
+
+            last_cubic_ctrl = cubic_ctrl;
+            last_quad_ctrl = quad_ctrl;
+        }
+
+        Ok(path)
+    }
+
+    /// Writes the verbs of the path as SVG path data, suitable for the `d` attribute of an SVG
+    /// `<path>` element.
+    ///
+    /// All coordinates are absolute. Sub-path windings set with [`Path::solidity`] have no SVG
+    /// equivalent and are omitted. Conics are approximated with quadratic curves within the
+    /// distance tolerance of the path.
+    ///
+    /// Segments drawn after closing a sub-path without moving continue from the last point, while SVG
+    /// starts them at the start of the closed sub-path, so a move to the last point is written first.
+    pub fn to_svg_path_data(&self) -> String {
+        let mut data = String::new();
+
+        let mut push = |command: char, coords: &[f32]| {
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            data.push(command);
+
+            for (i, coord) in coords.iter().enumerate() {
+                let separator = if i == 0 { "" } else { " " };
+                let _ = write!(data, "{separator}{coord}");
+            }
+        };
+
+        let mut last = [0.0; 2];
+        let mut closed = false;
+
+        for verb in self.verbs_without_conics() {
+            let (command, coords) = match verb {
+                Verb::MoveTo(x, y) => ('M', vec![x, y]),
+                Verb::LineTo(x, y) => ('L', vec![x, y]),
+                Verb::BezierTo(c1x, c1y, c2x, c2y, x, y) => ('C', vec![c1x, c1y, c2x, c2y, x, y]),
+                Verb::QuadTo(cx, cy, x, y) => ('Q', vec![cx, cy, x, y]),
+                Verb::Close => {
+                    push('Z', &[]);
+                    closed = true;
+                    continue;
+                }
+                Verb::ConicTo(..) | Verb::Solid | Verb::Hole => continue,
+            };
+
+            if closed && command != 'M' {
+                push('M', &last);
+            }
+            closed = false;
+
+            push(command, &coords);
+            last = [coords[coords.len() - 2], coords[coords.len() - 1]];
+        }
+
+        data
+    }
+}
+
+fn reflect(ctrl: Option<Position>, current: Position) -> Position {
+    ctrl.map_or(current, |ctrl| Position {
+        x: 2.0 * current.x - ctrl.x,
+        y: 2.0 * current.y - ctrl.y,
+    })
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> ErrorKind {
+        ErrorKind::SvgPathDataParseError(self.pos)
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.data.get(self.pos) {
+            if !(c.is_ascii_whitespace() || *c == b',') {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn number(&mut self) -> Result<f32, ErrorKind> {
+        self.skip_separators();
+        let start = self.pos;
+
+        if matches!(self.data.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+
+        let mut digits = self.skip_digits();
+        if self.data.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.pos = start;
+            return Err(self.error());
+        }
+
+        // Only consume the exponent if it is complete, "1e" is the number 1 followed by garbage.
+        if matches!(self.data.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if matches!(self.data.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(ErrorKind::SvgPathDataParseError(start))
+    }
+
+    fn point(&mut self) -> Result<Position, ErrorKind> {
+        Ok(Position {
+            x: self.number()?,
+            y: self.number()?,
+        })
+    }
+
+    fn flag(&mut self) -> Result<bool, ErrorKind> {
+        self.skip_separators();
+        let flag = match self.data.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error()),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solidity;
+
+    fn assert_same_verbs(actual: &Path, expected: &Path) {
+        assert_eq!(actual.verbs, expected.verbs);
+        assert_eq!(actual.coords.len(), expected.coords.len());
+        for (a, e) in actual.coords.iter().zip(&expected.coords) {
+            assert!((a.x - e.x).abs() < 1e-4 && (a.y - e.y).abs() < 1e-4, "{a:?} != {e:?}");
+        }
+    }
+
+    #[test]
+    fn parse_lines_and_curves() {
+        let path =
+            Path::from_svg_path_data("M10,20 L30 40 h10 v-10 H0 V5 z m1 1 l1-1.5e1 C1 2 3 4 5 6 Q0 0 10 10").unwrap();
+
+        let mut expected = Path::new();
+        expected.move_to(10.0, 20.0);
+        expected.line_to(30.0, 40.0);
+        expected.line_to(40.0, 40.0);
+        expected.line_to(40.0, 30.0);
+        expected.line_to(0.0, 30.0);
+        expected.line_to(0.0, 5.0);
+        expected.close();
+        expected.move_to(11.0, 21.0);
+        expected.line_to(12.0, 6.0);
+        expected.bezier_to(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        expected.quad_to(0.0, 0.0, 10.0, 10.0);
+
+        assert_same_verbs(&path, &expected);
+    }
+
+    #[test]
+    fn parse_implicit_commands_and_compact_numbers() {
+        let path = Path::from_svg_path_data("m0 0 10 0-5.5.5 1e1-1").unwrap();
+
+        let mut expected = Path::new();
+        expected.move_to(0.0, 0.0);
+        expected.line_to(10.0, 0.0);
+        expected.line_to(4.5, 0.5);
+        expected.line_to(14.5, -0.5);
+
+        assert_same_verbs(&path, &expected);
+    }
+
+    #[test]
+    fn parse_smooth_curves() {
+        let path = Path::from_svg_path_data("M0 0 C0 10 10 10 10 0 S20 -10 20 0 Q25 10 30 0 T40 0").unwrap();
+
+        let mut expected = Path::new();
+        expected.move_to(0.0, 0.0);
+        expected.bezier_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0);
+        expected.bezier_to(10.0, -10.0, 20.0, -10.0, 20.0, 0.0);
+        expected.quad_to(25.0, 10.0, 30.0, 0.0);
+        expected.quad_to(35.0, -10.0, 40.0, 0.0);
+
+        assert_same_verbs(&path, &expected);
+    }
+
+    #[test]
+    fn parse_arcs() {
+        // Two half circles with compact flags.
+        let path = Path::from_svg_path_data("M0 0 A10 10 0 0110 10 a10,10 0 1,1 0,20").unwrap();
+
+        assert_eq!(path.verbs[0], PackedVerb::MoveTo);
+        assert!(path.verbs[1..].iter().all(|verb| *verb == PackedVerb::BezierTo));
+        let last = path.coords[path.coords.len() - 1];
+        assert!((last.x - 10.0).abs() < 1e-4 && (last.y - 30.0).abs() < 1e-4);
+
+        // A quarter circle around (0, 10), bulging towards positive x.
+        let path = Path::from_svg_path_data("M0 0 A10 10 0 0 1 10 10").unwrap();
+        let [p0, p1, p2, p3] = [path.coords[0], path.coords[1], path.coords[2], path.coords[3]];
+        let mid_x = 0.125 * (p0.x + p3.x) + 0.375 * (p1.x + p2.x);
+        let mid_y = 0.125 * (p0.y + p3.y) + 0.375 * (p1.y + p2.y);
+        assert!((mid_x.hypot(mid_y - 10.0) - 10.0).abs() < 0.01);
+        assert!(mid_x > 5.0);
+
+        // Zero radii are straight lines.
+        let path = Path::from_svg_path_data("M0 0 A0 10 0 0 1 10 10").unwrap();
+        assert_eq!(path.verbs, [PackedVerb::MoveTo, PackedVerb::LineTo]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error_position = |data| match Path::from_svg_path_data(data) {
+            Err(ErrorKind::SvgPathDataParseError(position)) => Some(position),
+            _ => None,
+        };
+
+        assert_eq!(error_position("L10 10"), Some(0));
+        assert_eq!(error_position("M10"), Some(3));
+        assert_eq!(error_position("M10 10 X"), Some(7));
+        assert_eq!(error_position("M0 0 A1 1 0 2 0 1 1"), Some(12));
+        assert_eq!(error_position("M0 0 Z 1 1"), Some(7));
+        assert!(Path::from_svg_path_data("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut path = Path::new();
+        path.rounded_rect(10.5, 20.25, 100.0, 50.0, 7.0);
+        path.move_to(-1e-3, 3.0e7);
+        path.line_to(0.1, 0.2);
+        path.quad_to(1.0 / 3.0, 2.0, 5.0, 5.0);
+        path.circle(50.0, 50.0, 10.0);
+
+        let data = path.to_svg_path_data();
+        let parsed = Path::from_svg_path_data(&data).unwrap();
+
+        // Shortest round-trip formatting of the coordinates reproduces them exactly.
+        assert_eq!(parsed.verbs, path.verbs);
+        assert!(parsed
+            .coords
+            .iter()
+            .zip(&path.coords)
+            .all(|(a, b)| a.x == b.x && a.y == b.y));
+        assert_eq!(parsed.to_svg_path_data(), data);
+    }
+
+    #[test]
+    fn serialize() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.5);
+        path.line_to(10.0, -2.0);
+        path.bezier_to(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        path.solidity(Solidity::Hole);
+        path.close();
+
+        assert_eq!(path.to_svg_path_data(), "M0 0.5 L10 -2 C1 2 3 4 5 6 Z");
+    }
+
+    #[test]
+    fn serialize_segments_after_close() {
+        let mut path = Path::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(10.0, 0.0);
+        path.line_to(10.0, 10.0);
+        path.close();
+        path.line_to(20.0, 20.0);
+
+        let data = path.to_svg_path_data();
+        assert_eq!(data, "M0 0 L10 0 L10 10 Z M10 10 L20 20");
+
+        // The segment still starts at the last point after parsing.
+        let parsed = Path::from_svg_path_data(&data).unwrap();
+        let start = parsed.coords[parsed.coords.len() - 2];
+        assert_eq!((start.x, start.y), (10.0, 10.0));
+        assert_eq!(parsed.to_svg_path_data(), data);
+    }
+}
@@ -19,6 +19,9 @@ pub use wgpu::WGPURenderer;
 mod void;
 pub use void::Void;
 
+mod software;
+pub use software::Software;
+
 mod params;
 pub(crate) use params::Params;
 
@@ -75,8 +78,16 @@ pub enum CommandType {
         /// Image filter to apply.
         filter: ImageFilter,
     },
+    /// Remove the clip from the current render target. `triangles_verts` covers the whole render target.
+    ResetClip,
+    /// Intersect the clip of the current render target with the shape made up of the fill vertices of the
+    /// drawables, using the command's fill rule. `triangles_verts` covers the whole render target.
+    IntersectClip,
 }
 
+/// Stencil bit that marks pixels outside of the clip. The lower bits are used for the winding counts of fills.
+pub(crate) const STENCIL_CLIP_BIT: u8 = 0x80;
+
 /// Represents a command that can be executed by the renderer.
 #[derive(Debug)]
 pub struct Command {
@@ -87,6 +98,8 @@ pub struct Command {
     pub(crate) glyph_texture: GlyphTexture,
     pub(crate) fill_rule: FillRule,
     pub(crate) composite_operation: CompositeOperationState,
+    /// Whether the command is masked by the clip set up by preceding `ResetClip`/`IntersectClip` commands.
+    pub(crate) clip: bool,
 }
 
 impl Command {
@@ -100,6 +113,7 @@ impl Command {
             glyph_texture: GlyphTexture::default(),
             fill_rule: FillRule::default(),
             composite_operation: CompositeOperationState::default(),
+            clip: false,
         }
     }
 }
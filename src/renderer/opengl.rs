@@ -12,13 +12,13 @@ use rgb::RGBA8;
 
 use crate::{
     renderer::{GlyphTexture, ImageId, Vertex},
-    BlendFactor, Color, CompositeOperationState, ErrorKind, FillRule, ImageFilter, ImageInfo, ImageSource, ImageStore,
-    Scissor, Transform2D,
+    BlendFactor, BlendMode, Color, CompositeOperationState, ErrorKind, FillRule, ImageFilter, ImageInfo, ImageSource,
+    ImageStore, Scissor, Transform2D,
 };
 
 use glow::HasContext;
 
-use super::{Command, CommandType, Params, RenderTarget, Renderer, ShaderType, SurfacelessRenderer};
+use super::{Command, CommandType, Params, RenderTarget, Renderer, ShaderType, SurfacelessRenderer, STENCIL_CLIP_BIT};
 
 mod program;
 use program::MainProgram;
@@ -51,6 +51,11 @@ pub struct OpenGl {
     context: Rc<glow::Context>,
     screen_target: Option<Framebuffer>,
     current_render_target: RenderTarget,
+    // Copy of the render target that blend modes read the destination colors from, and its size
+    backdrop_texture: Option<(<glow::Context as glow::HasContext>::Texture, [i32; 2])>,
+    // Framebuffer with the backdrop texture attached, to resolve multisampled render targets into
+    backdrop_framebuffer: Option<<glow::Context as glow::HasContext>::Framebuffer>,
+    blend_mode: BlendMode,
 }
 
 impl OpenGl {
@@ -190,6 +195,9 @@ impl OpenGl {
             context,
             screen_target: None,
             current_render_target: RenderTarget::Screen,
+            backdrop_texture: None,
+            backdrop_framebuffer: None,
+            blend_mode: BlendMode::Normal,
         };
 
         unsafe {
@@ -257,8 +265,98 @@ impl OpenGl {
         }
     }
 
+    // Copies the current render target into the backdrop texture and binds it to texture unit 2, for blend modes
+    // to read the destination colors. Returns false if the render target can't be copied.
+    fn copy_backdrop(&mut self) -> bool {
+        let size = [self.view[0] as i32, self.view[1] as i32];
+
+        // Multisampled framebuffers can't be copied from, only resolved by a blit, which OpenGL ES 2 lacks.
+        let multisampled = unsafe { self.context.get_parameter_i32(glow::SAMPLE_BUFFERS) } > 0;
+        if multisampled && self.is_opengles_2_0 {
+            return false;
+        }
+
+        unsafe {
+            self.context.active_texture(glow::TEXTURE0 + 2);
+
+            match self.backdrop_texture {
+                Some((texture, backdrop_size)) if backdrop_size == size => {
+                    self.context.bind_texture(glow::TEXTURE_2D, Some(texture));
+                }
+                _ => {
+                    if let Some((texture, _)) = self.backdrop_texture.take() {
+                        self.context.delete_texture(texture);
+                    }
+
+                    let texture = self.context.create_texture().ok();
+                    self.context.bind_texture(glow::TEXTURE_2D, texture);
+                    self.context.tex_image_2d(
+                        glow::TEXTURE_2D,
+                        0,
+                        glow::RGBA as i32,
+                        size[0],
+                        size[1],
+                        0,
+                        glow::RGBA,
+                        glow::UNSIGNED_BYTE,
+                        glow::PixelUnpackData::Slice(None),
+                    );
+                    self.context
+                        .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::NEAREST as i32);
+                    self.context
+                        .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::NEAREST as i32);
+                    self.context
+                        .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+                    self.context
+                        .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+
+                    self.backdrop_texture = texture.map(|texture| (texture, size));
+                }
+            }
+
+            if multisampled {
+                let target = self.context.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+                if self.backdrop_framebuffer.is_none() {
+                    self.backdrop_framebuffer = self.context.create_framebuffer().ok();
+                }
+
+                self.context
+                    .bind_framebuffer(glow::DRAW_FRAMEBUFFER, self.backdrop_framebuffer);
+                self.context.framebuffer_texture_2d(
+                    glow::DRAW_FRAMEBUFFER,
+                    glow::COLOR_ATTACHMENT0,
+                    glow::TEXTURE_2D,
+                    self.backdrop_texture.map(|(texture, _)| texture),
+                    0,
+                );
+                self.context.blit_framebuffer(
+                    0,
+                    0,
+                    size[0],
+                    size[1],
+                    0,
+                    0,
+                    size[0],
+                    size[1],
+                    glow::COLOR_BUFFER_BIT,
+                    glow::NEAREST,
+                );
+                self.context.bind_framebuffer(glow::FRAMEBUFFER, target);
+            } else {
+                self.context
+                    .copy_tex_sub_image_2d(glow::TEXTURE_2D, 0, 0, 0, 0, 0, size[0], size[1]);
+            }
+            self.context.active_texture(glow::TEXTURE0);
+        }
+
+        self.check_error("copy_backdrop");
+
+        true
+    }
+
     fn convex_fill(&mut self, images: &ImageStore<GlTexture>, cmd: &Command, gpu_paint: &Params) {
         self.set_uniforms(images, gpu_paint, cmd.image, cmd.glyph_texture);
+        self.enable_clip(cmd);
 
         for drawable in &cmd.drawables {
             if let Some((start, count)) = drawable.fill_verts {
@@ -275,6 +373,7 @@ impl OpenGl {
             }
         }
 
+        self.disable_clip(cmd);
         self.check_error("convex_fill");
     }
 
@@ -285,10 +384,17 @@ impl OpenGl {
         stencil_paint: &Params,
         fill_paint: &Params,
     ) {
+        let clip_mask = if cmd.clip { STENCIL_CLIP_BIT as u32 } else { 0 };
+        let fill_mask = match cmd.fill_rule {
+            FillRule::NonZero => !STENCIL_CLIP_BIT as u32,
+            FillRule::EvenOdd => 0x1,
+        };
+
         unsafe {
             self.context.enable(glow::STENCIL_TEST);
-            self.context.stencil_mask(0xff);
-            self.context.stencil_func(glow::ALWAYS, 0, 0xff);
+            // Keep the clip bit out of the winding counts and only count pixels inside the clip.
+            self.context.stencil_mask(!STENCIL_CLIP_BIT as u32);
+            self.context.stencil_func(glow::EQUAL, 0, clip_mask);
             self.context.color_mask(false, false, false, false);
             //glow::DepthMask(glow::FALSE);
         }
@@ -322,11 +428,7 @@ impl OpenGl {
 
         if self.antialias {
             unsafe {
-                match cmd.fill_rule {
-                    FillRule::NonZero => self.context.stencil_func(glow::EQUAL, 0x0, 0xff),
-                    FillRule::EvenOdd => self.context.stencil_func(glow::EQUAL, 0x0, 0x1),
-                }
-
+                self.context.stencil_func(glow::EQUAL, 0x0, fill_mask | clip_mask);
                 self.context.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
             }
 
@@ -342,11 +444,7 @@ impl OpenGl {
         }
 
         unsafe {
-            match cmd.fill_rule {
-                FillRule::NonZero => self.context.stencil_func(glow::NOTEQUAL, 0x0, 0xff),
-                FillRule::EvenOdd => self.context.stencil_func(glow::NOTEQUAL, 0x0, 0x1),
-            }
-
+            self.context.stencil_func(glow::NOTEQUAL, 0x0, fill_mask);
             self.context.stencil_op(glow::ZERO, glow::ZERO, glow::ZERO);
 
             if let Some((start, count)) = cmd.triangles_verts {
@@ -354,6 +452,7 @@ impl OpenGl {
                     .draw_arrays(glow::TRIANGLE_STRIP, start as i32, count as i32);
             }
 
+            self.context.stencil_mask(0xff);
             self.context.disable(glow::STENCIL_TEST);
         }
 
@@ -362,6 +461,7 @@ impl OpenGl {
 
     fn stroke(&mut self, images: &ImageStore<GlTexture>, cmd: &Command, paint: &Params) {
         self.set_uniforms(images, paint, cmd.image, cmd.glyph_texture);
+        self.enable_clip(cmd);
 
         for drawable in &cmd.drawables {
             if let Some((start, count)) = drawable.stroke_verts {
@@ -372,16 +472,19 @@ impl OpenGl {
             }
         }
 
+        self.disable_clip(cmd);
         self.check_error("stroke");
     }
 
     fn stencil_stroke(&mut self, images: &ImageStore<GlTexture>, cmd: &Command, paint1: &Params, paint2: &Params) {
+        let test_mask = if cmd.clip { 0xff } else { !STENCIL_CLIP_BIT as u32 };
+
         unsafe {
             self.context.enable(glow::STENCIL_TEST);
-            self.context.stencil_mask(0xff);
+            self.context.stencil_mask(!STENCIL_CLIP_BIT as u32);
 
             // Fill the stroke base without overlap
-            self.context.stencil_func(glow::EQUAL, 0x0, 0xff);
+            self.context.stencil_func(glow::EQUAL, 0x0, test_mask);
             self.context.stencil_op(glow::KEEP, glow::KEEP, glow::INCR);
         }
 
@@ -400,7 +503,7 @@ impl OpenGl {
         self.set_uniforms(images, paint1, cmd.image, cmd.glyph_texture);
 
         unsafe {
-            self.context.stencil_func(glow::EQUAL, 0x0, 0xff);
+            self.context.stencil_func(glow::EQUAL, 0x0, test_mask);
             self.context.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
         }
 
@@ -431,6 +534,7 @@ impl OpenGl {
 
         unsafe {
             self.context.color_mask(true, true, true, true);
+            self.context.stencil_mask(0xff);
             self.context.disable(glow::STENCIL_TEST);
         }
 
@@ -439,6 +543,7 @@ impl OpenGl {
 
     fn triangles(&mut self, images: &ImageStore<GlTexture>, cmd: &Command, paint: &Params) {
         self.set_uniforms(images, paint, cmd.image, cmd.glyph_texture);
+        self.enable_clip(cmd);
 
         if let Some((start, count)) = cmd.triangles_verts {
             unsafe {
@@ -446,9 +551,122 @@ impl OpenGl {
             }
         }
 
+        self.disable_clip(cmd);
         self.check_error("triangles");
     }
 
+    fn enable_clip(&self, cmd: &Command) {
+        if cmd.clip {
+            unsafe {
+                self.context.enable(glow::STENCIL_TEST);
+                self.context.stencil_func(glow::EQUAL, 0x0, STENCIL_CLIP_BIT as u32);
+                self.context.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
+            }
+        }
+    }
+
+    fn disable_clip(&self, cmd: &Command) {
+        if cmd.clip {
+            unsafe {
+                self.context.disable(glow::STENCIL_TEST);
+            }
+        }
+    }
+
+    fn reset_clip(&mut self, images: &ImageStore<GlTexture>, cmd: &Command) {
+        let stencil_paint = Params {
+            stroke_thr: -1.0,
+            shader_type: ShaderType::Stencil,
+            ..Params::default()
+        };
+
+        self.set_uniforms(images, &stencil_paint, None, GlyphTexture::None);
+
+        unsafe {
+            self.context.enable(glow::STENCIL_TEST);
+            self.context.disable(glow::CULL_FACE);
+            self.context.color_mask(false, false, false, false);
+            self.context.stencil_mask(STENCIL_CLIP_BIT as u32);
+            self.context.stencil_func(glow::ALWAYS, 0x0, 0xff);
+            self.context.stencil_op(glow::ZERO, glow::ZERO, glow::ZERO);
+
+            if let Some((start, count)) = cmd.triangles_verts {
+                self.context
+                    .draw_arrays(glow::TRIANGLE_STRIP, start as i32, count as i32);
+            }
+
+            self.context.stencil_mask(0xff);
+            self.context.color_mask(true, true, true, true);
+            self.context.enable(glow::CULL_FACE);
+            self.context.disable(glow::STENCIL_TEST);
+        }
+
+        self.check_error("reset_clip");
+    }
+
+    fn intersect_clip(&mut self, images: &ImageStore<GlTexture>, cmd: &Command) {
+        let stencil_paint = Params {
+            stroke_thr: -1.0,
+            shader_type: ShaderType::Stencil,
+            ..Params::default()
+        };
+
+        let fill_mask = match cmd.fill_rule {
+            FillRule::NonZero => !STENCIL_CLIP_BIT as u32,
+            FillRule::EvenOdd => 0x1,
+        };
+
+        self.set_uniforms(images, &stencil_paint, None, GlyphTexture::None);
+
+        unsafe {
+            self.context.enable(glow::STENCIL_TEST);
+            self.context.disable(glow::CULL_FACE);
+            self.context.color_mask(false, false, false, false);
+
+            // Count the winding of the clip path in the lower bits.
+            self.context.stencil_mask(!STENCIL_CLIP_BIT as u32);
+            self.context.stencil_func(glow::ALWAYS, 0x0, 0xff);
+            self.context
+                .stencil_op_separate(glow::FRONT, glow::KEEP, glow::KEEP, glow::INCR_WRAP);
+            self.context
+                .stencil_op_separate(glow::BACK, glow::KEEP, glow::KEEP, glow::DECR_WRAP);
+        }
+
+        for drawable in &cmd.drawables {
+            if let Some((start, count)) = drawable.fill_verts {
+                unsafe {
+                    self.context.draw_arrays(glow::TRIANGLES, start as i32, count as i32);
+                }
+            }
+        }
+
+        unsafe {
+            if let Some((start, count)) = cmd.triangles_verts {
+                // Mark the pixels outside of the clip path as clipped.
+                self.context.stencil_mask(STENCIL_CLIP_BIT as u32);
+                self.context
+                    .stencil_func(glow::EQUAL, STENCIL_CLIP_BIT as i32, fill_mask);
+                self.context.stencil_op(glow::KEEP, glow::KEEP, glow::REPLACE);
+                self.context
+                    .draw_arrays(glow::TRIANGLE_STRIP, start as i32, count as i32);
+
+                // Reset the winding counts.
+                self.context.stencil_mask(!STENCIL_CLIP_BIT as u32);
+                self.context.stencil_func(glow::ALWAYS, 0x0, 0xff);
+                self.context.stencil_op(glow::ZERO, glow::ZERO, glow::ZERO);
+                self.context
+                    .draw_arrays(glow::TRIANGLE_STRIP, start as i32, count as i32);
+            }
+
+            self.context.stencil_mask(0xff);
+            self.context.color_mask(true, true, true, true);
+            self.context.enable(glow::CULL_FACE);
+            self.context.disable(glow::STENCIL_TEST);
+        }
+
+        self.check_error("intersect_clip");
+    }
+
     fn set_uniforms(
         &mut self,
         images: &ImageStore<GlTexture>,
@@ -457,7 +675,8 @@ impl OpenGl {
         glyph_tex: GlyphTexture,
     ) {
         self.select_main_program(paint);
-        let arr = UniformArray::from(paint);
+        let mut arr = UniformArray::from(paint);
+        arr.set_blend_mode(self.blend_mode as u8 as f32);
         self.main_program().set_config(arr.as_slice());
         self.check_error("set_uniforms uniforms");
 
@@ -676,6 +895,7 @@ impl OpenGl {
             // Bind the two uniform samplers to texture units
             program.set_tex(0);
             program.set_glyphtex(1);
+            program.set_dsttex(2);
             program.set_view(self.view);
         }
     }
@@ -757,6 +977,19 @@ impl Renderer for OpenGl {
         for cmd in commands {
             self.set_composite_operation(cmd.composite_operation);
 
+            self.blend_mode = match cmd.cmd_type {
+                CommandType::ConvexFill { .. }
+                | CommandType::ConcaveFill { .. }
+                | CommandType::Stroke { .. }
+                | CommandType::StencilStroke { .. }
+                | CommandType::Triangles { .. } => cmd.composite_operation.blend_mode(),
+                _ => BlendMode::Normal,
+            };
+
+            if self.blend_mode != BlendMode::Normal && !self.copy_backdrop() {
+                self.blend_mode = BlendMode::Normal;
+            }
+
             match cmd.cmd_type {
                 CommandType::ConvexFill { ref params } => self.convex_fill(images, &cmd, params),
                 CommandType::ConcaveFill {
@@ -785,6 +1018,8 @@ impl Renderer for OpenGl {
                 CommandType::RenderFilteredImage { target_image, filter } => {
                     self.render_filtered_image(images, cmd, target_image, filter)
                 }
+                CommandType::ResetClip => self.reset_clip(images, &cmd),
+                CommandType::IntersectClip => self.intersect_clip(images, &cmd),
             }
         }
 
@@ -879,6 +1114,18 @@ impl SurfacelessRenderer for OpenGl {
 
 impl Drop for OpenGl {
     fn drop(&mut self) {
+        if let Some((texture, _)) = self.backdrop_texture {
+            unsafe {
+                self.context.delete_texture(texture);
+            }
+        }
+
+        if let Some(framebuffer) = self.backdrop_framebuffer {
+            unsafe {
+                self.context.delete_framebuffer(framebuffer);
+            }
+        }
+
         if let Some(vert_arr) = self.vert_arr {
             unsafe {
                 self.context.delete_vertex_array(vert_arr);
@@ -22,9 +22,15 @@ uniform vec4 frag[UNIFORMARRAY_SIZE];
 #define imageBlurFilterDirection frag[11].yz
 #define imageBlurFilterSigma frag[11].w
 #define imageBlurFilterCoeff frag[12].xyz
+#define blendMode int(frag[12].w)
+#define gradientType int(frag[13].x)
+#define gradientSpread int(frag[13].y)
+#define gradientColorSpace int(frag[13].z)
 
 uniform sampler2D tex;
 uniform sampler2D glyphtex;
+// Copy of the render target, read by blend modes other than normal
+uniform sampler2D dsttex;
 uniform vec2 viewSize;
 
 varying vec2 ftcoord;
@@ -38,6 +44,15 @@ varying vec2 fpos;
  #define SHADER_TYPE_FillColor 5
  #define SHADER_TYPE_TextureCopyUnclipped 6
 
+ #define GRADIENT_TYPE_Conic 1
+ #define GRADIENT_TYPE_Focal 2
+
+ #define GRADIENT_SPREAD_Repeat 1
+ #define GRADIENT_SPREAD_Reflect 2
+
+ #define GRADIENT_COLOR_SPACE_LinearSrgb 1
+ #define GRADIENT_COLOR_SPACE_Oklab 2
+
 float sdroundrect(vec2 pt, vec2 ext, float rad) {
     vec2 ext2 = ext - vec2(rad,rad);
     vec2 d = abs(pt) - ext2;
@@ -61,20 +76,114 @@ float strokeMask() {
 }
 #endif
 
-vec4 renderGradient() {
-    // Calculate gradient color using box gradient
+// Two point conical gradient: finds the largest t for which pt lies on the circle interpolated between
+// the focal circle (at the origin, with radius `radius`) and the end circle (at `extent`, with radius
+// `radius + feather`). Returns (t, 1.0), or (0.0, 0.0) if there is no such circle.
+vec2 focalGradientPosition(vec2 pt) {
+    float a = dot(extent, extent) - feather * feather;
+    float b = dot(pt, extent) + radius * feather;
+    float c = dot(pt, pt) - radius * radius;
+
+    if (abs(a) <= 1e-5 * (dot(extent, extent) + feather * feather)) {
+        // The focal point is on the edge of the end circle, there is only one solution
+        if (b == 0.0) return vec2(0.0);
+        float t = c / (2.0 * b);
+        return radius + t * feather >= 0.0 ? vec2(t, 1.0) : vec2(0.0);
+    }
+
+    float discriminant = b * b - a * c;
+    if (discriminant < 0.0) return vec2(0.0);
+
+    float t1 = (b + sqrt(discriminant)) / a;
+    float t2 = (b - sqrt(discriminant)) / a;
+    if (radius + max(t1, t2) * feather >= 0.0) return vec2(max(t1, t2), 1.0);
+    if (radius + min(t1, t2) * feather >= 0.0) return vec2(min(t1, t2), 1.0);
+    return vec2(0.0);
+}
+
+// Position along the gradient, from 0 to 1, or -1 where the gradient is not defined.
+float gradientPosition() {
     vec2 pt = (paintMat * vec3(fpos, 1.0)).xy;
 
-    float d = clamp((sdroundrect(pt, extent, radius) + feather*0.5) / feather, 0.0, 1.0);
-    return mix(innerCol,outerCol,d);
+    float d;
+    if (gradientType == GRADIENT_TYPE_Conic) {
+        // Fraction of the clockwise turn from the start angle
+        d = fract(atan(pt.y, pt.x) / 6.283185307179586);
+    } else if (gradientType == GRADIENT_TYPE_Focal) {
+        vec2 focal = focalGradientPosition(pt);
+        if (focal.y == 0.0) return -1.0;
+        d = focal.x;
+    } else {
+        // Distance to the box gradient
+        d = (sdroundrect(pt, extent, radius) + feather*0.5) / feather;
+    }
+
+    if (gradientSpread == GRADIENT_SPREAD_Repeat) return fract(d);
+    if (gradientSpread == GRADIENT_SPREAD_Reflect) return 1.0 - abs(mod(d, 2.0) - 1.0);
+    return clamp(d, 0.0, 1.0);
+}
+
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), c));
+}
+
+vec3 linearToSrgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(vec3(0.0031308), c));
+}
+
+vec3 linearSrgbToOklab(vec3 c) {
+    vec3 lms = vec3(
+        dot(c, vec3(0.4122214708, 0.5363325363, 0.0514459929)),
+        dot(c, vec3(0.2119034982, 0.6806995451, 0.1073969566)),
+        dot(c, vec3(0.0883024619, 0.2817188376, 0.6299787005)));
+    lms = pow(max(lms, vec3(0.0)), vec3(1.0 / 3.0));
+    return vec3(
+        dot(lms, vec3(0.2104542553, 0.7936177850, -0.0040720468)),
+        dot(lms, vec3(1.9779984951, -2.4285922050, 0.4505937099)),
+        dot(lms, vec3(0.0259040371, 0.7827717662, -0.8086757660)));
+}
+
+vec3 oklabToLinearSrgb(vec3 c) {
+    vec3 lms = vec3(
+        dot(c, vec3(1.0, 0.3963377774, 0.2158037573)),
+        dot(c, vec3(1.0, -0.1055613458, -0.0638541728)),
+        dot(c, vec3(1.0, -0.0894841775, -1.2914855480)));
+    lms = lms * lms * lms;
+    return vec3(
+        dot(lms, vec3(4.0767416621, -3.3077115913, 0.2309699292)),
+        dot(lms, vec3(-1.2684380046, 2.6097574011, -0.3413193965)),
+        dot(lms, vec3(-0.0041960863, -0.7034186147, 1.7076147010)));
+}
+
+// Interpolates two sRGB colors in the gradient's color space, returning a premultiplied color. The colors are
+// premultiplied for sRGB and unpremultiplied for the other color spaces.
+vec4 mixGradientColors(vec4 c0, vec4 c1, float t) {
+    if (gradientColorSpace != GRADIENT_COLOR_SPACE_LinearSrgb && gradientColorSpace != GRADIENT_COLOR_SPACE_Oklab) {
+        return mix(c0, c1, t);
+    }
+
+    vec3 a = srgbToLinear(c0.rgb);
+    vec3 b = srgbToLinear(c1.rgb);
+    vec3 rgb;
+    if (gradientColorSpace == GRADIENT_COLOR_SPACE_Oklab) {
+        rgb = oklabToLinearSrgb(mix(linearSrgbToOklab(a), linearSrgbToOklab(b), t));
+    } else {
+        rgb = mix(a, b, t);
+    }
+    float alpha = mix(c0.a, c1.a, t);
+    return vec4(linearToSrgb(clamp(rgb, 0.0, 1.0)) * alpha, alpha);
+}
+
+vec4 renderGradient() {
+    float d = gradientPosition();
+    if (d < 0.0) return vec4(0.0);
+    return mixGradientColors(innerCol,outerCol,d);
 }
 
 // Image-based Gradient; sample a texture using the gradient position.
 vec4 renderImageGradient() {
-    // Calculate gradient color using box gradient
-    vec2 pt = (paintMat * vec3(fpos, 1.0)).xy;
-
-    float d = clamp((sdroundrect(pt, extent, radius) + feather*0.5) / feather, 0.0, 1.0);
+    float d = gradientPosition();
+    if (d < 0.0) return vec4(0.0);
     return texture2D(tex, vec2(d, 0.0));//mix(innerCol,outerCol,d);
 }
 
@@ -133,6 +242,103 @@ vec4 renderFilteredImage() {
     return color;
 }
 
+// Blend modes, numbered like the variants of femtovg::BlendMode
+#define BLEND_MODE_Normal 0
+#define BLEND_MODE_Multiply 1
+#define BLEND_MODE_Screen 2
+#define BLEND_MODE_Overlay 3
+#define BLEND_MODE_Darken 4
+#define BLEND_MODE_Lighten 5
+#define BLEND_MODE_ColorDodge 6
+#define BLEND_MODE_ColorBurn 7
+#define BLEND_MODE_HardLight 8
+#define BLEND_MODE_SoftLight 9
+#define BLEND_MODE_Difference 10
+#define BLEND_MODE_Exclusion 11
+#define BLEND_MODE_Hue 12
+#define BLEND_MODE_Saturation 13
+#define BLEND_MODE_Color 14
+#define BLEND_MODE_Luminosity 15
+
+float blendScreen(float b, float s) {
+    return b + s - b * s;
+}
+
+float blendHardLight(float b, float s) {
+    return s <= 0.5 ? b * 2.0 * s : blendScreen(b, 2.0 * s - 1.0);
+}
+
+float blendChannel(int mode, float b, float s) {
+    if (mode == BLEND_MODE_Multiply) return b * s;
+    if (mode == BLEND_MODE_Screen) return blendScreen(b, s);
+    if (mode == BLEND_MODE_Overlay) return blendHardLight(s, b);
+    if (mode == BLEND_MODE_Darken) return min(b, s);
+    if (mode == BLEND_MODE_Lighten) return max(b, s);
+    if (mode == BLEND_MODE_ColorDodge) {
+        if (b <= 0.0) return 0.0;
+        if (s >= 1.0) return 1.0;
+        return min(1.0, b / (1.0 - s));
+    }
+    if (mode == BLEND_MODE_ColorBurn) {
+        if (b >= 1.0) return 1.0;
+        if (s <= 0.0) return 0.0;
+        return 1.0 - min(1.0, (1.0 - b) / s);
+    }
+    if (mode == BLEND_MODE_HardLight) return blendHardLight(b, s);
+    if (mode == BLEND_MODE_SoftLight) {
+        if (s <= 0.5) return b - (1.0 - 2.0 * s) * b * (1.0 - b);
+        float d = b <= 0.25 ? ((16.0 * b - 12.0) * b + 4.0) * b : sqrt(b);
+        return b + (2.0 * s - 1.0) * (d - b);
+    }
+    if (mode == BLEND_MODE_Difference) return abs(b - s);
+    if (mode == BLEND_MODE_Exclusion) return b + s - 2.0 * b * s;
+    return s;
+}
+
+float lum(vec3 c) {
+    return dot(c, vec3(0.3, 0.59, 0.11));
+}
+
+vec3 setLum(vec3 c, float l) {
+    c += l - lum(c);
+    l = lum(c);
+    float n = min(min(c.r, c.g), c.b);
+    float x = max(max(c.r, c.g), c.b);
+    if (n < 0.0) c = l + (c - l) * l / (l - n);
+    if (x > 1.0) c = l + (c - l) * (1.0 - l) / (x - l);
+    return c;
+}
+
+float sat(vec3 c) {
+    return max(max(c.r, c.g), c.b) - min(min(c.r, c.g), c.b);
+}
+
+vec3 setSat(vec3 c, float s) {
+    float n = min(min(c.r, c.g), c.b);
+    float x = max(max(c.r, c.g), c.b);
+    return x > n ? (c - n) * s / (x - n) : vec3(0.0);
+}
+
+vec3 blendColors(int mode, vec3 b, vec3 s) {
+    if (mode == BLEND_MODE_Hue) return setLum(setSat(s, sat(b)), lum(b));
+    if (mode == BLEND_MODE_Saturation) return setLum(setSat(b, sat(s)), lum(b));
+    if (mode == BLEND_MODE_Color) return setLum(s, lum(b));
+    if (mode == BLEND_MODE_Luminosity) return setLum(b, lum(s));
+    return vec3(blendChannel(mode, b.r, s.r), blendChannel(mode, b.g, s.g), blendChannel(mode, b.b, s.b));
+}
+
+// Mixes the premultiplied source color with the render target according to the blend mode.
+// The source alpha is left as is, so that the blend function composites the result.
+vec4 applyBlendMode(vec4 src) {
+    if (blendMode == BLEND_MODE_Normal || src.a <= 0.0) return src;
+
+    vec4 dst = texture2D(dsttex, gl_FragCoord.xy / viewSize);
+    vec3 cs = clamp(src.rgb / src.a, 0.0, 1.0);
+    vec3 cb = dst.a > 0.0 ? clamp(dst.rgb / dst.a, 0.0, 1.0) : vec3(0.0);
+
+    return vec4((1.0 - dst.a) * src.rgb + src.a * dst.a * blendColors(blendMode, cb, cs), src.a);
+}
+
 void main(void) {
     vec4 result;
 
@@ -160,7 +366,7 @@ void main(void) {
     result = innerCol;
 #elif SELECT_SHADER == SHADER_TYPE_TextureCopyUnclipped
     // Plain texture copy, unclipped
-    gl_FragColor = renderPlainTextureCopy();
+    gl_FragColor = applyBlendMode(renderPlainTextureCopy());
     return;
 #elif SELECT_SHADER == SHADER_TYPE_Stencil
     // Stencil fill
@@ -195,5 +401,9 @@ void main(void) {
 #endif
 #endif
 
+#if SELECT_SHADER != SHADER_TYPE_Stencil && SELECT_SHADER != SHADER_TYPE_FilterImage
+    result = applyBlendMode(result);
+#endif
+
     gl_FragColor = result;
 }
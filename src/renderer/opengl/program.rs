@@ -135,6 +135,7 @@ pub struct MainProgram {
     loc_viewsize: <glow::Context as glow::HasContext>::UniformLocation,
     loc_tex: Option<<glow::Context as glow::HasContext>::UniformLocation>,
     loc_glyphtex: Option<<glow::Context as glow::HasContext>::UniformLocation>,
+    loc_dsttex: Option<<glow::Context as glow::HasContext>::UniformLocation>,
     loc_frag: Option<<glow::Context as glow::HasContext>::UniformLocation>,
 }
 
@@ -172,6 +173,7 @@ impl MainProgram {
         let loc_viewsize = program.uniform_location("viewSize").unwrap();
         let loc_tex = program.uniform_location("tex");
         let loc_glyphtex = program.uniform_location("glyphtex");
+        let loc_dsttex = program.uniform_location("dsttex");
         let loc_frag = program.uniform_location("frag");
 
         Ok(Self {
@@ -180,6 +182,7 @@ impl MainProgram {
             loc_viewsize,
             loc_tex,
             loc_glyphtex,
+            loc_dsttex,
             loc_frag,
         })
     }
@@ -196,6 +199,12 @@ impl MainProgram {
         }
     }
 
+    pub(crate) fn set_dsttex(&self, tex: i32) {
+        unsafe {
+            self.context.uniform_1_i32(self.loc_dsttex.as_ref(), tex);
+        }
+    }
+
     pub(crate) fn set_view(&self, view: [f32; 2]) {
         unsafe {
             self.context.uniform_2_f32_slice(Some(&self.loc_viewsize), &view);
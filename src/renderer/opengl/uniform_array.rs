@@ -86,6 +86,22 @@ impl UniformArray {
     pub fn set_image_blur_filter_coeff(&mut self, coeff: [f32; 3]) {
         self.0[48..51].copy_from_slice(&coeff);
     }
+
+    pub fn set_blend_mode(&mut self, blend_mode: f32) {
+        self.0[51] = blend_mode;
+    }
+
+    pub fn set_gradient_type(&mut self, gradient_type: f32) {
+        self.0[52] = gradient_type;
+    }
+
+    pub fn set_gradient_spread(&mut self, gradient_spread: f32) {
+        self.0[53] = gradient_spread;
+    }
+
+    pub fn set_gradient_color_space(&mut self, gradient_color_space: f32) {
+        self.0[54] = gradient_color_space;
+    }
 }
 
 impl From<&Params> for UniformArray {
@@ -109,6 +125,9 @@ impl From<&Params> for UniformArray {
         arr.set_image_blur_filter_direction(params.image_blur_filter_direction);
         arr.set_image_blur_filter_sigma(params.image_blur_filter_sigma);
         arr.set_image_blur_filter_coeff(params.image_blur_filter_coeff);
+        arr.set_gradient_type(params.gradient_type as f32);
+        arr.set_gradient_spread(params.gradient_spread as f32);
+        arr.set_gradient_color_space(params.gradient_color_space as f32);
 
         arr
     }
@@ -1,6 +1,6 @@
 use crate::{
     geometry::Position,
-    paint::{GlyphTexture, GradientColors},
+    paint::{GlyphTexture, GradientColorSpace, GradientColors, GradientSpread},
     ImageFlags, ImageStore, PaintFlavor, PixelFormat, Scissor, Transform2D,
 };
 
@@ -25,9 +25,13 @@ pub struct Params {
     pub(crate) image_blur_filter_direction: [f32; 2],
     pub(crate) image_blur_filter_sigma: f32,
     pub(crate) image_blur_filter_coeff: [f32; 3],
+    pub(crate) gradient_type: u8,        // 0 -> distance to a rounded rect, 1 -> conic angle
+    pub(crate) gradient_spread: u8,      // 0 -> pad, 1 -> repeat, 2 -> reflect
+    pub(crate) gradient_color_space: u8, // 0 -> sRGB, 1 -> linear sRGB, 2 -> OKLab
 }
 
 impl Params {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new<T>(
         images: &ImageStore<T>,
         global_transform: &Transform2D,
@@ -159,16 +163,7 @@ impl Params {
                 params.extent[1] = large + d * 0.5;
                 params.feather = 1.0f32.max(d);
 
-                match colors {
-                    GradientColors::TwoStop { start_color, end_color } => {
-                        params.inner_col = start_color.premultiplied().to_array();
-                        params.outer_col = end_color.premultiplied().to_array();
-                        params.shader_type = ShaderType::FillGradient;
-                    }
-                    GradientColors::MultiStop { .. } => {
-                        params.shader_type = ShaderType::FillImageGradient;
-                    }
-                }
+                params.set_gradient_colors(colors);
             }
             &PaintFlavor::BoxGradient {
                 pos: Position { x, y },
@@ -186,16 +181,7 @@ impl Params {
                 params.extent[1] = height * 0.5;
                 params.radius = *radius;
                 params.feather = *feather;
-                match colors {
-                    GradientColors::TwoStop { start_color, end_color } => {
-                        params.inner_col = start_color.premultiplied().to_array();
-                        params.outer_col = end_color.premultiplied().to_array();
-                        params.shader_type = ShaderType::FillGradient;
-                    }
-                    GradientColors::MultiStop { .. } => {
-                        params.shader_type = ShaderType::FillImageGradient;
-                    }
-                }
+                params.set_gradient_colors(colors);
             }
             &PaintFlavor::RadialGradient {
                 center: Position { x: cx, y: cy },
@@ -214,16 +200,38 @@ impl Params {
                 params.extent[1] = r;
                 params.radius = r;
                 params.feather = 1.0f32.max(f);
-                match colors {
-                    GradientColors::TwoStop { start_color, end_color } => {
-                        params.inner_col = start_color.premultiplied().to_array();
-                        params.outer_col = end_color.premultiplied().to_array();
-                        params.shader_type = ShaderType::FillGradient;
-                    }
-                    GradientColors::MultiStop { .. } => {
-                        params.shader_type = ShaderType::FillImageGradient;
-                    }
-                }
+                params.set_gradient_colors(colors);
+            }
+            &PaintFlavor::ConicGradient {
+                center: Position { x: cx, y: cy },
+                start_angle,
+                colors,
+            } => {
+                let mut transform = Transform2D::rotation(*start_angle);
+                transform.translate(*cx, *cy);
+                transform *= *global_transform;
+                inv_transform = transform.inverse();
+
+                params.gradient_type = 1;
+                params.set_gradient_colors(colors);
+            }
+            &PaintFlavor::FocalGradient {
+                focal: Position { x: fx, y: fy },
+                focal_radius,
+                center: Position { x: cx, y: cy },
+                radius,
+                colors,
+            } => {
+                let mut transform = Transform2D::translation(*fx, *fy);
+                transform *= *global_transform;
+                inv_transform = transform.inverse();
+
+                // The shader interpolates from the focal circle at the origin to the end circle at `extent`.
+                params.extent = [cx - fx, cy - fy];
+                params.radius = *focal_radius;
+                params.feather = radius - focal_radius;
+                params.gradient_type = 2;
+                params.set_gradient_colors(colors);
             }
         }
 
@@ -232,6 +240,41 @@ impl Params {
         params
     }
 
+    fn set_gradient_colors(&mut self, colors: &GradientColors) {
+        self.gradient_spread = match colors.spread() {
+            GradientSpread::Pad => 0,
+            GradientSpread::Repeat => 1,
+            GradientSpread::Reflect => 2,
+        };
+
+        match colors {
+            GradientColors::TwoStop {
+                start_color, end_color, ..
+            } => {
+                self.shader_type = ShaderType::FillGradient;
+                self.gradient_color_space = match colors.color_space() {
+                    GradientColorSpace::Srgb => 0,
+                    GradientColorSpace::LinearSrgb => 1,
+                    GradientColorSpace::Oklab => 2,
+                };
+
+                // Other color spaces than sRGB interpolate unpremultiplied colors, like the multi stop
+                // gradient texture, so that transparent colors keep their hue.
+                if self.gradient_color_space == 0 {
+                    self.inner_col = start_color.premultiplied().to_array();
+                    self.outer_col = end_color.premultiplied().to_array();
+                } else {
+                    self.inner_col = start_color.to_array();
+                    self.outer_col = end_color.to_array();
+                }
+            }
+            GradientColors::MultiStop { .. } => {
+                // The color space is already baked into the gradient texture.
+                self.shader_type = ShaderType::FillImageGradient;
+            }
+        }
+    }
+
     pub(crate) fn uses_glyph_texture(self) -> bool {
         self.glyph_texture_type != 0
     }
@@ -0,0 +1,1317 @@
+//! A renderer that rasterizes on the CPU.
+//!
+//! The implementation follows the OpenGL backend closely: vertices are rasterized with the same coverage
+//! rules, the fragment stage is a port of `main-fs.glsl` and the stencil buffer is emulated so that concave
+//! fills and stencil strokes produce the same results as on the GPU.
+
+use imgref::ImgVec;
+use rgb::RGBA8;
+
+use crate::{
+    paint::{GlyphTexture, GradientColorSpace},
+    BlendFactor, BlendMode, Color, CompositeOperationState, ErrorKind, FillRule, ImageFilter, ImageFlags, ImageId,
+    ImageInfo, ImageSource, ImageStore, Scissor, Transform2D,
+};
+
+use super::{
+    Command, CommandType, Params, RenderTarget, Renderer, ShaderType, SurfacelessRenderer, Vertex, STENCIL_CLIP_BIT,
+};
+
+// Vertex positions are snapped to a fixed point grid with 8 bits of sub-pixel precision. Doing the edge tests
+// in integer arithmetic makes the fill convention exact, so triangles sharing an edge never touch the same
+// pixel twice.
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
+const MAX_COORD: f32 = 1_000_000.0;
+
+/// Renderer that draws into an RGBA8 pixel buffer on the CPU.
+///
+/// Useful for headless rendering, for example on servers or in tests. The rendered frame can be read back
+/// with [`Canvas::screenshot`](crate::Canvas::screenshot).
+///
+/// ```
+/// use femtovg::{renderer::Software, Canvas, Color, Paint, Path};
+///
+/// let mut canvas = Canvas::new(Software::new()).unwrap();
+/// canvas.set_size(64, 64, 1.0);
+///
+/// let mut path = Path::new();
+/// path.rect(8.0, 8.0, 48.0, 48.0);
+/// canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+/// canvas.flush();
+///
+/// let image = canvas.screenshot().unwrap();
+/// assert_eq!(image.buf()[32 * 64 + 32].r, 255);
+/// ```
+pub struct Software {
+    view: [usize; 2],
+    screen_view: [usize; 2],
+    screen: Vec<RGBA8>,
+    stencil: Vec<u8>,
+    current_render_target: RenderTarget,
+}
+
+impl Software {
+    /// Creates a new software renderer. The size of the screen buffer is set by
+    /// [`Canvas::set_size`](crate::Canvas::set_size).
+    pub fn new() -> Self {
+        Self {
+            view: [0, 0],
+            screen_view: [0, 0],
+            screen: Vec::new(),
+            stencil: Vec::new(),
+            current_render_target: RenderTarget::Screen,
+        }
+    }
+
+    /// Runs `draw` against the pixels of the current render target.
+    ///
+    /// When rendering into an image its pixels are temporarily moved out of the image store, so that the
+    /// store can still be borrowed for texture lookups.
+    fn with_target(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        draw: impl FnOnce(&mut Target<'_>, &ImageStore<SoftwareImage>),
+    ) {
+        let [width, height] = self.view;
+
+        if self.stencil.len() != width * height {
+            self.stencil.clear();
+            self.stencil.resize(width * height, 0);
+        }
+
+        match self.current_render_target {
+            RenderTarget::Screen => {
+                let mut target = Target {
+                    pixels: &mut self.screen,
+                    stencil: &mut self.stencil,
+                    width,
+                    height,
+                    flip_y: false,
+                };
+                draw(&mut target, images);
+            }
+            RenderTarget::Image(id) => {
+                let Some(image) = images.get_mut(id) else {
+                    return;
+                };
+
+                let mut pixels = std::mem::take(&mut image.pixels);
+
+                // Same as with OpenGL framebuffers, the first row of a render target image is the bottom
+                // row of the rendered scene. Images used as render targets are therefore usually created
+                // with `ImageFlags::FLIP_Y`.
+                let mut target = Target {
+                    pixels: &mut pixels,
+                    stencil: &mut self.stencil,
+                    width,
+                    height,
+                    flip_y: true,
+                };
+                draw(&mut target, images);
+
+                if let Some(image) = images.get_mut(id) {
+                    image.pixels = pixels;
+                }
+            }
+        }
+    }
+
+    fn convex_fill(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        cmd: &Command,
+        verts: &[Vertex],
+        params: &Params,
+    ) {
+        self.with_target(images, |target, images| {
+            let shader = Shader::new(images, params, cmd.image, cmd.glyph_texture);
+            let state = DrawState {
+                stencil_test: clip_test(cmd),
+                ..DrawState::color(cmd.composite_operation)
+            };
+
+            for drawable in &cmd.drawables {
+                if let Some(range) = drawable.fill_verts {
+                    target.draw(vert_range(verts, range), Topology::Triangles, &state, Some(&shader));
+                }
+
+                if let Some(range) = drawable.stroke_verts {
+                    target.draw(vert_range(verts, range), Topology::TriangleStrip, &state, Some(&shader));
+                }
+            }
+        });
+    }
+
+    fn concave_fill(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        cmd: &Command,
+        verts: &[Vertex],
+        fill_params: &Params,
+    ) {
+        self.with_target(images, |target, images| {
+            let mask = fill_mask(cmd.fill_rule);
+            let clip_mask = if cmd.clip { STENCIL_CLIP_BIT } else { 0 };
+
+            // Count the winding of every pixel covered by the fill triangles that is inside the clip.
+            let stencil_state = DrawState {
+                cull: false,
+                stencil_test: StencilTest::Equal(clip_mask),
+                stencil_op: StencilOp::IncrDecrWrap,
+                stencil_write_mask: !STENCIL_CLIP_BIT,
+                blend: None,
+            };
+
+            for drawable in &cmd.drawables {
+                if let Some(range) = drawable.fill_verts {
+                    target.draw(vert_range(verts, range), Topology::Triangles, &stencil_state, None);
+                }
+            }
+
+            let shader = Shader::new(images, fill_params, cmd.image, cmd.glyph_texture);
+
+            // Draw anti-aliased pixels outside of the filled area.
+            let fringe_state = DrawState {
+                stencil_test: StencilTest::Equal(mask | clip_mask),
+                ..DrawState::color(cmd.composite_operation)
+            };
+
+            for drawable in &cmd.drawables {
+                if let Some(range) = drawable.stroke_verts {
+                    target.draw(
+                        vert_range(verts, range),
+                        Topology::TriangleStrip,
+                        &fringe_state,
+                        Some(&shader),
+                    );
+                }
+            }
+
+            // Cover the bounds, filling where the stencil is set and resetting it everywhere.
+            let cover_state = DrawState {
+                stencil_test: StencilTest::NotEqual(mask),
+                stencil_op: StencilOp::Zero,
+                ..DrawState::color(cmd.composite_operation)
+            };
+
+            if let Some(range) = cmd.triangles_verts {
+                target.draw(
+                    vert_range(verts, range),
+                    Topology::TriangleStrip,
+                    &cover_state,
+                    Some(&shader),
+                );
+            }
+        });
+    }
+
+    fn stroke(&mut self, images: &mut ImageStore<SoftwareImage>, cmd: &Command, verts: &[Vertex], params: &Params) {
+        self.with_target(images, |target, images| {
+            let shader = Shader::new(images, params, cmd.image, cmd.glyph_texture);
+            let state = DrawState {
+                stencil_test: clip_test(cmd),
+                ..DrawState::color(cmd.composite_operation)
+            };
+
+            for drawable in &cmd.drawables {
+                if let Some(range) = drawable.stroke_verts {
+                    target.draw(vert_range(verts, range), Topology::TriangleStrip, &state, Some(&shader));
+                }
+            }
+        });
+    }
+
+    fn stencil_stroke(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        cmd: &Command,
+        verts: &[Vertex],
+        params1: &Params,
+        params2: &Params,
+    ) {
+        let test_mask = if cmd.clip { 0xff } else { !STENCIL_CLIP_BIT };
+
+        self.with_target(images, |target, images| {
+            let passes = [
+                // Fill the stroke base without overlap
+                (
+                    DrawState {
+                        stencil_test: StencilTest::Equal(test_mask),
+                        stencil_op: StencilOp::Incr,
+                        ..DrawState::color(cmd.composite_operation)
+                    },
+                    Some(Shader::new(images, params2, cmd.image, cmd.glyph_texture)),
+                ),
+                // Draw anti-aliased pixels.
+                (
+                    DrawState {
+                        stencil_test: StencilTest::Equal(test_mask),
+                        ..DrawState::color(cmd.composite_operation)
+                    },
+                    Some(Shader::new(images, params1, cmd.image, cmd.glyph_texture)),
+                ),
+                // Clear stencil buffer.
+                (
+                    DrawState {
+                        cull: true,
+                        stencil_test: StencilTest::Always,
+                        stencil_op: StencilOp::Zero,
+                        stencil_write_mask: !STENCIL_CLIP_BIT,
+                        blend: None,
+                    },
+                    None,
+                ),
+            ];
+
+            for (state, shader) in &passes {
+                for drawable in &cmd.drawables {
+                    if let Some(range) = drawable.stroke_verts {
+                        target.draw(
+                            vert_range(verts, range),
+                            Topology::TriangleStrip,
+                            state,
+                            shader.as_ref(),
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    fn triangles(&mut self, images: &mut ImageStore<SoftwareImage>, cmd: &Command, verts: &[Vertex], params: &Params) {
+        self.with_target(images, |target, images| {
+            let shader = Shader::new(images, params, cmd.image, cmd.glyph_texture);
+            let state = DrawState {
+                stencil_test: clip_test(cmd),
+                ..DrawState::color(cmd.composite_operation)
+            };
+
+            if let Some(range) = cmd.triangles_verts {
+                target.draw(vert_range(verts, range), Topology::Triangles, &state, Some(&shader));
+            }
+        });
+    }
+
+    fn reset_clip(&mut self, images: &mut ImageStore<SoftwareImage>, cmd: &Command, verts: &[Vertex]) {
+        self.with_target(images, |target, _| {
+            let state = DrawState {
+                cull: false,
+                stencil_test: StencilTest::Always,
+                stencil_op: StencilOp::Zero,
+                stencil_write_mask: STENCIL_CLIP_BIT,
+                blend: None,
+            };
+
+            if let Some(range) = cmd.triangles_verts {
+                target.draw(vert_range(verts, range), Topology::TriangleStrip, &state, None);
+            }
+        });
+    }
+
+    fn intersect_clip(&mut self, images: &mut ImageStore<SoftwareImage>, cmd: &Command, verts: &[Vertex]) {
+        self.with_target(images, |target, _| {
+            // Count the winding of the clip path in the lower bits.
+            let stencil_state = DrawState {
+                cull: false,
+                stencil_test: StencilTest::Always,
+                stencil_op: StencilOp::IncrDecrWrap,
+                stencil_write_mask: !STENCIL_CLIP_BIT,
+                blend: None,
+            };
+
+            for drawable in &cmd.drawables {
+                if let Some(range) = drawable.fill_verts {
+                    target.draw(vert_range(verts, range), Topology::Triangles, &stencil_state, None);
+                }
+            }
+
+            // Mark the pixels outside of the clip path as clipped, then reset the winding counts.
+            let passes = [
+                DrawState {
+                    cull: false,
+                    stencil_test: StencilTest::Equal(fill_mask(cmd.fill_rule)),
+                    stencil_op: StencilOp::Replace(STENCIL_CLIP_BIT),
+                    stencil_write_mask: STENCIL_CLIP_BIT,
+                    blend: None,
+                },
+                DrawState {
+                    cull: false,
+                    stencil_test: StencilTest::Always,
+                    stencil_op: StencilOp::Zero,
+                    stencil_write_mask: !STENCIL_CLIP_BIT,
+                    blend: None,
+                },
+            ];
+
+            if let Some(range) = cmd.triangles_verts {
+                for state in &passes {
+                    target.draw(vert_range(verts, range), Topology::TriangleStrip, state, None);
+                }
+            }
+        });
+    }
+
+    fn clear_rect(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        color: Color,
+    ) {
+        let color = to_rgba8(color.to_array());
+
+        self.with_target(images, |target, _| {
+            for py in y..(y + height).min(target.height) {
+                for px in x..(x + width).min(target.width) {
+                    let index = target.index(px, py);
+                    target.pixels[index] = color;
+                    target.stencil[index] = 0;
+                }
+            }
+        });
+    }
+
+    fn set_target(&mut self, images: &ImageStore<SoftwareImage>, target: RenderTarget) {
+        self.current_render_target = target;
+
+        match target {
+            RenderTarget::Screen => self.view = self.screen_view,
+            RenderTarget::Image(id) => {
+                if let Some(info) = images.info(id) {
+                    self.view = [info.width(), info.height()];
+                }
+            }
+        }
+    }
+
+    fn render_filtered_image(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        cmd: Command,
+        verts: &[Vertex],
+        target_image: ImageId,
+        filter: ImageFilter,
+    ) {
+        match filter {
+            ImageFilter::GaussianBlur { sigma } => self.render_gaussian_blur(images, cmd, verts, target_image, sigma),
+        }
+    }
+
+    fn render_gaussian_blur(
+        &mut self,
+        images: &mut ImageStore<SoftwareImage>,
+        mut cmd: Command,
+        verts: &[Vertex],
+        target_image: ImageId,
+        sigma: f32,
+    ) {
+        let original_render_target = self.current_render_target;
+
+        // The filtering happens in two passes, first a horizontal blur and then the vertical blur. The
+        // first pass therefore renders into an intermediate, temporarily allocated image.
+
+        let Some(source_image_info) = cmd.image.and_then(|id| images.info(id)) else {
+            return;
+        };
+
+        let image_paint = crate::Paint::image(
+            cmd.image.unwrap(),
+            0.,
+            0.,
+            source_image_info.width() as _,
+            source_image_info.height() as _,
+            0.,
+            1.,
+        );
+        let mut blur_params = Params::new(
+            images,
+            &Transform2D::default(),
+            &image_paint.flavor,
+            &GlyphTexture::default(),
+            &Scissor::default(),
+            0.,
+            0.,
+            0.,
+        );
+        blur_params.shader_type = ShaderType::FilterImage;
+
+        let gauss_coeff_x = 1. / ((2. * std::f32::consts::PI).sqrt() * sigma);
+        let gauss_coeff_y = f32::exp(-0.5 / (sigma * sigma));
+        let gauss_coeff_z = gauss_coeff_y * gauss_coeff_y;
+
+        blur_params.image_blur_filter_coeff = [gauss_coeff_x, gauss_coeff_y, gauss_coeff_z];
+        blur_params.image_blur_filter_direction = [1.0, 0.0];
+
+        // Keep the same upper bound on the kernel size as the GPU backends.
+        blur_params.image_blur_filter_sigma = sigma.min(8.);
+
+        let Ok(horizontal_blur_buffer) = images.alloc(self, source_image_info) else {
+            return;
+        };
+
+        let (width, height) = (source_image_info.width(), source_image_info.height());
+
+        self.set_target(images, RenderTarget::Image(horizontal_blur_buffer));
+        self.clear_rect(images, 0, 0, width, height, Color::rgbaf(0., 0., 0., 0.));
+        self.triangles(images, &cmd, verts, &blur_params);
+
+        self.set_target(images, RenderTarget::Image(target_image));
+        self.clear_rect(images, 0, 0, width, height, Color::rgbaf(0., 0., 0., 0.));
+
+        blur_params.image_blur_filter_direction = [0.0, 1.0];
+
+        cmd.image = Some(horizontal_blur_buffer);
+
+        self.triangles(images, &cmd, verts, &blur_params);
+
+        images.remove(self, horizontal_blur_buffer);
+
+        self.set_target(images, original_render_target);
+    }
+}
+
+impl Default for Software {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for Software {
+    type Image = SoftwareImage;
+    type NativeTexture = ();
+    type Surface = ();
+    type CommandBuffer = ();
+
+    fn set_size(&mut self, width: u32, height: u32, _dpi: f32) {
+        let size = [width as usize, height as usize];
+
+        if size != self.screen_view {
+            self.screen = vec![RGBA8::default(); size[0] * size[1]];
+            self.screen_view = size;
+        }
+
+        if self.current_render_target == RenderTarget::Screen {
+            self.view = size;
+        }
+    }
+
+    fn render(
+        &mut self,
+        _surface: &Self::Surface,
+        images: &mut ImageStore<Self::Image>,
+        verts: &[Vertex],
+        commands: Vec<Command>,
+    ) {
+        for cmd in commands {
+            match cmd.cmd_type {
+                CommandType::ConvexFill { ref params } => self.convex_fill(images, &cmd, verts, params),
+                // The stencil pass doesn't write colors, so there is no need to evaluate its paint.
+                CommandType::ConcaveFill { ref fill_params, .. } => self.concave_fill(images, &cmd, verts, fill_params),
+                CommandType::Stroke { ref params } => self.stroke(images, &cmd, verts, params),
+                CommandType::StencilStroke {
+                    ref params1,
+                    ref params2,
+                } => self.stencil_stroke(images, &cmd, verts, params1, params2),
+                CommandType::Triangles { ref params } => self.triangles(images, &cmd, verts, params),
+                CommandType::ClearRect { color } => {
+                    if let Some((start, _)) = cmd.triangles_verts {
+                        let x = verts[start].x as usize;
+                        let y = verts[start].y as usize;
+                        let width = (verts[start + 1].x as usize).saturating_sub(x);
+                        let height = (verts[start + 1].y as usize).saturating_sub(y);
+                        self.clear_rect(images, x, y, width, height, color);
+                    }
+                }
+                CommandType::SetRenderTarget(target) => self.set_target(images, target),
+                CommandType::RenderFilteredImage { target_image, filter } => {
+                    self.render_filtered_image(images, cmd, verts, target_image, filter)
+                }
+                CommandType::ResetClip => self.reset_clip(images, &cmd, verts),
+                CommandType::IntersectClip => self.intersect_clip(images, &cmd, verts),
+            }
+        }
+    }
+
+    fn alloc_image(&mut self, info: ImageInfo) -> Result<Self::Image, ErrorKind> {
+        Ok(SoftwareImage {
+            info,
+            pixels: vec![RGBA8::default(); info.width() * info.height()],
+        })
+    }
+
+    fn create_image_from_native_texture(
+        &mut self,
+        _native_texture: Self::NativeTexture,
+        _info: ImageInfo,
+    ) -> Result<Self::Image, ErrorKind> {
+        Err(ErrorKind::UnsupportedImageFormat)
+    }
+
+    fn update_image(
+        &mut self,
+        image: &mut Self::Image,
+        data: ImageSource,
+        x: usize,
+        y: usize,
+    ) -> Result<(), ErrorKind> {
+        let size = data.dimensions();
+
+        if x + size.width > image.info.width() {
+            return Err(ErrorKind::ImageUpdateOutOfBounds);
+        }
+
+        if y + size.height > image.info.height() {
+            return Err(ErrorKind::ImageUpdateOutOfBounds);
+        }
+
+        if image.info.format() != data.format() {
+            return Err(ErrorKind::ImageUpdateWithDifferentFormat);
+        }
+
+        let stride = image.info.width();
+
+        // Pixels are stored the way an OpenGL texture would return them when sampled.
+        match data {
+            ImageSource::Gray(src) => {
+                for (row_index, row) in src.rows().enumerate() {
+                    let offset = (y + row_index) * stride + x;
+                    for (dst, gray) in image.pixels[offset..offset + row.len()].iter_mut().zip(row) {
+                        *dst = RGBA8::new(gray.value(), 0, 0, 255);
+                    }
+                }
+            }
+            ImageSource::Rgb(src) => {
+                for (row_index, row) in src.rows().enumerate() {
+                    let offset = (y + row_index) * stride + x;
+                    for (dst, rgb) in image.pixels[offset..offset + row.len()].iter_mut().zip(row) {
+                        *dst = rgb.with_alpha(255);
+                    }
+                }
+            }
+            ImageSource::Rgba(src) => {
+                for (row_index, row) in src.rows().enumerate() {
+                    let offset = (y + row_index) * stride + x;
+                    image.pixels[offset..offset + row.len()].copy_from_slice(row);
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            ImageSource::HtmlImageElement(_) => return Err(ErrorKind::UnsupportedImageFormat),
+        }
+
+        Ok(())
+    }
+
+    fn delete_image(&mut self, _image: Self::Image, _image_id: ImageId) {}
+
+    fn screenshot(&mut self) -> Result<ImgVec<RGBA8>, ErrorKind> {
+        let [width, height] = self.screen_view;
+        Ok(ImgVec::new(self.screen.clone(), width, height))
+    }
+}
+
+impl SurfacelessRenderer for Software {
+    fn render_surfaceless(&mut self, images: &mut ImageStore<Self::Image>, verts: &[Vertex], commands: Vec<Command>) {
+        self.render(&(), images, verts, commands)
+    }
+}
+
+/// Image type of the [`Software`] renderer.
+pub struct SoftwareImage {
+    info: ImageInfo,
+    pixels: Vec<RGBA8>,
+}
+
+impl SoftwareImage {
+    fn texel(&self, x: i64, y: i64) -> [f32; 4] {
+        let flags = self.info.flags();
+        let x = wrap(x, self.info.width(), flags.contains(ImageFlags::REPEAT_X));
+        let y = wrap(y, self.info.height(), flags.contains(ImageFlags::REPEAT_Y));
+        let RGBA8 { r, g, b, a } = self.pixels[y * self.info.width() + x];
+        [r, g, b, a].map(|c| c as f32 / 255.0)
+    }
+
+    /// Samples the image at normalized texture coordinates, like `texture2D()` in GLSL.
+    fn sample(&self, uv: [f32; 2]) -> [f32; 4] {
+        if self.pixels.is_empty() {
+            return [0.0; 4];
+        }
+
+        let x = uv[0] * self.info.width() as f32;
+        let y = uv[1] * self.info.height() as f32;
+
+        if self.info.flags().contains(ImageFlags::NEAREST) {
+            return self.texel(x.floor() as i64, y.floor() as i64);
+        }
+
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = mix(self.texel(x0, y0), self.texel(x0 + 1, y0), fx);
+        let bottom = mix(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), fx);
+        mix(top, bottom, fy)
+    }
+}
+
+fn wrap(i: i64, size: usize, repeat: bool) -> usize {
+    if repeat {
+        i.rem_euclid(size as i64) as usize
+    } else {
+        i.clamp(0, size as i64 - 1) as usize
+    }
+}
+
+/// The pixels and stencil buffer that are drawn to.
+struct Target<'a> {
+    pixels: &'a mut [RGBA8],
+    stencil: &'a mut [u8],
+    width: usize,
+    height: usize,
+    flip_y: bool,
+}
+
+#[derive(Copy, Clone)]
+enum Topology {
+    Triangles,
+    TriangleStrip,
+}
+
+#[derive(Copy, Clone)]
+enum StencilTest {
+    Always,
+    /// Passes if the masked stencil value is zero.
+    Equal(u8),
+    /// Passes if the masked stencil value is not zero.
+    NotEqual(u8),
+}
+
+#[derive(Copy, Clone)]
+enum StencilOp {
+    Keep,
+    /// Increments for front facing and decrements for back facing triangles, wrapping around.
+    IncrDecrWrap,
+    /// Increments and clamps.
+    Incr,
+    /// Resets the stencil value, regardless of whether the stencil test passed.
+    Zero,
+    /// Replaces the stencil value.
+    Replace(u8),
+}
+
+#[derive(Copy, Clone)]
+struct DrawState {
+    cull: bool,
+    stencil_test: StencilTest,
+    stencil_op: StencilOp,
+    /// The bits of the stencil value that are updated by the stencil operation.
+    stencil_write_mask: u8,
+    /// `None` disables writing colors.
+    blend: Option<CompositeOperationState>,
+}
+
+impl DrawState {
+    fn color(composite_operation: CompositeOperationState) -> Self {
+        Self {
+            cull: true,
+            stencil_test: StencilTest::Always,
+            stencil_op: StencilOp::Keep,
+            stencil_write_mask: !STENCIL_CLIP_BIT,
+            blend: Some(composite_operation),
+        }
+    }
+}
+
+impl Target<'_> {
+    fn index(&self, x: usize, y: usize) -> usize {
+        let y = if self.flip_y { self.height - 1 - y } else { y };
+        y * self.width + x
+    }
+
+    fn draw(&mut self, verts: &[Vertex], topology: Topology, state: &DrawState, shader: Option<&Shader<'_>>) {
+        match topology {
+            Topology::Triangles => {
+                for triangle in verts.chunks_exact(3) {
+                    self.draw_triangle([&triangle[0], &triangle[1], &triangle[2]], state, shader);
+                }
+            }
+            Topology::TriangleStrip => {
+                for (i, triangle) in verts.windows(3).enumerate() {
+                    // Every other triangle of a strip has its winding reversed.
+                    if i % 2 == 0 {
+                        self.draw_triangle([&triangle[0], &triangle[1], &triangle[2]], state, shader);
+                    } else {
+                        self.draw_triangle([&triangle[1], &triangle[0], &triangle[2]], state, shader);
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_triangle(&mut self, mut verts: [&Vertex; 3], state: &DrawState, shader: Option<&Shader<'_>>) {
+        let to_fixed = |v: &Vertex| {
+            [
+                (v.x.clamp(-MAX_COORD, MAX_COORD) * SUBPIXEL_ONE as f32).round() as i64,
+                (v.y.clamp(-MAX_COORD, MAX_COORD) * SUBPIXEL_ONE as f32).round() as i64,
+            ]
+        };
+
+        let mut p = verts.map(to_fixed);
+        let mut area = edge(p[0], p[1], p[2]);
+
+        if area == 0 {
+            return;
+        }
+
+        // Positions are in screen space where y points down, so triangles that are counter-clockwise in
+        // OpenGL's normalized device coordinates have a negative area here.
+        let front = area < 0;
+
+        if state.cull && !front {
+            return;
+        }
+
+        if area < 0 {
+            verts.swap(1, 2);
+            p.swap(1, 2);
+            area = -area;
+        }
+
+        let min_x = (p.iter().map(|p| p[0]).min().unwrap_or(0) >> SUBPIXEL_BITS).max(0);
+        let min_y = (p.iter().map(|p| p[1]).min().unwrap_or(0) >> SUBPIXEL_BITS).max(0);
+        let max_x = (p.iter().map(|p| p[0]).max().unwrap_or(0) >> SUBPIXEL_BITS).min(self.width as i64 - 1);
+        let max_y = (p.iter().map(|p| p[1]).max().unwrap_or(0) >> SUBPIXEL_BITS).min(self.height as i64 - 1);
+
+        let area = area as f32;
+
+        for py in min_y..=max_y {
+            let cy = py * SUBPIXEL_ONE + SUBPIXEL_HALF;
+
+            for px in min_x..=max_x {
+                let c = [px * SUBPIXEL_ONE + SUBPIXEL_HALF, cy];
+
+                let w0 = edge(p[1], p[2], c);
+                let w1 = edge(p[2], p[0], c);
+                let w2 = edge(p[0], p[1], c);
+
+                if !(covers(w0, p[1], p[2]) && covers(w1, p[2], p[0]) && covers(w2, p[0], p[1])) {
+                    continue;
+                }
+
+                let (l1, l2) = (w1 as f32 / area, w2 as f32 / area);
+                let l0 = 1.0 - l1 - l2;
+
+                let uv = [
+                    verts[0].u * l0 + verts[1].u * l1 + verts[2].u * l2,
+                    verts[0].v * l0 + verts[1].v * l1 + verts[2].v * l2,
+                ];
+                let fpos = [px as f32 + 0.5, py as f32 + 0.5];
+
+                let index = self.index(px as usize, py as usize);
+                self.fragment(index, front, fpos, uv, state, shader);
+            }
+        }
+    }
+
+    fn fragment(
+        &mut self,
+        index: usize,
+        front: bool,
+        fpos: [f32; 2],
+        uv: [f32; 2],
+        state: &DrawState,
+        shader: Option<&Shader<'_>>,
+    ) {
+        let stencil = &mut self.stencil[index];
+
+        let passed = match state.stencil_test {
+            StencilTest::Always => true,
+            StencilTest::Equal(mask) => *stencil & mask == 0,
+            StencilTest::NotEqual(mask) => *stencil & mask != 0,
+        };
+
+        let write_mask = state.stencil_write_mask;
+
+        if !passed {
+            if let StencilOp::Zero = state.stencil_op {
+                *stencil &= !write_mask;
+            }
+            return;
+        }
+
+        // Like a fragment shader discarding, rejected fragments don't update the stencil buffer.
+        let color = match (state.blend, shader) {
+            (Some(blend), Some(shader)) => match shader.shade(fpos, uv) {
+                Some(color) => Some((color, blend)),
+                None => return,
+            },
+            _ => None,
+        };
+
+        let value = match state.stencil_op {
+            StencilOp::Keep => *stencil,
+            StencilOp::IncrDecrWrap if front => stencil.wrapping_add(1),
+            StencilOp::IncrDecrWrap => stencil.wrapping_sub(1),
+            StencilOp::Incr => stencil.saturating_add(1),
+            StencilOp::Zero => 0,
+            StencilOp::Replace(value) => value,
+        };
+
+        *stencil = (*stencil & !write_mask) | (value & write_mask);
+
+        if let Some((color, blend)) = color {
+            let dst = &mut self.pixels[index];
+            *dst = blend_pixel(color, *dst, blend);
+        }
+    }
+}
+
+/// The stencil test that masks a draw call by the clip, if the command is clipped.
+fn clip_test(cmd: &Command) -> StencilTest {
+    if cmd.clip {
+        StencilTest::Equal(STENCIL_CLIP_BIT)
+    } else {
+        StencilTest::Always
+    }
+}
+
+/// The bits of the stencil value that count the winding for the fill rule.
+fn fill_mask(fill_rule: FillRule) -> u8 {
+    match fill_rule {
+        FillRule::NonZero => !STENCIL_CLIP_BIT,
+        FillRule::EvenOdd => 0x1,
+    }
+}
+
+fn edge(a: [i64; 2], b: [i64; 2], c: [i64; 2]) -> i64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Top-left fill convention: pixel centers exactly on an edge belong to the triangle only if the edge is a
+/// top or a left edge.
+fn covers(w: i64, a: [i64; 2], b: [i64; 2]) -> bool {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    w > 0 || (w == 0 && ((dy == 0 && dx > 0) || dy < 0))
+}
+
+fn vert_range(verts: &[Vertex], (start, count): (usize, usize)) -> &[Vertex] {
+    &verts[start..start + count]
+}
+
+/// Port of the fragment stage of `main-fs.glsl`.
+struct Shader<'a> {
+    params: &'a Params,
+    image: Option<&'a SoftwareImage>,
+    glyph_texture: Option<&'a SoftwareImage>,
+}
+
+impl<'a> Shader<'a> {
+    fn new(
+        images: &'a ImageStore<SoftwareImage>,
+        params: &'a Params,
+        image: Option<ImageId>,
+        glyph_texture: GlyphTexture,
+    ) -> Self {
+        Self {
+            params,
+            image: image.and_then(|id| images.get(id)),
+            glyph_texture: glyph_texture.image_id().and_then(|id| images.get(id)),
+        }
+    }
+
+    /// Returns the premultiplied color of a fragment, or `None` if the fragment is discarded.
+    fn shade(&self, fpos: [f32; 2], uv: [f32; 2]) -> Option<[f32; 4]> {
+        let params = self.params;
+
+        if params.shader_type == ShaderType::TextureCopyUnclipped {
+            return Some(self.texture_copy(uv));
+        }
+
+        let stroke_alpha = stroke_mask(uv, params.stroke_mult);
+
+        if stroke_alpha < params.stroke_thr {
+            return None;
+        }
+
+        let mut result = match params.shader_type {
+            ShaderType::FillGradient => match self.gradient_position(fpos) {
+                Some(d) => mix_gradient_colors(params.inner_col, params.outer_col, d, params.gradient_color_space),
+                None => [0.0; 4],
+            },
+            ShaderType::FillImageGradient => match self.gradient_position(fpos) {
+                Some(d) => self.sample_image([d, 0.0]),
+                None => [0.0; 4],
+            },
+            ShaderType::FillImage => self.image(fpos),
+            ShaderType::FillColor | ShaderType::FillColorUnclipped => params.inner_col,
+            ShaderType::Stencil => [1.0; 4],
+            ShaderType::FilterImage => self.filtered_image(fpos),
+            ShaderType::TextureCopyUnclipped => unreachable!(),
+        };
+
+        let scissor = if params.shader_type == ShaderType::FillColorUnclipped {
+            1.0
+        } else {
+            self.scissor_mask(fpos)
+        };
+
+        if params.uses_glyph_texture() {
+            let mut mask = self.glyph_texture.map_or([0.0; 4], |texture| texture.sample(uv));
+
+            if params.glyph_texture_type == 1 {
+                mask = [mask[0]; 4];
+            } else {
+                result = [1.0; 4];
+                mask = premultiply(mask);
+            }
+
+            for (channel, mask) in result.iter_mut().zip(mask) {
+                *channel *= mask * scissor;
+            }
+        } else if !matches!(params.shader_type, ShaderType::Stencil | ShaderType::FilterImage) {
+            result = result.map(|channel| channel * stroke_alpha * scissor);
+        }
+
+        Some(result)
+    }
+
+    fn sample_image(&self, uv: [f32; 2]) -> [f32; 4] {
+        self.image.map_or([0.0, 0.0, 0.0, 1.0], |image| image.sample(uv))
+    }
+
+    /// Returns the position along the gradient, or `None` where the gradient is not defined.
+    fn gradient_position(&self, fpos: [f32; 2]) -> Option<f32> {
+        let params = self.params;
+        let pt = transform_point(&params.paint_mat, fpos);
+
+        let d = match params.gradient_type {
+            // Fraction of the clockwise turn from the start angle.
+            1 => (pt[1].atan2(pt[0]) / std::f32::consts::TAU).rem_euclid(1.0),
+            2 => focal_gradient_position(pt, params.extent, params.radius, params.feather)?,
+            _ => (sdroundrect(pt, params.extent, params.radius) + params.feather * 0.5) / params.feather,
+        };
+
+        Some(match params.gradient_spread {
+            1 => clamp01(d.rem_euclid(1.0)),
+            2 => clamp01(1.0 - (d.rem_euclid(2.0) - 1.0).abs()),
+            _ => clamp01(d),
+        })
+    }
+
+    fn image(&self, fpos: [f32; 2]) -> [f32; 4] {
+        let params = self.params;
+        let pt = transform_point(&params.paint_mat, fpos);
+        let color = self.tex_type(self.sample_image([pt[0] / params.extent[0], pt[1] / params.extent[1]]));
+        mul(color, params.inner_col)
+    }
+
+    fn texture_copy(&self, uv: [f32; 2]) -> [f32; 4] {
+        let color = self.tex_type(self.sample_image(uv));
+        mul(color, self.params.inner_col)
+    }
+
+    fn filtered_image(&self, fpos: [f32; 2]) -> [f32; 4] {
+        let params = self.params;
+        let [dx, dy] = params.image_blur_filter_direction;
+        let [ext_x, ext_y] = params.extent;
+        let sample_count = (1.5 * params.image_blur_filter_sigma).ceil();
+
+        let mut gaussian_coeff = params.image_blur_filter_coeff;
+
+        let mut color_sum = self
+            .sample_image([fpos[0] / ext_x, fpos[1] / ext_y])
+            .map(|c| c * gaussian_coeff[0]);
+        let mut coefficient_sum = gaussian_coeff[0];
+        gaussian_coeff[0] *= gaussian_coeff[1];
+        gaussian_coeff[1] *= gaussian_coeff[2];
+
+        let mut i = 1.0;
+        while i < sample_count {
+            let before = self.sample_image([(fpos[0] - i * dx) / ext_x, (fpos[1] - i * dy) / ext_y]);
+            let after = self.sample_image([(fpos[0] + i * dx) / ext_x, (fpos[1] + i * dy) / ext_y]);
+
+            for ((sum, before), after) in color_sum.iter_mut().zip(before).zip(after) {
+                *sum += (before + after) * gaussian_coeff[0];
+            }
+            coefficient_sum += 2.0 * gaussian_coeff[0];
+
+            gaussian_coeff[0] *= gaussian_coeff[1];
+            gaussian_coeff[1] *= gaussian_coeff[2];
+
+            i += 1.0;
+        }
+
+        self.tex_type(color_sum.map(|c| c / coefficient_sum))
+    }
+
+    fn tex_type(&self, color: [f32; 4]) -> [f32; 4] {
+        match self.params.tex_type as i32 {
+            1 => premultiply(color),
+            2 => [color[0]; 4],
+            _ => color,
+        }
+    }
+
+    fn scissor_mask(&self, fpos: [f32; 2]) -> f32 {
+        let params = self.params;
+        let pt = transform_point(&params.scissor_mat, fpos);
+        let sc_x = 0.5 - (pt[0].abs() - params.scissor_ext[0]) * params.scissor_scale[0];
+        let sc_y = 0.5 - (pt[1].abs() - params.scissor_ext[1]) * params.scissor_scale[1];
+        clamp01(sc_x) * clamp01(sc_y)
+    }
+}
+
+/// Stroke - from [0..1] to clipped pyramid, where the slope is 1px.
+fn stroke_mask(uv: [f32; 2], stroke_mult: f32) -> f32 {
+    ((1.0 - (uv[0] * 2.0 - 1.0).abs()) * stroke_mult).min(1.0) * uv[1].min(1.0)
+}
+
+fn sdroundrect(pt: [f32; 2], ext: [f32; 2], rad: f32) -> f32 {
+    let dx = pt[0].abs() - (ext[0] - rad);
+    let dy = pt[1].abs() - (ext[1] - rad);
+    dx.max(dy).min(0.0) + dx.max(0.0).hypot(dy.max(0.0)) - rad
+}
+
+/// Two point conical gradient: finds the largest `t` for which `pt` lies on the circle interpolated between the
+/// focal circle (at the origin, with radius `radius`) and the end circle (at `center`, with radius
+/// `radius + dr`).
+fn focal_gradient_position(pt: [f32; 2], center: [f32; 2], radius: f32, dr: f32) -> Option<f32> {
+    let dot = |a: [f32; 2], b: [f32; 2]| a[0] * b[0] + a[1] * b[1];
+
+    let a = dot(center, center) - dr * dr;
+    let b = dot(pt, center) + radius * dr;
+    let c = dot(pt, pt) - radius * radius;
+    let valid = |t: f32| radius + t * dr >= 0.0;
+
+    if a.abs() <= 1e-5 * (dot(center, center) + dr * dr) {
+        // The focal point is on the edge of the end circle, there is only one solution.
+        if b == 0.0 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return valid(t).then_some(t);
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t1 = (b + discriminant.sqrt()) / a;
+    let t2 = (b - discriminant.sqrt()) / a;
+    [t1.max(t2), t1.min(t2)].into_iter().find(|t| valid(*t))
+}
+
+/// Applies a matrix in the column major `mat3x4` layout of [`Transform2D::to_mat3x4`].
+fn transform_point(mat: &[f32; 12], p: [f32; 2]) -> [f32; 2] {
+    [
+        mat[0] * p[0] + mat[4] * p[1] + mat[8],
+        mat[1] * p[0] + mat[5] * p[1] + mat[9],
+    ]
+}
+
+/// Clamps to `[0, 1]`, mapping NaN to zero.
+fn clamp01(value: f32) -> f32 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Interpolates two sRGB colors in the gradient color space encoded by `color_space`, returning a premultiplied
+/// color. The colors are premultiplied for sRGB and unpremultiplied for the other color spaces.
+fn mix_gradient_colors(a: [f32; 4], b: [f32; 4], t: f32, color_space: u8) -> [f32; 4] {
+    let color_space = match color_space {
+        1 => GradientColorSpace::LinearSrgb,
+        2 => GradientColorSpace::Oklab,
+        _ => return mix(a, b, t),
+    };
+
+    let c0 = color_space.encode([a[0], a[1], a[2]]);
+    let c1 = color_space.encode([b[0], b[1], b[2]]);
+    let alpha = a[3] + (b[3] - a[3]) * t;
+
+    let rgb = color_space.decode([0, 1, 2].map(|i| c0[i] + (c1[i] - c0[i]) * t));
+    [rgb[0] * alpha, rgb[1] * alpha, rgb[2] * alpha, alpha]
+}
+
+fn mul(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] * b[i])
+}
+
+fn premultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [r * a, g * a, b * a, a]
+}
+
+fn to_rgba8(color: [f32; 4]) -> RGBA8 {
+    let [r, g, b, a] = color.map(|c| (clamp01(c) * 255.0).round() as u8);
+    RGBA8::new(r, g, b, a)
+}
+
+fn blend_pixel(src: [f32; 4], dst: RGBA8, op: CompositeOperationState) -> RGBA8 {
+    let src = src.map(clamp01);
+    let dst = [dst.r, dst.g, dst.b, dst.a].map(|c| c as f32 / 255.0);
+    let src = mix_blend_mode(src, dst, op.blend_mode());
+
+    let factor = |factor: BlendFactor, channel: usize| match factor {
+        BlendFactor::Zero => 0.0,
+        BlendFactor::One => 1.0,
+        BlendFactor::SrcColor => src[channel],
+        BlendFactor::OneMinusSrcColor => 1.0 - src[channel],
+        BlendFactor::DstColor => dst[channel],
+        BlendFactor::OneMinusDstColor => 1.0 - dst[channel],
+        BlendFactor::SrcAlpha => src[3],
+        BlendFactor::OneMinusSrcAlpha => 1.0 - src[3],
+        BlendFactor::DstAlpha => dst[3],
+        BlendFactor::OneMinusDstAlpha => 1.0 - dst[3],
+        BlendFactor::SrcAlphaSaturate if channel == 3 => 1.0,
+        BlendFactor::SrcAlphaSaturate => src[3].min(1.0 - dst[3]),
+    };
+
+    let result = [0, 1, 2, 3].map(|channel| {
+        let (src_factor, dst_factor) = if channel < 3 {
+            (op.src_rgb, op.dst_rgb)
+        } else {
+            (op.src_alpha, op.dst_alpha)
+        };
+
+        src[channel] * factor(src_factor, channel) + dst[channel] * factor(dst_factor, channel)
+    });
+
+    to_rgba8(result)
+}
+
+/// Mixes the premultiplied source color with the destination color according to the blend mode, leaving the
+/// source alpha as is. The result is then composited like any other source color.
+fn mix_blend_mode(src: [f32; 4], dst: [f32; 4], mode: BlendMode) -> [f32; 4] {
+    if mode == BlendMode::Normal || src[3] <= 0.0 {
+        return src;
+    }
+
+    let unpremultiply = |[r, g, b, a]: [f32; 4]| {
+        if a > 0.0 {
+            [r / a, g / a, b / a].map(clamp01)
+        } else {
+            [0.0; 3]
+        }
+    };
+
+    let cs = unpremultiply(src);
+    let cb = unpremultiply(dst);
+    let blended = blend_colors(mode, cb, cs);
+
+    let (src_alpha, dst_alpha) = (src[3], dst[3]);
+    let [r, g, b] = [0, 1, 2].map(|i| (1.0 - dst_alpha) * src[i] + src_alpha * dst_alpha * blended[i]);
+    [r, g, b, src_alpha]
+}
+
+/// The blend function B(cb, cs) of the W3C Compositing and Blending specification.
+fn blend_colors(mode: BlendMode, cb: [f32; 3], cs: [f32; 3]) -> [f32; 3] {
+    let separable = |f: fn(f32, f32) -> f32| [0, 1, 2].map(|i| f(cb[i], cs[i]));
+
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => separable(|b, s| b * s),
+        BlendMode::Screen => separable(screen),
+        BlendMode::Overlay => separable(|b, s| hard_light(s, b)),
+        BlendMode::Darken => separable(f32::min),
+        BlendMode::Lighten => separable(f32::max),
+        BlendMode::ColorDodge => separable(|b, s| {
+            if b <= 0.0 {
+                0.0
+            } else if s >= 1.0 {
+                1.0
+            } else {
+                (b / (1.0 - s)).min(1.0)
+            }
+        }),
+        BlendMode::ColorBurn => separable(|b, s| {
+            if b >= 1.0 {
+                1.0
+            } else if s <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - b) / s).min(1.0)
+            }
+        }),
+        BlendMode::HardLight => separable(hard_light),
+        BlendMode::SoftLight => separable(|b, s| {
+            if s <= 0.5 {
+                b - (1.0 - 2.0 * s) * b * (1.0 - b)
+            } else {
+                let d = if b <= 0.25 {
+                    ((16.0 * b - 12.0) * b + 4.0) * b
+                } else {
+                    b.sqrt()
+                };
+                b + (2.0 * s - 1.0) * (d - b)
+            }
+        }),
+        BlendMode::Difference => separable(|b, s| (b - s).abs()),
+        BlendMode::Exclusion => separable(|b, s| b + s - 2.0 * b * s),
+        BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        BlendMode::Color => set_lum(cs, lum(cb)),
+        BlendMode::Luminosity => set_lum(cb, lum(cs)),
+    }
+}
+
+fn screen(b: f32, s: f32) -> f32 {
+    b + s - b * s
+}
+
+fn hard_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b * 2.0 * s
+    } else {
+        screen(b, 2.0 * s - 1.0)
+    }
+}
+
+fn lum([r, g, b]: [f32; 3]) -> f32 {
+    0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn set_lum(color: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(color);
+    let color = color.map(|c| c + d);
+
+    let l = lum(color);
+    let min = color[0].min(color[1]).min(color[2]);
+    let max = color[0].max(color[1]).max(color[2]);
+
+    color.map(|c| {
+        let mut c = c;
+        if min < 0.0 {
+            c = l + (c - l) * l / (l - min);
+        }
+        if max > 1.0 {
+            c = l + (c - l) * (1.0 - l) / (max - l);
+        }
+        c
+    })
+}
+
+fn sat([r, g, b]: [f32; 3]) -> f32 {
+    r.max(g).max(b) - r.min(g).min(b)
+}
+
+fn set_sat(color: [f32; 3], s: f32) -> [f32; 3] {
+    let min = color[0].min(color[1]).min(color[2]);
+    let max = color[0].max(color[1]).max(color[2]);
+
+    if max > min {
+        color.map(|c| (c - min) * s / (max - min))
+    } else {
+        [0.0; 3]
+    }
+}
@@ -22,6 +22,7 @@ pub use wgpu;
 
 use super::Params;
 use super::Vertex;
+use super::STENCIL_CLIP_BIT;
 
 const UNIFORMARRAY_SIZE: usize = 14;
 
@@ -110,6 +111,18 @@ impl UniformArray {
     pub fn set_image_blur_filter_coeff(&mut self, coeff: [f32; 3]) {
         self.0[48..51].copy_from_slice(&coeff);
     }
+
+    pub fn set_gradient_type(&mut self, gradient_type: f32) {
+        self.0[52] = gradient_type;
+    }
+
+    pub fn set_gradient_spread(&mut self, gradient_spread: f32) {
+        self.0[53] = gradient_spread;
+    }
+
+    pub fn set_gradient_color_space(&mut self, gradient_color_space: f32) {
+        self.0[54] = gradient_color_space;
+    }
 }
 
 impl From<&Params> for UniformArray {
@@ -133,6 +146,9 @@ impl From<&Params> for UniformArray {
         arr.set_image_blur_filter_direction(params.image_blur_filter_direction);
         arr.set_image_blur_filter_sigma(params.image_blur_filter_sigma);
         arr.set_image_blur_filter_coeff(params.image_blur_filter_coeff);
+        arr.set_gradient_type(params.gradient_type as f32);
+        arr.set_gradient_spread(params.gradient_spread as f32);
+        arr.set_gradient_color_space(params.gradient_color_space as f32);
 
         arr
     }
@@ -440,6 +456,22 @@ impl Renderer for WGPURenderer {
                         images,
                     );
                 }
+                super::CommandType::ResetClip => {
+                    reset_clip(
+                        &command,
+                        &mut pipeline_and_bindgroup_mapper,
+                        &mut render_pass_builder,
+                        images,
+                    );
+                }
+                super::CommandType::IntersectClip => {
+                    intersect_clip(
+                        &command,
+                        &mut pipeline_and_bindgroup_mapper,
+                        &mut render_pass_builder,
+                        images,
+                    );
+                }
                 super::CommandType::RenderFilteredImage { target_image, filter } => match filter {
                     crate::ImageFilter::GaussianBlur { sigma } => {
                         gaussian_blur_filter(
@@ -722,7 +754,7 @@ fn triangles(
         render_pass_builder,
         blend_state(command).into(),
         wgpu::PrimitiveTopology::TriangleList,
-        StencilTest::Disabled,
+        clip_stencil_test(command),
         Some(wgpu::Face::Back),
         params,
         images,
@@ -749,6 +781,7 @@ fn stencil_stroke(
     }
 
     let blend_state = blend_state(command).into();
+    let read_mask = if command.clip { 0xff } else { !STENCIL_CLIP_BIT as u32 };
 
     // Fill the stroke base without overlap
 
@@ -770,8 +803,8 @@ fn stencil_stroke(
                     depth_fail_op: wgpu::StencilOperation::Keep,
                     pass_op: wgpu::StencilOperation::IncrementClamp,
                 },
-                read_mask: !0,
-                write_mask: !0,
+                read_mask,
+                write_mask: !STENCIL_CLIP_BIT as u32,
             },
             stencil_reference: 0,
         },
@@ -808,8 +841,8 @@ fn stencil_stroke(
                     depth_fail_op: wgpu::StencilOperation::Keep,
                     pass_op: wgpu::StencilOperation::Keep,
                 },
-                read_mask: !0,
-                write_mask: !0,
+                read_mask,
+                write_mask: !STENCIL_CLIP_BIT as u32,
             },
             stencil_reference: 0,
         },
@@ -847,7 +880,7 @@ fn stencil_stroke(
                     pass_op: wgpu::StencilOperation::Zero,
                 },
                 read_mask: !0,
-                write_mask: !0,
+                write_mask: !STENCIL_CLIP_BIT as u32,
             },
             stencil_reference: 0,
         },
@@ -880,7 +913,7 @@ fn stroke(
             render_pass_builder,
             blend_state(command).into(),
             wgpu::PrimitiveTopology::TriangleStrip,
-            StencilTest::Disabled,
+            clip_stencil_test(command),
             Some(wgpu::Face::Back),
             &params,
             images,
@@ -899,6 +932,12 @@ fn concave_fill(
     images: &mut ImageStore<Image>,
     fill_params: &Params,
 ) {
+    let clip_mask = if command.clip { STENCIL_CLIP_BIT as u32 } else { 0 };
+    let fill_mask = match command.fill_rule {
+        FillRule::NonZero => !STENCIL_CLIP_BIT as u32,
+        FillRule::EvenOdd => 0x1,
+    };
+
     if command.drawables.iter().any(|drawable| drawable.fill_verts.is_some()) {
         pipeline_and_bindgroup_mapper.update_renderpass(
             render_pass_builder,
@@ -907,19 +946,20 @@ fn concave_fill(
             StencilTest::Enabled {
                 stencil_state: wgpu::StencilState {
                     front: wgpu::StencilFaceState {
-                        compare: wgpu::CompareFunction::Always,
+                        compare: wgpu::CompareFunction::Equal,
                         fail_op: wgpu::StencilOperation::Keep,
                         depth_fail_op: wgpu::StencilOperation::Keep,
                         pass_op: wgpu::StencilOperation::IncrementWrap,
                     },
                     back: wgpu::StencilFaceState {
-                        compare: wgpu::CompareFunction::Always,
+                        compare: wgpu::CompareFunction::Equal,
                         fail_op: wgpu::StencilOperation::Keep,
                         depth_fail_op: wgpu::StencilOperation::Keep,
                         pass_op: wgpu::StencilOperation::DecrementWrap,
                     },
-                    read_mask: !0,
-                    write_mask: !0,
+                    // Only count pixels inside the clip and keep the clip bit out of the winding counts.
+                    read_mask: clip_mask,
+                    write_mask: !STENCIL_CLIP_BIT as u32,
                 },
                 stencil_reference: 0,
             },
@@ -960,14 +1000,8 @@ fn concave_fill(
                             depth_fail_op: wgpu::StencilOperation::Keep,
                             pass_op: wgpu::StencilOperation::Keep,
                         },
-                        read_mask: match command.fill_rule {
-                            FillRule::NonZero => 0xff,
-                            FillRule::EvenOdd => 0x1,
-                        },
-                        write_mask: match command.fill_rule {
-                            FillRule::NonZero => 0xff,
-                            FillRule::EvenOdd => 0x1,
-                        },
+                        read_mask: fill_mask | clip_mask,
+                        write_mask: fill_mask,
                     },
                     stencil_reference: 0,
                 },
@@ -1003,14 +1037,8 @@ fn concave_fill(
                         depth_fail_op: wgpu::StencilOperation::Zero,
                         pass_op: wgpu::StencilOperation::Zero,
                     },
-                    read_mask: match command.fill_rule {
-                        FillRule::NonZero => 0xff,
-                        FillRule::EvenOdd => 0x1,
-                    },
-                    write_mask: match command.fill_rule {
-                        FillRule::NonZero => 0xff,
-                        FillRule::EvenOdd => 0x1,
-                    },
+                    read_mask: fill_mask,
+                    write_mask: !STENCIL_CLIP_BIT as u32,
                 },
                 stencil_reference: 0,
             },
@@ -1039,7 +1067,7 @@ fn convex_fill(
                 render_pass_builder,
                 blend_state,
                 wgpu::PrimitiveTopology::TriangleList,
-                StencilTest::Disabled,
+                clip_stencil_test(command),
                 Some(wgpu::Face::Back),
                 params,
                 images,
@@ -1054,7 +1082,7 @@ fn convex_fill(
                 render_pass_builder,
                 blend_state,
                 wgpu::PrimitiveTopology::TriangleStrip,
-                StencilTest::Disabled,
+                clip_stencil_test(command),
                 Some(wgpu::Face::Back),
                 params,
                 images,
@@ -1112,6 +1140,204 @@ fn clear_rect(
     }
 }
 
+fn reset_clip(
+    command: &super::Command,
+    pipeline_and_bindgroup_mapper: &mut CommandToPipelineAndBindGroupMapper,
+    render_pass_builder: &mut RenderPassBuilder<'_>,
+    images: &mut ImageStore<Image>,
+) {
+    let Some((start, count)) = command.triangles_verts else {
+        return;
+    };
+
+    let stencil_params = Params {
+        stroke_thr: -1.0,
+        shader_type: ShaderType::Stencil,
+        ..Params::default()
+    };
+
+    pipeline_and_bindgroup_mapper.update_renderpass(
+        render_pass_builder,
+        None,
+        wgpu::PrimitiveTopology::TriangleStrip,
+        StencilTest::Enabled {
+            stencil_state: wgpu::StencilState {
+                front: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Zero,
+                    depth_fail_op: wgpu::StencilOperation::Zero,
+                    pass_op: wgpu::StencilOperation::Zero,
+                },
+                back: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Zero,
+                    depth_fail_op: wgpu::StencilOperation::Zero,
+                    pass_op: wgpu::StencilOperation::Zero,
+                },
+                read_mask: !0,
+                write_mask: STENCIL_CLIP_BIT as u32,
+            },
+            stencil_reference: 0,
+        },
+        None,
+        &stencil_params,
+        images,
+        None,
+        GlyphTexture::None,
+    );
+    render_pass_builder.draw(start as u32..(start + count) as u32);
+}
+
+fn intersect_clip(
+    command: &super::Command,
+    pipeline_and_bindgroup_mapper: &mut CommandToPipelineAndBindGroupMapper,
+    render_pass_builder: &mut RenderPassBuilder<'_>,
+    images: &mut ImageStore<Image>,
+) {
+    let Some((start, count)) = command.triangles_verts else {
+        return;
+    };
+
+    let stencil_params = Params {
+        stroke_thr: -1.0,
+        shader_type: ShaderType::Stencil,
+        ..Params::default()
+    };
+
+    // Count the winding of the clip path in the lower bits.
+    if command.drawables.iter().any(|drawable| drawable.fill_verts.is_some()) {
+        pipeline_and_bindgroup_mapper.update_renderpass(
+            render_pass_builder,
+            None,
+            wgpu::PrimitiveTopology::TriangleList,
+            StencilTest::Enabled {
+                stencil_state: wgpu::StencilState {
+                    front: wgpu::StencilFaceState {
+                        compare: wgpu::CompareFunction::Always,
+                        fail_op: wgpu::StencilOperation::Keep,
+                        depth_fail_op: wgpu::StencilOperation::Keep,
+                        pass_op: wgpu::StencilOperation::IncrementWrap,
+                    },
+                    back: wgpu::StencilFaceState {
+                        compare: wgpu::CompareFunction::Always,
+                        fail_op: wgpu::StencilOperation::Keep,
+                        depth_fail_op: wgpu::StencilOperation::Keep,
+                        pass_op: wgpu::StencilOperation::DecrementWrap,
+                    },
+                    read_mask: !0,
+                    write_mask: !STENCIL_CLIP_BIT as u32,
+                },
+                stencil_reference: 0,
+            },
+            None,
+            &stencil_params,
+            images,
+            None,
+            GlyphTexture::None,
+        );
+
+        for drawable in &command.drawables {
+            if let Some((start, count)) = drawable.fill_verts {
+                render_pass_builder.draw(start as u32..(start + count) as u32);
+            }
+        }
+    }
+
+    // Mark the pixels outside of the clip path as clipped.
+    pipeline_and_bindgroup_mapper.update_renderpass(
+        render_pass_builder,
+        None,
+        wgpu::PrimitiveTopology::TriangleStrip,
+        StencilTest::Enabled {
+            stencil_state: wgpu::StencilState {
+                front: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Equal,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                back: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Equal,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                read_mask: match command.fill_rule {
+                    FillRule::NonZero => !STENCIL_CLIP_BIT as u32,
+                    FillRule::EvenOdd => 0x1,
+                },
+                write_mask: STENCIL_CLIP_BIT as u32,
+            },
+            stencil_reference: STENCIL_CLIP_BIT as u32,
+        },
+        None,
+        &stencil_params,
+        images,
+        None,
+        GlyphTexture::None,
+    );
+    render_pass_builder.draw(start as u32..(start + count) as u32);
+
+    // Reset the winding counts.
+    pipeline_and_bindgroup_mapper.update_renderpass(
+        render_pass_builder,
+        None,
+        wgpu::PrimitiveTopology::TriangleStrip,
+        StencilTest::Enabled {
+            stencil_state: wgpu::StencilState {
+                front: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Zero,
+                    depth_fail_op: wgpu::StencilOperation::Zero,
+                    pass_op: wgpu::StencilOperation::Zero,
+                },
+                back: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Zero,
+                    depth_fail_op: wgpu::StencilOperation::Zero,
+                    pass_op: wgpu::StencilOperation::Zero,
+                },
+                read_mask: !0,
+                write_mask: !STENCIL_CLIP_BIT as u32,
+            },
+            stencil_reference: 0,
+        },
+        None,
+        &stencil_params,
+        images,
+        None,
+        GlyphTexture::None,
+    );
+    render_pass_builder.draw(start as u32..(start + count) as u32);
+}
+
+/// The stencil test that masks a draw call by the clip, if the command is clipped.
+fn clip_stencil_test(command: &super::Command) -> StencilTest {
+    if !command.clip {
+        return StencilTest::Disabled;
+    }
+
+    StencilTest::Enabled {
+        stencil_state: wgpu::StencilState {
+            front: wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Equal,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: wgpu::StencilOperation::Keep,
+            },
+            back: wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Equal,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: wgpu::StencilOperation::Keep,
+            },
+            read_mask: STENCIL_CLIP_BIT as u32,
+            write_mask: 0,
+        },
+        stencil_reference: 0,
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 enum StencilTest {
     Disabled,
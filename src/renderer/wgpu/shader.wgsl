@@ -16,6 +16,10 @@ struct Params {
     image_blur_filter_sigma: f32,
     image_blur_filter_direction: vec2<f32>,
     image_blur_filter_coeff: vec3<f32>,
+    _unused_blend_mode: f32,
+    gradient_type: f32, // 0 -> distance to a rounded rect, 1 -> conic angle
+    gradient_spread: f32, // 0 -> pad, 1 -> repeat, 2 -> reflect
+    gradient_color_space: f32, // 0 -> sRGB, 1 -> linear sRGB, 2 -> OKLab
 }
 
 override shader_type: i32;
@@ -31,6 +35,15 @@ const SHADER_TYPE_FillColor: i32 = 5;
 const SHADER_TYPE_TextureCopyUnclipped: i32 = 6;
 const SHADER_TYPE_FillColorUnclipped: i32 = 7;
 
+const GRADIENT_TYPE_Conic: f32 = 1.0;
+const GRADIENT_TYPE_Focal: f32 = 2.0;
+
+const GRADIENT_SPREAD_Repeat: f32 = 1.0;
+const GRADIENT_SPREAD_Reflect: f32 = 2.0;
+
+const GRADIENT_COLOR_SPACE_LinearSrgb: f32 = 1.0;
+const GRADIENT_COLOR_SPACE_Oklab: f32 = 2.0;
+
 struct ViewSize {
     x: f32,
     y: f32,
@@ -182,21 +195,138 @@ fn strokeMask(vertex: VertexOutput, params: Params) -> f32 {
     //return smoothstep(0.0, 1.0, (1.0-abs(vertex.ftcoord.x*2.0-1.0))*params.stroke_mult) * smoothstep(0.0, 1.0, vertex.ftcoord.y);
 }
 
-fn renderGradient(vertex: VertexOutput, params: Params) -> vec4<f32> {
-    // Calculate gradient color using box gradient
+// Two point conical gradient: finds the largest t for which pt lies on the circle interpolated between
+// the focal circle (at the origin, with radius `radius`) and the end circle (at `extent`, with radius
+// `radius + feather`). Returns (t, 1.0), or (0.0, 0.0) if there is no such circle.
+fn focalGradientPosition(pt: vec2<f32>, params: Params) -> vec2<f32> {
+    let a: f32 = dot(params.extent, params.extent) - params.feather * params.feather;
+    let b: f32 = dot(pt, params.extent) + params.radius * params.feather;
+    let c: f32 = dot(pt, pt) - params.radius * params.radius;
+
+    if (abs(a) <= 1e-5 * (dot(params.extent, params.extent) + params.feather * params.feather)) {
+        // The focal point is on the edge of the end circle, there is only one solution
+        if (b == 0.0) {
+            return vec2<f32>(0.0);
+        }
+        let t: f32 = c / (2.0 * b);
+        if (params.radius + t * params.feather >= 0.0) {
+            return vec2<f32>(t, 1.0);
+        }
+        return vec2<f32>(0.0);
+    }
+
+    let discriminant: f32 = b * b - a * c;
+    if (discriminant < 0.0) {
+        return vec2<f32>(0.0);
+    }
+
+    let t1: f32 = (b + sqrt(discriminant)) / a;
+    let t2: f32 = (b - sqrt(discriminant)) / a;
+    if (params.radius + max(t1, t2) * params.feather >= 0.0) {
+        return vec2<f32>(max(t1, t2), 1.0);
+    }
+    if (params.radius + min(t1, t2) * params.feather >= 0.0) {
+        return vec2<f32>(min(t1, t2), 1.0);
+    }
+    return vec2<f32>(0.0);
+}
+
+// Position along the gradient, from 0 to 1, or -1 where the gradient is not defined.
+fn gradientPosition(vertex: VertexOutput, params: Params) -> f32 {
     let pt: vec2<f32> = (params.paint_mat * vec3<f32>(vertex.fpos, 1.0)).xy;
 
-    let d: f32 = clamp((sdroundrect(pt, params.extent, params.radius) + params.feather*0.5) / params.feather, 0.0, 1.0);
-    return mix(params.inner_col,params.outer_col,d);
+    var d: f32;
+    if (params.gradient_type == GRADIENT_TYPE_Conic) {
+        // Fraction of the clockwise turn from the start angle
+        d = fract(atan2(pt.y, pt.x) / 6.283185307179586);
+    } else if (params.gradient_type == GRADIENT_TYPE_Focal) {
+        let focal: vec2<f32> = focalGradientPosition(pt, params);
+        if (focal.y == 0.0) {
+            return -1.0;
+        }
+        d = focal.x;
+    } else {
+        // Distance to the box gradient
+        d = (sdroundrect(pt, params.extent, params.radius) + params.feather*0.5) / params.feather;
+    }
+
+    if (params.gradient_spread == GRADIENT_SPREAD_Repeat) {
+        return fract(d);
+    }
+    if (params.gradient_spread == GRADIENT_SPREAD_Reflect) {
+        return 1.0 - abs(d - 2.0 * floor(d * 0.5) - 1.0);
+    }
+    return clamp(d, 0.0, 1.0);
+}
+
+fn srgbToLinear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+fn linearToSrgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn linearSrgbToOklab(c: vec3<f32>) -> vec3<f32> {
+    var lms = vec3<f32>(
+        dot(c, vec3<f32>(0.4122214708, 0.5363325363, 0.0514459929)),
+        dot(c, vec3<f32>(0.2119034982, 0.6806995451, 0.1073969566)),
+        dot(c, vec3<f32>(0.0883024619, 0.2817188376, 0.6299787005)));
+    lms = pow(max(lms, vec3<f32>(0.0)), vec3<f32>(1.0 / 3.0));
+    return vec3<f32>(
+        dot(lms, vec3<f32>(0.2104542553, 0.7936177850, -0.0040720468)),
+        dot(lms, vec3<f32>(1.9779984951, -2.4285922050, 0.4505937099)),
+        dot(lms, vec3<f32>(0.0259040371, 0.7827717662, -0.8086757660)));
+}
+
+fn oklabToLinearSrgb(c: vec3<f32>) -> vec3<f32> {
+    var lms = vec3<f32>(
+        dot(c, vec3<f32>(1.0, 0.3963377774, 0.2158037573)),
+        dot(c, vec3<f32>(1.0, -0.1055613458, -0.0638541728)),
+        dot(c, vec3<f32>(1.0, -0.0894841775, -1.2914855480)));
+    lms = lms * lms * lms;
+    return vec3<f32>(
+        dot(lms, vec3<f32>(4.0767416621, -3.3077115913, 0.2309699292)),
+        dot(lms, vec3<f32>(-1.2684380046, 2.6097574011, -0.3413193965)),
+        dot(lms, vec3<f32>(-0.0041960863, -0.7034186147, 1.7076147010)));
+}
+
+// Interpolates two sRGB colors in the gradient's color space, returning a premultiplied color. The colors are
+// premultiplied for sRGB and unpremultiplied for the other color spaces.
+fn mixGradientColors(c0: vec4<f32>, c1: vec4<f32>, t: f32, params: Params) -> vec4<f32> {
+    if (params.gradient_color_space != GRADIENT_COLOR_SPACE_LinearSrgb && params.gradient_color_space != GRADIENT_COLOR_SPACE_Oklab) {
+        return mix(c0, c1, t);
+    }
+
+    let a = srgbToLinear(c0.rgb);
+    let b = srgbToLinear(c1.rgb);
+    var rgb: vec3<f32>;
+    if (params.gradient_color_space == GRADIENT_COLOR_SPACE_Oklab) {
+        rgb = oklabToLinearSrgb(mix(linearSrgbToOklab(a), linearSrgbToOklab(b), t));
+    } else {
+        rgb = mix(a, b, t);
+    }
+    let alpha = mix(c0.a, c1.a, t);
+    return vec4<f32>(linearToSrgb(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0))) * alpha, alpha);
+}
+
+fn renderGradient(vertex: VertexOutput, params: Params) -> vec4<f32> {
+    let d: f32 = gradientPosition(vertex, params);
+    if (d < 0.0) {
+        return vec4<f32>(0.0);
+    }
+    return mixGradientColors(params.inner_col, params.outer_col, d, params);
 }
 
 // Image-based Gradient; sample a texture using the gradient position.
 fn renderImageGradient(vertex: VertexOutput, params: Params) -> vec4<f32> {
-    // Calculate gradient color using box gradient
-    let pt: vec2<f32> = (params.paint_mat * vec3<f32>(vertex.fpos, 1.0)).xy;
-
-    let d: f32 = clamp((sdroundrect(pt, params.extent, params.radius) + params.feather*0.5) / params.feather, 0.0, 1.0);
-    return textureSample(image_texture, image_sampler, vec2<f32>(d, 0.0));//mix(innerCol,outerCol,d);
+    let d: f32 = gradientPosition(vertex, params);
+    // Sample before branching, textureSample must be called in uniform control flow.
+    let color: vec4<f32> = textureSample(image_texture, image_sampler, vec2<f32>(max(d, 0.0), 0.0));//mix(innerCol,outerCol,d);
+    if (d < 0.0) {
+        return vec4<f32>(0.0);
+    }
+    return color;
 }
 
 fn renderImage(vertex: VertexOutput, params: Params) -> vec4<f32> {
@@ -1,5 +1,5 @@
 use std::{
-    borrow::Borrow,
+    borrow::{Borrow, Cow},
     cell::RefCell,
     collections::HashMap,
     ffi::OsStr,
@@ -26,10 +26,22 @@ use crate::{
 mod atlas;
 pub use atlas::Atlas;
 
+mod attributed;
+pub use attributed::AttributedText;
+
+mod caret;
+pub use caret::Affinity;
+
+mod decoration;
+pub(crate) use decoration::decoration_path;
+
 mod font;
 pub use font::FontMetrics;
 use font::{Font, GlyphRendering};
 
+mod paragraph;
+pub use paragraph::{LineHeight, Paragraph, ParagraphLine};
+
 // This padding is an empty border around the glyph’s pixels but inside the
 // sampled area (texture coordinates) for the quad in render_atlas().
 const GLYPH_PADDING: u32 = 1;
@@ -40,6 +52,9 @@ const GLYPH_PADDING: u32 = 1;
 const GLYPH_MARGIN: u32 = 1;
 
 const TEXTURE_SIZE: usize = 512;
+
+// Appended to text that's cut off to fit a width.
+const ELLIPSIS: &str = "…";
 const DEFAULT_LRU_CACHE_CAPACITY: usize = 1000;
 
 /// A font handle.
@@ -78,6 +93,22 @@ pub enum Align {
     Right,
 }
 
+bitflags::bitflags! {
+    /// Lines drawn along text.
+    ///
+    /// The default value is `empty()`.
+    #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct TextDecoration: u8 {
+        /// A line below the baseline.
+        const UNDERLINE = 1;
+        /// A line above the text.
+        const OVERLINE = 1 << 1;
+        /// A line through the middle of lowercase letters.
+        const LINE_THROUGH = 1 << 2;
+    }
+}
+
 /// Represents the rendering mode for a path.
 ///
 /// The default value is `Fill`.
@@ -164,17 +195,19 @@ struct ShapingId {
 }
 
 impl ShapingId {
-    fn new(font_size: f32, font_ids: [Option<FontId>; 8], word: &str, max_width: Option<f32>) -> Self {
+    fn new(text_settings: &TextSettings, word: &str, max_width: Option<f32>) -> Self {
         let mut hasher = FnvHasher::default();
         word.hash(&mut hasher);
+        // The letter spacing is part of the shaped width.
+        text_settings.letter_spacing.to_bits().hash(&mut hasher);
         if let Some(max_width) = max_width {
             (max_width.trunc() as i32).hash(&mut hasher);
         }
 
         Self {
-            size: (font_size * 10.0).trunc() as u32,
+            size: (text_settings.font_size * 10.0).trunc() as u32,
             word_hash: hasher.finish(),
-            font_ids,
+            font_ids: text_settings.font_ids,
         }
     }
 }
@@ -248,6 +281,11 @@ impl TextContext {
         self.0.borrow_mut().measure_text(x, y, text, &paint.text)
     }
 
+    /// Returns information on how the provided attributed text will be drawn.
+    pub fn measure_attributed_text(&self, x: f32, y: f32, text: &AttributedText) -> Result<TextMetrics, ErrorKind> {
+        text.shape(x, y, &mut self.0.borrow_mut(), 1.0)
+    }
+
     /// Returns the maximum index-th byte of text that will fit inside `max_width`.
     ///
     /// The retuned index will always lie at the start and/or end of a UTF-8 code point sequence or at the start or end of the text
@@ -265,6 +303,14 @@ impl TextContext {
         self.0.borrow_mut().break_text_vec(max_width, text, &paint.text)
     }
 
+    /// Returns the byte length of the longest start of the text that fits inside `max_width` together with an
+    /// ellipsis ("…") after it, or the length of the whole text if it fits without one.
+    ///
+    /// The text is only cut between grapheme clusters, and whitespace before the ellipsis is left out.
+    pub fn truncate_text<S: AsRef<str>>(&self, max_width: f32, text: S, paint: &Paint) -> Result<usize, ErrorKind> {
+        self.0.borrow_mut().truncate_text(max_width, text, &paint.text)
+    }
+
     /// Returns font metrics for a particular Paint.
     pub fn measure_font(&self, paint: &Paint) -> Result<FontMetrics, ErrorKind> {
         self.0
@@ -272,6 +318,20 @@ impl TextContext {
             .measure_font(paint.text.font_size, &paint.text.font_ids)
     }
 
+    /// Breaks the text into lines that fit inside `max_width` and positions them below each other, `line_height`
+    /// apart. Pass `f32::INFINITY` as `max_width` to only break lines at line feeds.
+    pub fn layout_paragraph<S: AsRef<str>>(
+        &self,
+        text: S,
+        paint: &Paint,
+        max_width: f32,
+        line_height: LineHeight,
+    ) -> Result<Paragraph, ErrorKind> {
+        self.0
+            .borrow_mut()
+            .layout_paragraph(text, &paint.text, max_width, line_height)
+    }
+
     /// Adjusts the capacity of the shaping run cache. This is a cache for measurements of whole
     /// strings.
     pub fn resize_shaping_run_cache(&self, capacity: std::num::NonZeroUsize) {
@@ -479,6 +539,59 @@ impl TextContextImpl {
         Ok(res)
     }
 
+    pub fn truncate_text<S: AsRef<str>>(
+        &mut self,
+        max_width: f32,
+        text: S,
+        text_settings: &TextSettings,
+    ) -> Result<usize, ErrorKind> {
+        let text = text.as_ref();
+
+        let metrics = shape(0.0, 0.0, self, text_settings, text, None)?;
+        if metrics.width() <= max_width {
+            return Ok(text.len());
+        }
+
+        let ellipsis_width = shape(0.0, 0.0, self, text_settings, ELLIPSIS, None)?.width();
+
+        // The glyphs are in visual order, the width of a start of the text is summed up in logical order.
+        let mut advances: Vec<(usize, f32)> = metrics
+            .glyphs
+            .iter()
+            .map(|glyph| (glyph.byte_index, glyph.advance_x + text_settings.letter_spacing))
+            .collect();
+        advances.sort_by_key(|(byte_index, _)| *byte_index);
+        let mut advances = advances.into_iter().peekable();
+
+        let mut width = 0.0;
+        let mut fitting = 0;
+
+        for (start, grapheme) in text.grapheme_indices(true) {
+            let end = start + grapheme.len();
+            while let Some((_, advance)) = advances.next_if(|(byte_index, _)| *byte_index < end) {
+                width += advance;
+            }
+
+            if width + ellipsis_width > max_width {
+                break;
+            }
+
+            fitting = end;
+        }
+
+        Ok(text[..fitting].trim_end().len())
+    }
+
+    pub fn layout_paragraph<S: AsRef<str>>(
+        &mut self,
+        text: S,
+        text_settings: &TextSettings,
+        max_width: f32,
+        line_height: LineHeight,
+    ) -> Result<Paragraph, ErrorKind> {
+        paragraph::layout_paragraph(self, text.as_ref(), text_settings, max_width, line_height)
+    }
+
     pub fn measure_font(&self, font_size: f32, font_ids: &[Option<FontId>; 8]) -> Result<FontMetrics, ErrorKind> {
         if let Some(Some(id)) = font_ids.first() {
             if let Some(font) = self.font(*id) {
@@ -490,6 +603,24 @@ impl TextContextImpl {
     }
 }
 
+/// Returns the start of the text up to `byte_index` followed by an ellipsis, or the whole text if it ends there.
+///
+/// After right-to-left text, a right-to-left mark keeps the ellipsis at the end of the run, on its left.
+pub(crate) fn ellipsize(text: &str, byte_index: usize) -> Cow<'_, str> {
+    if byte_index >= text.len() {
+        return Cow::Borrowed(text);
+    }
+
+    let start = &text[..byte_index];
+    let bidi_info = BidiInfo::new(start, Some(unicode_bidi::Level::ltr()));
+    let rtl = start
+        .char_indices()
+        .last()
+        .is_some_and(|(index, _)| bidi_info.levels[index].is_rtl());
+
+    Cow::Owned(format!("{start}{ELLIPSIS}{}", if rtl { "\u{200F}" } else { "" }))
+}
+
 /// Represents the result of a text shaping run.
 #[derive(Clone, Default, Debug)]
 pub struct TextMetrics {
@@ -544,22 +675,20 @@ pub fn shape(
     text: &str,
     max_width: Option<f32>,
 ) -> Result<TextMetrics, ErrorKind> {
-    let id = ShapingId::new(text_settings.font_size, text_settings.font_ids, text, max_width);
+    let id = ShapingId::new(text_settings, text, max_width);
+
+    let styles = [StyleRun {
+        range: 0..text.len(),
+        text_settings,
+    }];
 
     if !context.shaping_run_cache.contains(&id) {
-        let metrics = shape_run(
-            context,
-            text_settings.font_size,
-            text_settings.font_ids,
-            text_settings.letter_spacing,
-            text,
-            max_width,
-        );
+        let metrics = shape_run(context, &styles, text, max_width);
         context.shaping_run_cache.put(id, metrics);
     }
 
     if let Some(mut metrics) = context.shaping_run_cache.get(&id).cloned() {
-        layout(x, y, context, &mut metrics, text_settings)?;
+        layout(x, y, context, &mut metrics, &styles)?;
 
         return Ok(metrics);
     }
@@ -567,14 +696,35 @@ pub fn shape(
     Err(ErrorKind::UnknownError)
 }
 
-fn shape_run(
+/// A range of text shaped with the same settings.
+#[derive(Clone)]
+pub(crate) struct StyleRun<'a> {
+    pub range: Range<usize>,
+    pub text_settings: &'a TextSettings,
+}
+
+// Returns the style run a byte of the text belongs to. The runs cover the whole text in order.
+fn style_at<'a, 'b>(styles: &'a [StyleRun<'b>], byte_index: usize) -> &'a StyleRun<'b> {
+    let index = styles.partition_point(|style| style.range.end <= byte_index);
+    &styles[index.min(styles.len() - 1)]
+}
+
+/// Shapes text with different settings for ranges of it as one run, respecting the bidi order across
+/// the ranges. The runs must cover the whole text in order.
+pub(crate) fn shape_styled(
+    x: f32,
+    y: f32,
     context: &mut TextContextImpl,
-    font_size: f32,
-    font_ids: [Option<FontId>; 8],
-    letter_spacing: f32,
+    styles: &[StyleRun],
     text: &str,
-    max_width: Option<f32>,
-) -> TextMetrics {
+) -> Result<TextMetrics, ErrorKind> {
+    let mut metrics = shape_run(context, styles, text, None);
+    layout(x, y, context, &mut metrics, styles)?;
+
+    Ok(metrics)
+}
+
+fn shape_run(context: &mut TextContextImpl, styles: &[StyleRun], text: &str, max_width: Option<f32>) -> TextMetrics {
     let mut result = TextMetrics {
         x: 0.0,
         y: 0.0,
@@ -598,9 +748,7 @@ fn shape_run(
     let (levels, runs) = bidi_info.visual_runs(paragraph, line);
 
     for run in runs {
-        let sub_text = &text[run.clone()];
-
-        if sub_text.is_empty() {
+        if run.is_empty() {
             continue;
         }
 
@@ -614,90 +762,109 @@ fn shape_run(
         let mut word_break_reached = false;
         let mut byte_index = run.start;
 
-        for mut word_txt in sub_text.split_word_bounds() {
-            let id = ShapingId::new(font_size, font_ids, word_txt, max_width);
+        // Words are split where the style changes within the run.
+        for style in styles
+            .iter()
+            .filter(|style| style.range.start < run.end && style.range.end > run.start)
+        {
+            let TextSettings {
+                font_size,
+                font_ids,
+                letter_spacing,
+                ..
+            } = *style.text_settings;
+            let piece = style.range.start.max(run.start)..style.range.end.min(run.end);
+            byte_index = piece.start;
+
+            for mut word_txt in text[piece].split_word_bounds() {
+                let id = ShapingId::new(style.text_settings, word_txt, max_width);
+
+                if !context.shaped_words_cache.contains(&id) {
+                    let word = shape_word(word_txt, hb_direction, context, font_size, &font_ids, letter_spacing);
+                    context.shaped_words_cache.put(id, word);
+                }
 
-            if !context.shaped_words_cache.contains(&id) {
-                let word = shape_word(word_txt, hb_direction, context, font_size, &font_ids, letter_spacing);
-                context.shaped_words_cache.put(id, word);
-            }
+                if let Some(Ok(word)) = context.shaped_words_cache.get(&id) {
+                    let mut word = word.clone();
+
+                    if let Some(max_width) = max_width {
+                        if result.width + word.width >= max_width {
+                            word_break_reached = true;
+                            if first_word_in_paragraph {
+                                // search for the largest prefix of the word that can fit
+                                let mut bytes_included = 0;
+                                let mut subword_width = 0.0;
+                                let target_width = max_width - result.width;
+                                for glyph in word.glyphs {
+                                    bytes_included = glyph.byte_index;
+                                    let glyph_width = glyph.advance_x + letter_spacing;
+
+                                    // nuance: we want to include the first glyph even if it breaks
+                                    // the bounds. this is to allow pathologically small bounds to
+                                    // at least complete rendering
+                                    if subword_width + glyph_width >= target_width && bytes_included != 0 {
+                                        break;
+                                    }
+
+                                    subword_width += glyph_width;
+                                }
 
-            if let Some(Ok(word)) = context.shaped_words_cache.get(&id) {
-                let mut word = word.clone();
-
-                if let Some(max_width) = max_width {
-                    if result.width + word.width >= max_width {
-                        word_break_reached = true;
-                        if first_word_in_paragraph {
-                            // search for the largest prefix of the word that can fit
-                            let mut bytes_included = 0;
-                            let mut subword_width = 0.0;
-                            let target_width = max_width - result.width;
-                            for glyph in word.glyphs {
-                                bytes_included = glyph.byte_index;
-                                let glyph_width = glyph.advance_x + letter_spacing;
-
-                                // nuance: we want to include the first glyph even if it breaks
-                                // the bounds. this is to allow pathologically small bounds to
-                                // at least complete rendering
-                                if subword_width + glyph_width >= target_width && bytes_included != 0 {
+                                if bytes_included == 0 {
+                                    // just in case - never mind!
                                     break;
                                 }
 
-                                subword_width += glyph_width;
-                            }
-
-                            if bytes_included == 0 {
-                                // just in case - never mind!
-                                break;
-                            }
-
-                            let subword_txt = &word_txt[..bytes_included];
-                            let id = ShapingId::new(font_size, font_ids, subword_txt, Some(max_width));
-                            if !context.shaped_words_cache.contains(&id) {
-                                let subword = shape_word(
-                                    subword_txt,
-                                    hb_direction,
-                                    context,
-                                    font_size,
-                                    &font_ids,
-                                    letter_spacing,
-                                );
-                                context.shaped_words_cache.put(id, subword);
-                            }
+                                let subword_txt = &word_txt[..bytes_included];
+                                let id = ShapingId::new(style.text_settings, subword_txt, Some(max_width));
+                                if !context.shaped_words_cache.contains(&id) {
+                                    let subword = shape_word(
+                                        subword_txt,
+                                        hb_direction,
+                                        context,
+                                        font_size,
+                                        &font_ids,
+                                        letter_spacing,
+                                    );
+                                    context.shaped_words_cache.put(id, subword);
+                                }
 
-                            if let Some(Ok(subword)) = context.shaped_words_cache.get(&id) {
-                                // replace the outer variables so we can continue normally
-                                word = subword.clone();
-                                word_txt = subword_txt;
+                                if let Some(Ok(subword)) = context.shaped_words_cache.get(&id) {
+                                    // replace the outer variables so we can continue normally
+                                    word = subword.clone();
+                                    word_txt = subword_txt;
+                                } else {
+                                    break;
+                                }
+                            } else if word.glyphs.iter().all(|g| g.c.is_whitespace()) {
+                                // the last word we've broken in the middle of is whitespace.
+                                // include this word for now, but we will discard its metrics in a moment.
                             } else {
+                                // we are not breaking up words - discard this word
                                 break;
                             }
-                        } else if word.glyphs.iter().all(|g| g.c.is_whitespace()) {
-                            // the last word we've broken in the middle of is whitespace.
-                            // include this word for now, but we will discard its metrics in a moment.
-                        } else {
-                            // we are not breaking up words - discard this word
-                            break;
                         }
                     }
-                }
 
-                // if we have broken in the middle of whitespace, do not include this word in metrics
-                if !word_break_reached || !word.glyphs.iter().all(|g| g.c.is_whitespace()) {
-                    result.width += word.width;
+                    // if we have broken in the middle of whitespace, do not include this word in metrics
+                    if !word_break_reached || !word.glyphs.iter().all(|g| g.c.is_whitespace()) {
+                        result.width += word.width;
+                    }
+
+                    for glyph in &mut word.glyphs {
+                        glyph.byte_index += byte_index;
+                        debug_assert!(text.get(glyph.byte_index..).is_some());
+                    }
+                    words.push(word);
+                    first_word_in_paragraph = false;
                 }
 
-                for glyph in &mut word.glyphs {
-                    glyph.byte_index += byte_index;
-                    debug_assert!(text.get(glyph.byte_index..).is_some());
+                byte_index += word_txt.len();
+
+                if word_break_reached {
+                    break;
                 }
-                words.push(word);
-                first_word_in_paragraph = false;
             }
 
-            byte_index += word_txt.len();
-
             if word_break_reached {
                 break;
             }
@@ -794,13 +961,16 @@ fn shape_word(
 }
 
 // Calculates the x,y coordinates for each glyph based on their advances. Calculates total width and height of the shaped text run
+// The alignment and baseline are taken from the first style run.
 fn layout(
     x: f32,
     y: f32,
     context: &mut TextContextImpl,
     res: &mut TextMetrics,
-    text_settings: &TextSettings,
+    styles: &[StyleRun],
 ) -> Result<(), ErrorKind> {
+    let text_settings = styles[0].text_settings;
+
     let mut cursor_x = x;
     let mut cursor_y = y;
 
@@ -820,8 +990,9 @@ fn layout(
     let mut descender: f32 = 0.;
 
     for glyph in &mut res.glyphs {
+        let font_size = style_at(styles, glyph.byte_index).text_settings.font_size;
         let font = context.font_mut(glyph.font_id).ok_or(ErrorKind::NoFontFound)?;
-        let metrics = font.metrics(text_settings.font_size);
+        let metrics = font.metrics(font_size);
         ascender = ascender.max(metrics.ascender());
         descender = descender.min(metrics.descender());
     }
@@ -851,7 +1022,7 @@ fn layout(
         min_y = min_y.min(glyph.y);
         max_y = max_y.max(glyph.y + glyph.height);
 
-        cursor_x += glyph.advance_x + text_settings.letter_spacing;
+        cursor_x += glyph.advance_x + style_at(styles, glyph.byte_index).text_settings.letter_spacing;
         cursor_y += glyph.advance_y;
     }
 
@@ -1243,6 +1414,7 @@ impl GlyphAtlas {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_direct<T: Renderer>(
     canvas: &mut Canvas<T>,
     text_layout: &TextMetrics,
@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+use super::{shape_styled, StyleRun, TextContextImpl, TextMetrics};
+use crate::{paint::TextSettings, ErrorKind, Paint};
+
+#[derive(Clone, Debug)]
+struct Span {
+    range: Range<usize>,
+    paint: Paint,
+}
+
+/// Text made of ranges with their own paint, for example a sentence with a bold word or a colored link.
+///
+/// Each range is shaped with the fonts, font size and letter spacing of its paint and drawn with its color
+/// or gradient, but the text is shaped as one run, so the bidi order is kept across the ranges. The text
+/// align and baseline of the first range apply to the whole text.
+///
+/// ```
+/// # use femtovg::{AttributedText, Color, Paint};
+/// let paint = Paint::color(Color::black()).with_font_size(14.0);
+/// let link = Paint::color(Color::rgb(0, 0, 255)).with_font_size(14.0);
+///
+/// let text = AttributedText::new("Read the ", &paint)
+///     .with_str("manual", &link)
+///     .with_str(" first.", &paint);
+///
+/// assert_eq!(text.text(), "Read the manual first.");
+/// assert_eq!(text.spans().nth(1).unwrap().0, 9..15);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AttributedText {
+    text: String,
+    spans: Vec<Span>,
+}
+
+impl AttributedText {
+    /// Creates attributed text drawn with a single paint.
+    pub fn new<S: Into<String>>(text: S, paint: &Paint) -> Self {
+        let mut attributed = Self::default();
+        attributed.push_str(&text.into(), paint);
+        attributed
+    }
+
+    /// Appends text drawn with the given paint.
+    pub fn push_str(&mut self, text: &str, paint: &Paint) {
+        if text.is_empty() {
+            return;
+        }
+
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.spans.push(Span {
+            range: start..self.text.len(),
+            paint: paint.clone(),
+        });
+    }
+
+    /// Appends text drawn with the given paint.
+    pub fn with_str(mut self, text: &str, paint: &Paint) -> Self {
+        self.push_str(text, paint);
+        self
+    }
+
+    /// Draws a byte range of the text with the given paint instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't start and end at character boundaries.
+    pub fn set_paint(&mut self, range: Range<usize>, paint: &Paint) {
+        assert!(
+            self.text.get(range.clone()).is_some(),
+            "range {range:?} is not within the text"
+        );
+
+        if range.is_empty() {
+            return;
+        }
+
+        let mut spans = Vec::with_capacity(self.spans.len() + 2);
+
+        for span in self.spans.drain(..) {
+            if span.range.end <= range.start || span.range.start >= range.end {
+                spans.push(span);
+                continue;
+            }
+
+            if span.range.start < range.start {
+                spans.push(Span {
+                    range: span.range.start..range.start,
+                    paint: span.paint.clone(),
+                });
+            }
+
+            if span.range.start <= range.start {
+                spans.push(Span {
+                    range: range.clone(),
+                    paint: paint.clone(),
+                });
+            }
+
+            if span.range.end > range.end {
+                spans.push(Span {
+                    range: range.end..span.range.end,
+                    paint: span.paint,
+                });
+            }
+        }
+
+        self.spans = spans;
+    }
+
+    /// Draws a byte range of the text with the given paint instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't start and end at character boundaries.
+    pub fn with_paint(mut self, range: Range<usize>, paint: &Paint) -> Self {
+        self.set_paint(range, paint);
+        self
+    }
+
+    /// Returns the whole text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte ranges of the text and their paints, in order.
+    pub fn spans(&self) -> impl Iterator<Item = (Range<usize>, &Paint)> {
+        self.spans.iter().map(|span| (span.range.clone(), &span.paint))
+    }
+
+    /// Shapes and lays out the text with its sizes multiplied by `scale`.
+    pub(crate) fn shape(
+        &self,
+        x: f32,
+        y: f32,
+        context: &mut TextContextImpl,
+        scale: f32,
+    ) -> Result<TextMetrics, ErrorKind> {
+        if self.spans.is_empty() {
+            return Ok(TextMetrics {
+                x,
+                y,
+                ..Default::default()
+            });
+        }
+
+        let text_settings: Vec<TextSettings> = self
+            .spans
+            .iter()
+            .map(|span| {
+                let mut text_settings = span.paint.text.clone();
+                text_settings.font_size *= scale;
+                text_settings.letter_spacing *= scale;
+                text_settings
+            })
+            .collect();
+
+        // Spans that only differ in paint are shaped together, so kerning and ligatures aren't broken where the
+        // color changes. Drawing picks the glyphs of each span by their byte index.
+        let mut styles: Vec<StyleRun> = Vec::with_capacity(self.spans.len());
+
+        for (span, text_settings) in self.spans.iter().zip(&text_settings) {
+            match styles.last_mut() {
+                Some(style)
+                    if style.text_settings.font_ids == text_settings.font_ids
+                        && style.text_settings.font_size == text_settings.font_size
+                        && style.text_settings.letter_spacing == text_settings.letter_spacing =>
+                {
+                    style.range.end = span.range.end;
+                }
+                _ => styles.push(StyleRun {
+                    range: span.range.clone(),
+                    text_settings,
+                }),
+            }
+        }
+
+        shape_styled(x, y, context, &styles, &self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{paint::PaintFlavor, Color};
+
+    fn spans(text: &AttributedText) -> Vec<(Range<usize>, Color)> {
+        text.spans()
+            .map(|(range, paint)| match paint.flavor {
+                PaintFlavor::Color(color) => (range, color),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn set_paint() {
+        let (red, blue) = (Color::rgb(255, 0, 0), Color::rgb(0, 0, 255));
+
+        let text = AttributedText::new("one two", &Paint::color(red))
+            .with_str(" three", &Paint::color(blue))
+            .with_paint(4..9, &Paint::color(blue));
+        assert_eq!(spans(&text), vec![(0..4, red), (4..9, blue), (9..13, blue)]);
+
+        let text = AttributedText::new("abc", &Paint::color(red)).with_paint(1..2, &Paint::color(blue));
+        assert_eq!(spans(&text), vec![(0..1, red), (1..2, blue), (2..3, red)]);
+    }
+}
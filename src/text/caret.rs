@@ -0,0 +1,276 @@
+use std::ops::Range;
+
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{Paragraph, ShapedGlyph, TextMetrics};
+use crate::Bounds;
+
+/// Determines which character a position between two characters belongs to, and so where a caret at that
+/// position is shown where the text changes direction or wraps.
+///
+/// The default value is `Downstream`.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Affinity {
+    /// The position belongs to the character before it.
+    Upstream,
+    /// The position belongs to the character after it.
+    #[default]
+    Downstream,
+}
+
+/// A grapheme cluster of a line of shaped text and where it's drawn.
+struct Slot {
+    range: Range<usize>,
+    left: f32,
+    right: f32,
+    rtl: bool,
+}
+
+/// The grapheme clusters of a line of text, from left to right, with a caret at `x` if the line is empty.
+struct Carets {
+    slots: Vec<Slot>,
+    range: Range<usize>,
+    x: f32,
+}
+
+impl Carets {
+    /// Splits the glyphs of the bytes `range` of the text into grapheme clusters. Glyphs made of several
+    /// grapheme clusters, like ligatures, are split evenly.
+    fn new(glyphs: &[ShapedGlyph], text: &str, range: Range<usize>, x: f32) -> Self {
+        // Runs of glyphs from the same characters, as (byte index, left, right).
+        let mut clusters: Vec<(usize, f32, f32)> = Vec::new();
+
+        for glyph in glyphs {
+            let left = glyph.x - glyph.offset_x - glyph.bearing_x;
+
+            match clusters.last_mut() {
+                Some(cluster) if cluster.0 == glyph.byte_index => cluster.2 = left + glyph.advance_x,
+                previous => {
+                    // Letter spacing is counted to the previous cluster.
+                    if let Some(cluster) = previous {
+                        cluster.2 = cluster.2.max(left);
+                    }
+                    clusters.push((glyph.byte_index, left, left + glyph.advance_x));
+                }
+            }
+        }
+
+        let mut starts: Vec<usize> = clusters.iter().map(|cluster| cluster.0).collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let bidi_info = BidiInfo::new(&text[range.clone()], Some(unicode_bidi::Level::ltr()));
+
+        let mut slots = Vec::new();
+
+        for (start, left, right) in clusters {
+            let end = starts.iter().find(|&&next| next > start).copied().unwrap_or(range.end);
+            let rtl = bidi_info.levels[start - range.start].is_rtl();
+
+            let graphemes: Vec<(usize, &str)> = text[start..end].grapheme_indices(true).collect();
+            let width = (right - left) / graphemes.len() as f32;
+
+            for (i, (offset, grapheme)) in graphemes.into_iter().enumerate() {
+                let (left, right) = if rtl {
+                    (right - width * (i + 1) as f32, right - width * i as f32)
+                } else {
+                    (left + width * i as f32, left + width * (i + 1) as f32)
+                };
+
+                slots.push(Slot {
+                    range: start + offset..start + offset + grapheme.len(),
+                    left,
+                    right,
+                    rtl,
+                });
+            }
+        }
+
+        slots.sort_by(|a, b| a.left.total_cmp(&b.left));
+
+        Self { slots, range, x }
+    }
+
+    fn hit_test(&self, x: f32) -> (usize, Affinity) {
+        let Some(slot) = self
+            .slots
+            .iter()
+            .find(|slot| x < slot.right)
+            .or_else(|| self.slots.last())
+        else {
+            return (self.range.start, Affinity::Downstream);
+        };
+
+        // The left half of a left-to-right cluster is before it, the left half of a right-to-left one after it.
+        if (x < (slot.left + slot.right) / 2.0) != slot.rtl {
+            (slot.range.start, Affinity::Downstream)
+        } else {
+            (slot.range.end, Affinity::Upstream)
+        }
+    }
+
+    fn caret_position(&self, byte_index: usize, affinity: Affinity) -> f32 {
+        // Positions within a grapheme cluster are moved to its start.
+        let byte_index = self
+            .slots
+            .iter()
+            .find(|slot| slot.range.start < byte_index && byte_index < slot.range.end)
+            .map_or(byte_index, |slot| slot.range.start);
+
+        let before = || {
+            self.slots
+                .iter()
+                .find(|slot| slot.range.start == byte_index)
+                .map(|slot| if slot.rtl { slot.right } else { slot.left })
+        };
+        let after = || {
+            self.slots.iter().find(|slot| slot.range.end == byte_index).map(|slot| {
+                if slot.rtl {
+                    slot.left
+                } else {
+                    slot.right
+                }
+            })
+        };
+
+        match affinity {
+            Affinity::Downstream => before().or_else(after),
+            Affinity::Upstream => after().or_else(before),
+        }
+        .unwrap_or(self.x)
+    }
+
+    fn selection_rects(&self, range: &Range<usize>) -> Vec<(f32, f32)> {
+        let mut rects: Vec<(f32, f32)> = Vec::new();
+
+        for slot in &self.slots {
+            if slot.range.start >= range.end || slot.range.end <= range.start {
+                continue;
+            }
+
+            match rects.last_mut() {
+                Some(rect) if slot.left - rect.1 < 0.5 => rect.1 = rect.1.max(slot.right),
+                _ => rects.push((slot.left, slot.right)),
+            }
+        }
+
+        rects
+    }
+}
+
+fn caret_bounds(x: f32, top: f32, bottom: f32) -> Bounds {
+    Bounds {
+        minx: x,
+        miny: top,
+        maxx: x,
+        maxy: bottom,
+    }
+}
+
+impl TextMetrics {
+    fn carets(&self, text: &str) -> Carets {
+        Carets::new(&self.glyphs, text, 0..text.len(), self.x)
+    }
+
+    /// Returns the position in the text closest to `x`, and the character it belongs to.
+    ///
+    /// `text` has to be the text these metrics were measured for. Positions are between grapheme clusters, in
+    /// bytes.
+    pub fn hit_test(&self, text: &str, x: f32) -> (usize, Affinity) {
+        self.carets(text).hit_test(x)
+    }
+
+    /// Returns where a caret at the byte position in the text is shown, as bounds without width.
+    ///
+    /// `text` has to be the text these metrics were measured for. Where the text changes direction, the
+    /// affinity decides if the caret is shown next to the character before or after the position.
+    pub fn caret_position(&self, text: &str, byte_index: usize, affinity: Affinity) -> Bounds {
+        let x = self.carets(text).caret_position(byte_index, affinity);
+        caret_bounds(x, self.y, self.y + self.height)
+    }
+
+    /// Returns the rectangles covering the grapheme clusters of a byte range of the text, from left to right.
+    /// Text with both directions can take several rectangles.
+    ///
+    /// `text` has to be the text these metrics were measured for.
+    pub fn selection_rects(&self, text: &str, range: Range<usize>) -> Vec<Bounds> {
+        self.carets(text)
+            .selection_rects(&range)
+            .into_iter()
+            .map(|(left, right)| Bounds {
+                minx: left,
+                miny: self.y,
+                maxx: right,
+                maxy: self.y + self.height,
+            })
+            .collect()
+    }
+}
+
+impl Paragraph {
+    /// Returns the position in the text closest to `x` and `y`, and the character it belongs to.
+    ///
+    /// `text` has to be the text the paragraph was laid out for. Positions are between grapheme clusters, in
+    /// bytes. At the end of a wrapped line, the position belongs to the character before it.
+    pub fn hit_test(&self, text: &str, x: f32, y: f32) -> (usize, Affinity) {
+        let lines = self.lines();
+        let Some(line) = lines
+            .iter()
+            .find(|line| y < line.top + line.height)
+            .or_else(|| lines.last())
+        else {
+            return (0, Affinity::Downstream);
+        };
+
+        Carets::new(&line.glyphs, text, line.byte_range.clone(), line.x).hit_test(x)
+    }
+
+    /// Returns where a caret at the byte position in the text is shown, as bounds without width spanning the
+    /// height of its line.
+    ///
+    /// `text` has to be the text the paragraph was laid out for. Where a line wraps, the affinity decides if
+    /// the caret is shown at the end of the line or at the start of the next one.
+    pub fn caret_position(&self, text: &str, byte_index: usize, affinity: Affinity) -> Bounds {
+        let mut lines = self
+            .lines()
+            .iter()
+            .filter(|line| line.byte_range.start <= byte_index && byte_index <= line.byte_range.end);
+
+        let line = match affinity {
+            Affinity::Upstream => lines.next(),
+            Affinity::Downstream => lines.next_back(),
+        }
+        .or_else(|| self.lines().last());
+
+        let Some(line) = line else {
+            return caret_bounds(0.0, 0.0, 0.0);
+        };
+
+        let x = Carets::new(&line.glyphs, text, line.byte_range.clone(), line.x).caret_position(byte_index, affinity);
+        caret_bounds(x, line.top, line.top + line.height)
+    }
+
+    /// Returns the rectangles covering the grapheme clusters of a byte range of the text, line by line and
+    /// from left to right, each spanning the height of its line.
+    ///
+    /// `text` has to be the text the paragraph was laid out for.
+    pub fn selection_rects(&self, text: &str, range: Range<usize>) -> Vec<Bounds> {
+        self.lines()
+            .iter()
+            .filter(|line| line.byte_range.start < range.end && line.byte_range.end > range.start)
+            .flat_map(|line| {
+                Carets::new(&line.glyphs, text, line.byte_range.clone(), line.x)
+                    .selection_rects(&range)
+                    .into_iter()
+                    .map(|(left, right)| Bounds {
+                        minx: left,
+                        miny: line.top,
+                        maxx: right,
+                        maxy: line.top + line.height,
+                    })
+            })
+            .collect()
+    }
+}
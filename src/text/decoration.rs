@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use super::{FontId, TextContextImpl, TextDecoration, TextMetrics};
+use crate::{geometry::Position, paint::TextSettings, path::PathCache, ErrorKind, Path, Transform2D};
+
+/// Returns rectangles for the decoration lines of the text settings along the glyphs of the layout, in the
+/// coordinates of the glyphs.
+pub(crate) fn decoration_path(
+    context: &TextContextImpl,
+    layout: &TextMetrics,
+    text_settings: &TextSettings,
+) -> Result<Path, ErrorKind> {
+    let mut path = Path::new();
+
+    let decoration = text_settings.text_decoration;
+    if decoration.is_empty() || layout.glyphs.is_empty() {
+        return Ok(path);
+    }
+
+    let metrics = text_settings
+        .font_ids
+        .iter()
+        .flatten()
+        .find_map(|id| context.font(*id))
+        .or_else(|| context.font(layout.glyphs[0].font_id))
+        .ok_or(ErrorKind::NoFontFound)?
+        .metrics(text_settings.font_size);
+
+    // Lines run along glyphs that follow each other on the same baseline, as (start, end, baseline).
+    let mut runs: Vec<(f32, f32, f32)> = Vec::new();
+
+    for glyph in &layout.glyphs {
+        let start = glyph.x - glyph.offset_x - glyph.bearing_x;
+        let end = start + glyph.advance_x + text_settings.letter_spacing;
+        let baseline = glyph.y - glyph.offset_y + glyph.bearing_y;
+
+        match runs.last_mut() {
+            Some(run) if (run.1 - start).abs() < 0.5 && (run.2 - baseline).abs() < 0.5 => run.1 = end,
+            _ => runs.push((start, end, baseline)),
+        }
+    }
+
+    // (flag, distance of the top of the line above the baseline, thickness, whether glyphs are skipped)
+    let lines = [
+        (
+            TextDecoration::UNDERLINE,
+            metrics.underline_position(),
+            metrics.underline_thickness(),
+            text_settings.text_decoration_skip_ink,
+        ),
+        (
+            TextDecoration::OVERLINE,
+            metrics.ascender(),
+            metrics.underline_thickness(),
+            text_settings.text_decoration_skip_ink,
+        ),
+        (
+            TextDecoration::LINE_THROUGH,
+            metrics.strikeout_position(),
+            metrics.strikeout_thickness(),
+            false,
+        ),
+    ];
+
+    for (flag, position, thickness, skip_ink) in lines {
+        if !decoration.contains(flag) {
+            continue;
+        }
+
+        let thickness = thickness.max(1.0);
+
+        for &(start, end, baseline) in &runs {
+            let top = baseline - position;
+            let end = end - text_settings.letter_spacing;
+
+            // The line is left out around glyphs within a line's thickness of it.
+            let gaps = if skip_ink {
+                ink_extents(
+                    context,
+                    layout,
+                    text_settings.font_size,
+                    top - thickness,
+                    top + thickness * 2.0,
+                )?
+            } else {
+                Vec::new()
+            };
+
+            let mut x = start;
+            for &(gap_start, gap_end) in &gaps {
+                let (gap_start, gap_end) = (gap_start - thickness, gap_end + thickness);
+                if gap_end <= x || gap_start >= end {
+                    continue;
+                }
+
+                if gap_start > x {
+                    path.rect(x, top, gap_start - x, thickness);
+                }
+                x = x.max(gap_end);
+            }
+
+            if end > x {
+                path.rect(x, top, end - x, thickness);
+            }
+        }
+    }
+
+    Ok(path)
+}
+
+/// Returns the horizontal extents of the outlines of each glyph between `top` and `bottom`, ordered from left
+/// to right.
+fn ink_extents(
+    context: &TextContextImpl,
+    layout: &TextMetrics,
+    font_size: f32,
+    top: f32,
+    bottom: f32,
+) -> Result<Vec<(f32, f32)>, ErrorKind> {
+    let mut face_cache: HashMap<FontId, rustybuzz::Face> = HashMap::default();
+    let mut extents = Vec::new();
+
+    for glyph in &layout.glyphs {
+        if glyph.y > bottom || glyph.y + glyph.height < top {
+            continue;
+        }
+
+        let font = context.font(glyph.font_id).ok_or(ErrorKind::NoFontFound)?;
+        let face = face_cache.entry(glyph.font_id).or_insert_with(|| font.face_ref());
+        let Some(outline) = font.glyph(face, glyph.codepoint as u16) else {
+            continue;
+        };
+        let Some(path) = outline.path.as_ref() else {
+            continue;
+        };
+
+        let scale = font.scale(font_size);
+        let transform = Transform2D::new(
+            scale,
+            0.0,
+            0.0,
+            -scale,
+            glyph.x - glyph.bearing_x,
+            glyph.y + glyph.bearing_y,
+        );
+        let cache = PathCache::new(path.verbs(), &transform, 0.25, 0.01);
+
+        let mut extent: Option<(f32, f32)> = None;
+        let mut include = |x: f32| {
+            extent = Some(extent.map_or((x, x), |(min, max)| (min.min(x), max.max(x))));
+        };
+
+        for contour in cache.contour_positions() {
+            let points: Vec<Position> = contour.collect();
+
+            for (i, &p0) in points.iter().enumerate() {
+                let p1 = points[(i + 1) % points.len()];
+                let (min_y, max_y) = (p0.y.min(p1.y), p0.y.max(p1.y));
+                if max_y < top || min_y > bottom {
+                    continue;
+                }
+
+                // The part of the segment between top and bottom.
+                for y in [p0.y.clamp(top, bottom), p1.y.clamp(top, bottom)] {
+                    let t = if p1.y == p0.y { 0.0 } else { (y - p0.y) / (p1.y - p0.y) };
+                    include(p0.x + (p1.x - p0.x) * t.clamp(0.0, 1.0));
+                }
+            }
+        }
+
+        extents.extend(extent);
+    }
+
+    extents.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    Ok(extents)
+}
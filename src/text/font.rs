@@ -27,7 +27,7 @@ pub enum GlyphRendering<'a> {
 #[derive(Copy, Clone, Default, Debug)]
 struct FontFlags(u8);
 
-// TODO: underline, strikeout, subscript, superscript metrics
+// TODO: subscript, superscript metrics
 impl FontFlags {
     fn new(regular: bool, italic: bool, bold: bool, oblique: bool, variable: bool) -> Self {
         let mut flags = 0;
@@ -81,6 +81,10 @@ pub struct FontMetrics {
     ascender: f32,
     descender: f32,
     height: f32,
+    underline_position: f32,
+    underline_thickness: f32,
+    strikeout_position: f32,
+    strikeout_thickness: f32,
     flags: FontFlags,
     weight: u16,
     width: u16,
@@ -91,6 +95,10 @@ impl FontMetrics {
         self.ascender *= scale;
         self.descender *= scale;
         self.height *= scale;
+        self.underline_position *= scale;
+        self.underline_thickness *= scale;
+        self.strikeout_position *= scale;
+        self.strikeout_thickness *= scale;
     }
 
     /// Returns the distance from the baseline to the top of the highest glyph.
@@ -108,6 +116,26 @@ impl FontMetrics {
         self.height.round()
     }
 
+    /// Returns the distance from the baseline to the top of the underline, negative below the baseline.
+    pub fn underline_position(&self) -> f32 {
+        self.underline_position
+    }
+
+    /// Returns the thickness of the underline.
+    pub fn underline_thickness(&self) -> f32 {
+        self.underline_thickness
+    }
+
+    /// Returns the distance from the baseline to the top of the strikeout line.
+    pub fn strikeout_position(&self) -> f32 {
+        self.strikeout_position
+    }
+
+    /// Returns the thickness of the strikeout line.
+    pub fn strikeout_thickness(&self) -> f32 {
+        self.strikeout_thickness
+    }
+
     /// Returns if the font is regular.
     pub fn regular(&self) -> bool {
         self.flags.regular()
@@ -158,10 +186,25 @@ impl Font {
 
         let units_per_em = ttf_font.units_per_em();
 
+        // Fonts without decoration metrics get lines similar to common fonts.
+        let fallback_thickness = (units_per_em / 14) as i16;
+        let underline = ttf_font.underline_metrics().unwrap_or(ttf_parser::LineMetrics {
+            position: ttf_font.descender() / 2,
+            thickness: fallback_thickness,
+        });
+        let strikeout = ttf_font.strikeout_metrics().unwrap_or(ttf_parser::LineMetrics {
+            position: ttf_font.x_height().unwrap_or(ttf_font.ascender() / 2) / 2 + fallback_thickness / 2,
+            thickness: fallback_thickness,
+        });
+
         let metrics = FontMetrics {
             ascender: ttf_font.ascender() as f32,
             descender: ttf_font.descender() as f32,
             height: ttf_font.height() as f32,
+            underline_position: underline.position as f32,
+            underline_thickness: underline.thickness as f32,
+            strikeout_position: strikeout.position as f32,
+            strikeout_thickness: strikeout.thickness as f32,
             flags: FontFlags::new(
                 ttf_font.is_regular(),
                 ttf_font.is_italic(),
@@ -247,7 +290,7 @@ impl Font {
         face: &rustybuzz::Face<'_>,
         codepoint: u16,
         #[allow(unused_variables)] pixels_per_em: u16,
-    ) -> Option<GlyphRendering> {
+    ) -> Option<GlyphRendering<'_>> {
         #[cfg(feature = "image-loading")]
         if let Some(image) = face
             .glyph_raster_image(GlyphId(codepoint), pixels_per_em)
@@ -0,0 +1,256 @@
+use std::ops::Range;
+
+use super::{shape, Align, Baseline, FontMetrics, ShapedGlyph, TextContextImpl, TextMetrics};
+use crate::{paint::TextSettings, ErrorKind};
+
+/// Determines the distance between the baselines of consecutive lines of a [`Paragraph`].
+///
+/// The default value is `Normal`.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineHeight {
+    /// The lines are as high as recommended by the font, see [`FontMetrics::height()`].
+    #[default]
+    Normal,
+    /// The lines are as high as the font size times this factor.
+    Relative(f32),
+    /// The lines are this high, regardless of the font size.
+    Absolute(f32),
+}
+
+/// A line of a [`Paragraph`].
+///
+/// All coordinates are relative to the top left corner of the paragraph.
+#[derive(Clone, Debug)]
+pub struct ParagraphLine {
+    /// The bytes of the paragraph's text on this line, excluding the line break.
+    pub byte_range: Range<usize>,
+    /// X-coordinate of the start of the line after alignment.
+    pub x: f32,
+    /// Width of the line, excluding trailing whitespace.
+    pub width: f32,
+    /// Y-coordinate of the top of the line box.
+    pub top: f32,
+    /// Height of the line box.
+    pub height: f32,
+    /// Y-coordinate of the baseline.
+    pub baseline: f32,
+    /// Distance from the baseline to the top of the highest glyphs of the fonts on this line.
+    pub ascender: f32,
+    /// Distance from the baseline to the bottom of the lowest descenders of the fonts on this line, usually
+    /// negative.
+    pub descender: f32,
+    /// The positioned glyphs of the line. Their byte indices refer to the whole text of the paragraph.
+    pub glyphs: Vec<ShapedGlyph>,
+}
+
+/// Text broken into lines that fit a maximum width, ready to be drawn with
+/// [`Canvas::fill_paragraph()`](crate::Canvas::fill_paragraph).
+///
+/// Lines are broken at line feeds and, where the text doesn't fit the width, between words. The lines are
+/// aligned within the maximum width according to the text align of the paint the paragraph was laid out
+/// with. Its text baseline is ignored, the first line box starts at the top of the paragraph.
+#[derive(Clone, Debug, Default)]
+pub struct Paragraph {
+    lines: Vec<ParagraphLine>,
+    width: f32,
+    height: f32,
+    pub(crate) font_size: f32,
+}
+
+impl Paragraph {
+    /// Returns the lines of the paragraph, from top to bottom.
+    pub fn lines(&self) -> &[ParagraphLine] {
+        &self.lines
+    }
+
+    /// Returns the width of the widest line.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Returns the sum of the heights of the lines.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Returns the glyphs of a line moved by `x` and `y` and scaled for drawing at `scale`.
+    pub(crate) fn line_layout(&self, line: &ParagraphLine, x: f32, y: f32, scale: f32) -> TextMetrics {
+        let glyphs = line
+            .glyphs
+            .iter()
+            .map(|glyph| ShapedGlyph {
+                x: (glyph.x + x) * scale,
+                y: (glyph.y + y) * scale,
+                width: glyph.width * scale,
+                height: glyph.height * scale,
+                advance_x: glyph.advance_x * scale,
+                advance_y: glyph.advance_y * scale,
+                offset_x: glyph.offset_x * scale,
+                offset_y: glyph.offset_y * scale,
+                bearing_x: glyph.bearing_x * scale,
+                bearing_y: glyph.bearing_y * scale,
+                ..*glyph
+            })
+            .collect();
+
+        TextMetrics {
+            x: (line.x + x) * scale,
+            y: (line.top + y) * scale,
+            width: line.width * scale,
+            height: line.height * scale,
+            glyphs,
+            final_byte_index: line.byte_range.end,
+        }
+    }
+}
+
+pub(crate) fn layout_paragraph(
+    context: &mut TextContextImpl,
+    text: &str,
+    text_settings: &TextSettings,
+    max_width: f32,
+    line_height: LineHeight,
+) -> Result<Paragraph, ErrorKind> {
+    let mut text_settings = text_settings.clone();
+    let align = text_settings.text_align;
+    text_settings.text_align = Align::Left;
+    text_settings.text_baseline = Baseline::Alphabetic;
+
+    let primary_metrics = context.find_font(&text_settings.font_ids, |(_, font)| {
+        (false, font.metrics(text_settings.font_size))
+    })?;
+
+    let mut paragraph = Paragraph {
+        font_size: text_settings.font_size,
+        ..Default::default()
+    };
+
+    let mut start = 0;
+
+    for hard_line in text.split('\n') {
+        let end = start + hard_line.strip_suffix('\r').unwrap_or(hard_line).len();
+        let mut line_start = start;
+
+        loop {
+            let length = if line_start < end {
+                context.break_text(max_width, &text[line_start..end], &text_settings)?
+            } else {
+                0
+            };
+
+            // Text that can't be broken up any further takes the rest of the line.
+            let line_end = if length == 0 { end } else { line_start + length };
+
+            let line = layout_line(
+                context,
+                text,
+                line_start..line_end,
+                &text_settings,
+                &primary_metrics,
+                line_height,
+                paragraph.height,
+            )?;
+            paragraph.width = paragraph.width.max(line.width);
+            paragraph.height += line.height;
+            paragraph.lines.push(line);
+
+            line_start = line_end;
+            if line_start >= end {
+                break;
+            }
+        }
+
+        start += hard_line.len() + 1;
+    }
+
+    // Without a width to fit, lines are aligned to the widest one.
+    let align_width = if max_width.is_finite() {
+        max_width
+    } else {
+        paragraph.width
+    };
+
+    for line in &mut paragraph.lines {
+        let offset = match align {
+            Align::Left => 0.0,
+            Align::Center => (align_width - line.width) / 2.0,
+            Align::Right => align_width - line.width,
+        };
+
+        line.x += offset;
+        for glyph in &mut line.glyphs {
+            glyph.x += offset;
+        }
+    }
+
+    Ok(paragraph)
+}
+
+fn layout_line(
+    context: &mut TextContextImpl,
+    text: &str,
+    byte_range: Range<usize>,
+    text_settings: &TextSettings,
+    primary_metrics: &FontMetrics,
+    line_height: LineHeight,
+    top: f32,
+) -> Result<ParagraphLine, ErrorKind> {
+    let line_text = &text[byte_range.clone()];
+
+    let mut ascender: f32 = 0.0;
+    let mut descender: f32 = 0.0;
+    let mut font_height: f32 = 0.0;
+
+    let mut metrics = shape(0.0, 0.0, context, text_settings, line_text, None)?;
+
+    for glyph in &mut metrics.glyphs {
+        glyph.byte_index += byte_range.start;
+
+        let font = context.font_mut(glyph.font_id).ok_or(ErrorKind::NoFontFound)?;
+        let font_metrics = font.metrics(text_settings.font_size);
+        ascender = ascender.max(font_metrics.ascender());
+        descender = descender.min(font_metrics.descender());
+        font_height = font_height.max(font_metrics.height());
+    }
+
+    if metrics.glyphs.is_empty() {
+        ascender = primary_metrics.ascender();
+        descender = primary_metrics.descender();
+        font_height = primary_metrics.height();
+    }
+
+    let height = match line_height {
+        LineHeight::Normal => font_height,
+        LineHeight::Relative(factor) => text_settings.font_size * factor,
+        LineHeight::Absolute(height) => height,
+    };
+
+    // Space between the lines is split evenly above and below the glyphs.
+    let baseline = (top + (height - (ascender - descender)) / 2.0 + ascender).round();
+
+    // Whitespace at the end of a broken line doesn't take part in the alignment.
+    let trailing_whitespace = byte_range.start + line_text.trim_end().len();
+    let trailing_width: f32 = metrics
+        .glyphs
+        .iter()
+        .filter(|glyph| glyph.byte_index >= trailing_whitespace)
+        .map(|glyph| glyph.advance_x + text_settings.letter_spacing)
+        .sum();
+
+    for glyph in &mut metrics.glyphs {
+        glyph.y += baseline;
+    }
+
+    Ok(ParagraphLine {
+        byte_range,
+        x: 0.0,
+        width: metrics.width() - trailing_width,
+        top,
+        height,
+        baseline,
+        ascender,
+        descender,
+        glyphs: metrics.glyphs,
+    })
+}
@@ -1,4 +1,7 @@
-use femtovg::{renderer::Void, Baseline, Canvas, Color, FillRule, Paint, Path, Solidity};
+use femtovg::{
+    renderer::Void, Affinity, Align, AttributedText, Baseline, Bounds, Canvas, Color, FillRule, LineCap, LineHeight,
+    Paint, Path, Solidity, Transform2D, Verb,
+};
 
 #[test]
 fn path_with_single_move_to() {
@@ -120,6 +123,256 @@ fn degenerate_arc() {
     canvas.stroke_path(&path, &Paint::color(Color::rgb(100, 100, 100)));
 }
 
+#[test]
+fn degenerate_elliptical_arc_to() {
+    let mut canvas = Canvas::new(Void).unwrap();
+
+    let mut path = Path::new();
+    path.move_to(10.0, 10.0);
+    // Arc to the current point, zero and non-finite radii, and radii too small to reach the end point.
+    path.elliptical_arc_to(5.0, 5.0, 0.0, false, true, 10.0, 10.0);
+    path.elliptical_arc_to(0.0, 5.0, 0.0, false, true, 20.0, 10.0);
+    path.elliptical_arc_to(f32::INFINITY, 5.0, 0.0, false, true, 30.0, 10.0);
+    path.elliptical_arc_to(-1.0, 1.0, 0.3, true, false, 50.0, 30.0);
+
+    let verbs: Vec<_> = path.verbs().collect();
+    assert!(matches!(
+        verbs[..3],
+        [Verb::MoveTo(..), Verb::LineTo(..), Verb::LineTo(..)]
+    ));
+    assert!(verbs[3..].iter().all(|verb| matches!(verb, Verb::BezierTo(..))));
+    assert!(matches!(verbs.last(), Some(Verb::BezierTo(.., x, y)) if (x, y) == (&50.0, &30.0)));
+
+    canvas.fill_path(&path, &Paint::color(Color::rgb(100, 100, 100)));
+    canvas.stroke_path(&path, &Paint::color(Color::rgb(100, 100, 100)));
+}
+
+#[test]
+fn elliptical_arc_to() {
+    let on_ellipse = |path: &Path, cx: f32, cy: f32, rx: f32, ry: f32| {
+        let mut last = (0.0, 0.0);
+        for verb in path.verbs() {
+            match verb {
+                Verb::MoveTo(x, y) | Verb::LineTo(x, y) => last = (x, y),
+                Verb::BezierTo(x1, y1, x2, y2, x, y) => {
+                    // Check the point in the middle of the segment.
+                    let mx = 0.125 * (last.0 + x) + 0.375 * (x1 + x2);
+                    let my = 0.125 * (last.1 + y) + 0.375 * (y1 + y2);
+                    let d = ((mx - cx) / rx).powi(2) + ((my - cy) / ry).powi(2);
+                    assert!((d - 1.0).abs() < 1e-3, "({mx}, {my}) is not on the ellipse");
+                    last = (x, y);
+                }
+                _ => {}
+            }
+        }
+    };
+
+    // The large arc from the left to the top of an ellipse centered at the origin, drawn through the bottom.
+    let mut path = Path::new();
+    path.move_to(-20.0, 0.0);
+    path.elliptical_arc_to(20.0, 10.0, 0.0, true, false, 0.0, -10.0);
+    on_ellipse(&path, 0.0, 0.0, 20.0, 10.0);
+    assert_eq!(path.verbs().count(), 4);
+
+    // The small arc with the other sweep direction around the same center.
+    let mut path = Path::new();
+    path.move_to(-20.0, 0.0);
+    path.elliptical_arc_to(20.0, 10.0, 0.0, false, true, 0.0, -10.0);
+    on_ellipse(&path, 0.0, 0.0, 20.0, 10.0);
+    assert_eq!(path.verbs().count(), 2);
+
+    // Radii that are too small are scaled up to a half ellipse between the points.
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.elliptical_arc_to(1.0, 1.0, 0.0, false, true, 40.0, 0.0);
+    on_ellipse(&path, 20.0, 0.0, 20.0, 20.0);
+
+    // A rotation by 90 degrees swaps the axes.
+    let mut path = Path::new();
+    path.move_to(0.0, -20.0);
+    path.elliptical_arc_to(20.0, 10.0, std::f32::consts::FRAC_PI_2, false, true, 10.0, 0.0);
+    on_ellipse(&path, 0.0, 0.0, 10.0, 20.0);
+}
+
+#[test]
+fn path_transform() {
+    let mut canvas = Canvas::new(Void).unwrap();
+    canvas.set_size(100, 100, 1.0);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 10.0, 10.0);
+    assert!(canvas.contains_point(&path, 5.0, 5.0, FillRule::NonZero));
+
+    // The path is no longer where it was cached at.
+    path.transform(&Transform2D::translation(50.0, 50.0));
+    assert!(!canvas.contains_point(&path, 5.0, 5.0, FillRule::NonZero));
+    assert!(canvas.contains_point(&path, 55.0, 55.0, FillRule::NonZero));
+
+    let scaled = path.transformed(&Transform2D::scaling(0.5, 0.5));
+    assert_eq!(
+        scaled.bounds(),
+        Bounds {
+            minx: 25.0,
+            miny: 25.0,
+            maxx: 30.0,
+            maxy: 30.0
+        }
+    );
+
+    // Later segments continue from the transformed end point, so an arc to it is skipped.
+    let mut path = Path::new();
+    path.move_to(1.0, 2.0);
+    path.transform(&Transform2D::translation(10.0, 0.0));
+    path.elliptical_arc_to(5.0, 5.0, 0.0, false, true, 11.0, 2.0);
+    assert_eq!(path.verbs().count(), 1);
+}
+
+#[test]
+fn path_quad_and_conic_verbs() {
+    let mut path = Path::new();
+    path.move_to(10.0, 0.0);
+    path.quad_to(10.0, 10.0, 0.0, 10.0);
+    path.conic_to(-10.0, 10.0, -10.0, 0.0, std::f32::consts::FRAC_1_SQRT_2);
+    path.conic_to(-10.0, -10.0, 0.0, -10.0, 0.0);
+
+    // The verbs come back as they were added, a weight that isn't positive gives a line.
+    assert!(
+        matches!(path.verbs().nth(1), Some(Verb::QuadTo(cx, cy, x, y)) if (cx, cy, x, y) == (10.0, 10.0, 0.0, 10.0))
+    );
+    assert!(
+        matches!(path.verbs().nth(2), Some(Verb::ConicTo(.., weight)) if weight == std::f32::consts::FRAC_1_SQRT_2)
+    );
+    assert!(matches!(path.verbs().nth(3), Some(Verb::LineTo(x, y)) if (x, y) == (0.0, -10.0)));
+
+    // The conic is an exact quarter circle.
+    let mut circle = Path::new();
+    circle.move_to(10.0, 0.0);
+    for (cx, cy, x, y) in [
+        (10.0, 10.0, 0.0, 10.0),
+        (-10.0, 10.0, -10.0, 0.0),
+        (-10.0, -10.0, 0.0, -10.0),
+        (10.0, -10.0, 10.0, 0.0),
+    ] {
+        circle.conic_to(cx, cy, x, y, std::f32::consts::FRAC_1_SQRT_2);
+    }
+    circle.close();
+
+    let length = circle.measure().length();
+    assert!((length - 20.0 * std::f32::consts::PI).abs() < 0.05, "{length}");
+
+    let bounds = circle.transformed(&Transform2D::translation(50.0, 50.0)).bounds();
+    for (actual, expected) in [
+        (bounds.minx, 40.0),
+        (bounds.miny, 40.0),
+        (bounds.maxx, 60.0),
+        (bounds.maxy, 60.0),
+    ] {
+        assert!((actual - expected).abs() < 1e-4);
+    }
+
+    let mut canvas = Canvas::new(Void).unwrap();
+    canvas.set_size(100, 100, 1.0);
+    canvas.translate(50.0, 50.0);
+    assert!(canvas.contains_point(&circle, 57.0, 57.0, FillRule::NonZero));
+    assert!(!canvas.contains_point(&circle, 58.0, 58.0, FillRule::NonZero));
+
+    // Reversing keeps the curves.
+    circle.reverse();
+    assert!(
+        matches!(circle.verbs().nth(1), Some(Verb::ConicTo(cx, cy, x, y, _)) if (cx, cy, x, y) == (10.0, -10.0, 0.0, -10.0))
+    );
+
+    // SVG has no conics, they are written as quadratic curves.
+    let data = circle.to_svg_path_data();
+    assert!(data.starts_with("M10 0 Q") && !data.contains('C'));
+}
+
+#[test]
+fn path_bounds() {
+    assert_eq!(Path::new().bounds(), Bounds::default());
+
+    let mut path = Path::new();
+    path.circle(50.0, 50.0, 10.0);
+
+    let bounds = path.bounds();
+    for (actual, expected) in [
+        (bounds.minx, 40.0),
+        (bounds.miny, 40.0),
+        (bounds.maxx, 60.0),
+        (bounds.maxy, 60.0),
+    ] {
+        assert!((actual - expected).abs() < 1e-4);
+    }
+
+    // The control points of a curve bulge out further than the curve.
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.bezier_to(0.0, 20.0, 10.0, 20.0, 10.0, 0.0);
+    assert!((path.bounds().maxy - 15.0).abs() < 1e-4);
+    assert_eq!(path.control_bounds().maxy, 20.0);
+
+    // Paths far away from the origin aren't clamped.
+    let mut path = Path::new();
+    path.rect(2e6, 2e6, 10.0, 10.0);
+    for bounds in [path.bounds(), path.control_bounds()] {
+        assert_eq!(
+            (bounds.minx, bounds.miny, bounds.maxx, bounds.maxy),
+            (2e6, 2e6, 2e6 + 10.0, 2e6 + 10.0)
+        );
+    }
+
+    let mut path = Path::new();
+    path.move_to(-3e6, -3e6);
+    path.line_to(-2e6, -2e6);
+    assert_eq!(path.bounds().maxx, -2e6);
+}
+
+#[test]
+fn path_reverse() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.bezier_to(10.0, 5.0, 5.0, 10.0, 0.0, 10.0);
+    path.close();
+    path.solidity(Solidity::Hole);
+    path.move_to(20.0, 20.0);
+    path.line_to(30.0, 20.0);
+
+    path.reverse();
+
+    let verbs: Vec<_> = path.verbs().map(|verb| format!("{verb:?}")).collect();
+    assert_eq!(
+        verbs,
+        [
+            "MoveTo(0.0, 10.0)",
+            "BezierTo(5.0, 10.0, 10.0, 5.0, 10.0, 0.0)",
+            "LineTo(0.0, 0.0)",
+            "Close",
+            "Hole",
+            "MoveTo(30.0, 20.0)",
+            "LineTo(20.0, 20.0)",
+        ]
+    );
+}
+
+#[test]
+fn path_extend() {
+    let mut a = Path::new();
+    a.rect(0.0, 0.0, 10.0, 10.0);
+
+    let mut b = Path::new();
+    b.move_to(20.0, 20.0);
+    b.line_to(30.0, 30.0);
+
+    let count = a.verbs().count();
+    a.extend(&b);
+    assert_eq!(a.verbs().count(), count + 2);
+
+    // New segments continue from the end of the appended path, so an arc to it is skipped.
+    a.elliptical_arc_to(5.0, 5.0, 0.0, false, true, 30.0, 30.0);
+    assert_eq!(a.verbs().count(), count + 2);
+}
+
 #[test]
 fn path_contains_point() {
     let mut canvas = Canvas::new(Void).unwrap();
@@ -144,6 +397,44 @@ fn path_contains_point() {
     assert!(canvas.contains_point(&path, 50.0, 5.0, FillRule::NonZero));
 }
 
+#[test]
+fn path_contains_point_in_stroke() {
+    let mut canvas = Canvas::new(Void).unwrap();
+    canvas.set_size(100, 100, 1.0);
+
+    let mut path = Path::new();
+    path.move_to(20.0, 50.0);
+    path.line_to(80.0, 50.0);
+
+    let mut paint = Paint::color(Color::black()).with_line_width(10.0);
+    assert!(canvas.contains_point_in_stroke(&path, &paint, 50.0, 54.0));
+    assert!(!canvas.contains_point_in_stroke(&path, &paint, 50.0, 56.0));
+    assert!(!canvas.contains_point_in_stroke(&path, &paint, 17.0, 50.0));
+
+    // Square caps extend the line by half its width.
+    paint.set_line_cap(LineCap::Square);
+    assert!(canvas.contains_point_in_stroke(&path, &paint, 17.0, 50.0));
+
+    // Dashes leave gaps.
+    paint.set_line_cap(LineCap::Butt);
+    paint.set_dash_array(&[10.0, 10.0]);
+    assert!(canvas.contains_point_in_stroke(&path, &paint, 25.0, 50.0));
+    assert!(!canvas.contains_point_in_stroke(&path, &paint, 35.0, 50.0));
+
+    // The line width is scaled by the transform.
+    paint.set_dash_array(&[]);
+    canvas.translate(50.0, 50.0);
+    canvas.scale(2.0, 2.0);
+    canvas.translate(-50.0, -50.0);
+    assert!(canvas.contains_point_in_stroke(&path, &paint, 50.0, 59.0));
+    assert!(!canvas.contains_point_in_stroke(&path, &paint, 50.0, 61.0));
+
+    // Hairlines can still be hit.
+    canvas.reset_transform();
+    paint.set_line_width(0.1);
+    assert!(canvas.contains_point_in_stroke(&path, &paint, 50.0, 50.4));
+}
+
 #[test]
 fn text_location_respects_scale() {
     let mut canvas = Canvas::new(Void).unwrap();
@@ -194,6 +485,11 @@ fn font_measure_without_canvas() {
         .expect("font measuring failed unexpectedly");
 
     assert_eq!(metrics.ascender().ceil(), 17.);
+
+    assert!(metrics.underline_position() < 0. && metrics.underline_position() > metrics.descender());
+    assert!(metrics.underline_thickness() > 0.);
+    assert!(metrics.strikeout_position() > 0. && metrics.strikeout_position() < metrics.ascender());
+    assert!(metrics.strikeout_thickness() > 0.);
 }
 
 #[test]
@@ -220,3 +516,253 @@ fn break_text_without_canvas() {
         vec!["Multiple ", "Lines ", "Broken"]
     );
 }
+
+#[test]
+fn paragraph_layout_without_canvas() {
+    let text_context = femtovg::TextContext::default();
+
+    let font_id = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+
+    let paint = femtovg::Paint::default().with_font(&[font_id]).with_font_size(16.);
+    let font_metrics = text_context.measure_font(&paint).unwrap();
+
+    let text = "Multiple Lines Broken\r\n\nEnd";
+
+    let paragraph = text_context
+        .layout_paragraph(text, &paint, 60., LineHeight::Normal)
+        .expect("text shaping failed unexpectedly");
+
+    let lines = paragraph.lines();
+    assert_eq!(
+        lines
+            .iter()
+            .map(|line| &text[line.byte_range.clone()])
+            .collect::<Vec<_>>(),
+        vec!["Multiple ", "Lines ", "Broken", "", "End"]
+    );
+
+    for (i, line) in lines.iter().enumerate() {
+        assert_eq!(line.top, i as f32 * font_metrics.height());
+        assert_eq!(line.height, font_metrics.height());
+        assert!(line.baseline > line.top && line.baseline < line.top + line.height);
+        assert!(line.width <= 60.);
+        assert!(line
+            .glyphs
+            .iter()
+            .all(|glyph| line.byte_range.contains(&glyph.byte_index)));
+    }
+    assert_eq!(paragraph.height(), 5. * font_metrics.height());
+
+    // Trailing whitespace is not aligned.
+    let paint = paint.with_text_align(Align::Right);
+    let paragraph = text_context
+        .layout_paragraph(text, &paint, 60., LineHeight::Absolute(20.))
+        .unwrap();
+
+    for line in paragraph.lines() {
+        assert!((line.x + line.width - 60.).abs() < 0.01);
+        assert_eq!(line.height, 20.);
+    }
+    assert_eq!(paragraph.lines()[1].baseline - paragraph.lines()[0].baseline, 20.);
+
+    let mut canvas = Canvas::new_with_text_context(Void, text_context).unwrap();
+    canvas.fill_paragraph(10., 10., &paragraph, &paint).unwrap();
+}
+
+#[test]
+fn attributed_text_measure() {
+    let text_context = femtovg::TextContext::default();
+
+    let font_id = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+
+    let paint = Paint::default().with_font(&[font_id]).with_font_size(16.);
+    let plain = text_context.measure_text(0., 0., "Hello World", &paint).unwrap();
+
+    // Ranges with the same paint measure like plain text.
+    let text = AttributedText::new("Hello ", &paint).with_str("World", &paint);
+    let metrics = text_context.measure_attributed_text(0., 0., &text).unwrap();
+    assert_eq!(metrics.width(), plain.width());
+
+    let large = paint.clone().with_font_size(32.).with_letter_spacing(2.);
+    let text = text.with_paint(6..11, &large);
+    let metrics = text_context.measure_attributed_text(0., 0., &text).unwrap();
+    assert!(metrics.width() > plain.width() * 1.5);
+
+    let glyphs = &metrics.glyphs;
+    assert_eq!(glyphs.len(), 11);
+    assert!(glyphs[6].height > glyphs[0].height * 1.5);
+    assert!(glyphs.windows(2).all(|pair| pair[0].x < pair[1].x));
+
+    let mut canvas = Canvas::new_with_text_context(Void, text_context).unwrap();
+    canvas.scale(2., 2.);
+    let drawn = canvas.fill_attributed_text(0., 0., &text).unwrap();
+    assert!((drawn.width() - metrics.width()).abs() < 1.);
+}
+
+#[test]
+fn attributed_text_paint_keeps_shaping() {
+    let text_context = femtovg::TextContext::default();
+
+    let font_id = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+
+    let paint = Paint::color(Color::black()).with_font(&[font_id]).with_font_size(16.);
+    let plain = text_context.measure_text(0., 0., "AVAVA", &paint).unwrap();
+
+    // Changing only the color of a letter doesn't split the text for shaping, so kerning is kept.
+    let red = paint.clone().with_color(Color::rgb(255, 0, 0));
+    let text = AttributedText::new("AVAVA", &paint).with_paint(1..2, &red);
+    let metrics = text_context.measure_attributed_text(0., 0., &text).unwrap();
+
+    assert_eq!(metrics.width(), plain.width());
+    for (glyph, plain_glyph) in metrics.glyphs.iter().zip(&plain.glyphs) {
+        assert_eq!(glyph.x, plain_glyph.x);
+    }
+}
+
+#[test]
+fn truncate_text_without_canvas() {
+    let text_context = femtovg::TextContext::default();
+
+    let font_id = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+
+    let paint = Paint::default().with_font(&[font_id]).with_font_size(16.);
+
+    let text = "Hello World";
+    let width = text_context.measure_text(0., 0., text, &paint).unwrap().width();
+    assert_eq!(text_context.truncate_text(width, text, &paint).unwrap(), text.len());
+
+    // Whitespace before the ellipsis is left out.
+    let hello = text_context.measure_text(0., 0., "Hello …", &paint).unwrap().width();
+    assert_eq!(text_context.truncate_text(hello + 1., text, &paint).unwrap(), 5);
+
+    // Grapheme clusters are kept together.
+    let text = "Cafe\u{301}s and more";
+    let cafe = text_context
+        .measure_text(0., 0., "Cafe\u{301}…", &paint)
+        .unwrap()
+        .width();
+    assert_eq!(text_context.truncate_text(cafe - 0.1, text, &paint).unwrap(), 3);
+    assert_eq!(text_context.truncate_text(cafe + 0.1, text, &paint).unwrap(), 6);
+}
+
+#[test]
+fn fill_text_truncated() {
+    let mut canvas = Canvas::new(Void).unwrap();
+
+    let roboto = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+    let amiri = canvas.add_font("examples/assets/amiri-regular.ttf").unwrap();
+
+    let paint = Paint::default().with_font(&[roboto]).with_font_size(16.);
+    let metrics = canvas.fill_text_truncated(0., 0., "Hello World", 50., &paint).unwrap();
+    assert!(metrics.width() <= 50.);
+    assert_eq!(metrics.glyphs.last().unwrap().c, '…');
+
+    // The ellipsis ends right-to-left text on the left.
+    let paint = Paint::default().with_font(&[amiri]).with_font_size(16.);
+    let metrics = canvas
+        .fill_text_truncated(0., 0., "مرحبا بالعالم", 50., &paint)
+        .unwrap();
+    assert!(metrics.width() <= 50.);
+    let ellipsis = metrics.glyphs.iter().position(|glyph| glyph.c == '…').unwrap();
+    assert!(metrics.glyphs[..ellipsis].iter().all(|glyph| glyph.advance_x == 0.));
+}
+
+#[test]
+fn caret_positions_and_hit_testing() {
+    let text_context = femtovg::TextContext::default();
+
+    let roboto = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+    let amiri = text_context
+        .add_font_file("examples/assets/amiri-regular.ttf")
+        .expect("Font not found");
+
+    let paint = Paint::default().with_font(&[roboto, amiri]).with_font_size(16.);
+
+    let text = "Cafe\u{301} fit";
+    let metrics = text_context.measure_text(10., 0., text, &paint).unwrap();
+    let caret = |byte_index| metrics.caret_position(text, byte_index, Affinity::Downstream).minx;
+
+    assert_eq!(caret(0), 10.);
+    assert!((caret(text.len()) - (10. + metrics.width())).abs() < 0.5);
+
+    // Positions within a grapheme cluster are moved to its start.
+    assert_eq!(caret(4), caret(3));
+    assert_eq!(metrics.hit_test(text, caret(6) - 0.5), (6, Affinity::Upstream));
+    assert_eq!(metrics.hit_test(text, caret(6) + 0.5), (6, Affinity::Downstream));
+
+    // Ligatures are split between their characters.
+    assert!(caret(8) < caret(9) && caret(9) < caret(10));
+
+    assert_eq!(metrics.hit_test(text, -100.), (0, Affinity::Downstream));
+    assert_eq!(metrics.hit_test(text, 1000.), (text.len(), Affinity::Upstream));
+
+    let rects = metrics.selection_rects(text, 1..6);
+    assert_eq!(rects.len(), 1);
+    assert_eq!((rects[0].minx, rects[0].maxx), (caret(1), caret(6)));
+    assert_eq!(
+        (rects[0].miny, rects[0].maxy),
+        (metrics.y, metrics.y + metrics.height())
+    );
+
+    // Right-to-left text starts at the right.
+    let text = "ab \u{633}\u{644}\u{627}\u{645}";
+    let metrics = text_context.measure_text(0., 0., text, &paint).unwrap();
+    let caret = |byte_index, affinity| metrics.caret_position(text, byte_index, affinity).minx;
+
+    assert!(caret(3, Affinity::Downstream) > caret(5, Affinity::Downstream));
+    assert!((caret(3, Affinity::Downstream) - metrics.width()).abs() < 0.5);
+
+    // The position before the Arabic word is after the space on the left, the end of the text is next to it.
+    assert!(caret(3, Affinity::Upstream) < caret(5, Affinity::Downstream));
+    assert!((caret(text.len(), Affinity::Upstream) - caret(3, Affinity::Upstream)).abs() < 0.5);
+    assert_eq!(metrics.hit_test(text, metrics.width() - 0.5), (3, Affinity::Downstream));
+
+    // The selection of "b" and the first Arabic letter isn't contiguous.
+    assert_eq!(metrics.selection_rects(text, 1..5).len(), 2);
+}
+
+#[test]
+fn paragraph_caret_positions() {
+    let text_context = femtovg::TextContext::default();
+
+    let font_id = text_context
+        .add_font_file("examples/assets/Roboto-Regular.ttf")
+        .expect("Font not found");
+
+    let paint = Paint::default().with_font(&[font_id]).with_font_size(16.);
+
+    let text = "Multiple Lines Broken";
+    let paragraph = text_context
+        .layout_paragraph(text, &paint, 60., LineHeight::Normal)
+        .unwrap();
+    let lines = paragraph.lines();
+
+    // The wrapped position is at the end of the first line or the start of the second.
+    let upstream = paragraph.caret_position(text, 9, Affinity::Upstream);
+    let downstream = paragraph.caret_position(text, 9, Affinity::Downstream);
+    assert_eq!(
+        (upstream.miny, upstream.maxy),
+        (lines[0].top, lines[0].top + lines[0].height)
+    );
+    assert_eq!((downstream.miny, downstream.minx), (lines[1].top, lines[1].x));
+
+    let y = lines[1].top + lines[1].height / 2.;
+    assert_eq!(paragraph.hit_test(text, -10., y), (9, Affinity::Downstream));
+    assert_eq!(paragraph.hit_test(text, 1000., y), (15, Affinity::Upstream));
+    assert_eq!(paragraph.hit_test(text, 1000., 1000.), (text.len(), Affinity::Upstream));
+
+    let rects = paragraph.selection_rects(text, 4..18);
+    assert_eq!(rects.len(), 3);
+    assert_eq!(rects[1].minx, lines[1].x);
+    assert_eq!(rects[2].miny, lines[2].top);
+}
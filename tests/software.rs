@@ -0,0 +1,827 @@
+use femtovg::{
+    renderer::Software, rgb::RGBA8, AttributedText, BlendMode, BooleanOp, Bounds, Canvas, Color, CompositeOperation,
+    FillRule, GradientColorSpace, GradientSpread, ImageFilter, ImageFlags, LineHeight, Paint, Path, PixelFormat,
+    RenderTarget, TextDecoration,
+};
+
+fn canvas(width: u32, height: u32) -> Canvas<Software> {
+    let mut canvas = Canvas::new(Software::new()).unwrap();
+    canvas.set_size(width, height, 1.0);
+    canvas
+}
+
+fn pixel(canvas: &mut Canvas<Software>, x: usize, y: usize) -> RGBA8 {
+    let image = canvas.screenshot().unwrap();
+    image.buf()[y * image.width() + x]
+}
+
+fn star() -> Path {
+    // A pentagram, the center of which has a winding number of two.
+    let mut path = Path::new();
+    for i in 0..5 {
+        let angle = -std::f32::consts::FRAC_PI_2 + i as f32 * 4.0 * std::f32::consts::PI / 5.0;
+        let (x, y) = (50.0 + 40.0 * angle.cos(), 50.0 + 40.0 * angle.sin());
+        if i == 0 {
+            path.move_to(x, y);
+        } else {
+            path.line_to(x, y);
+        }
+    }
+    path.close();
+    path
+}
+
+#[test]
+fn screenshot_has_screen_size() {
+    let mut canvas = canvas(30, 20);
+    canvas.flush();
+
+    let image = canvas.screenshot().unwrap();
+    assert_eq!((image.width(), image.height()), (30, 20));
+    assert!(image.pixels().all(|pixel| pixel == RGBA8::new(0, 0, 0, 0)));
+}
+
+#[test]
+fn clear_rect() {
+    let mut canvas = canvas(20, 20);
+    canvas.clear_rect(5, 5, 10, 10, Color::rgb(0, 0, 255));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 10, 10), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 2, 2), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn convex_fill() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.rect(20.0, 20.0, 60.0, 60.0);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 20, 20), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 79, 79), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 10, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 85, 50), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn concave_fill_rules() {
+    let mut canvas = canvas(100, 100);
+    canvas.fill_path(&star(), &Paint::color(Color::rgb(0, 255, 0)));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 255, 0, 255));
+    assert_eq!(pixel(&mut canvas, 50, 15), RGBA8::new(0, 255, 0, 255));
+    assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0));
+
+    let mut canvas = self::canvas(100, 100);
+    let paint = Paint::color(Color::rgb(0, 255, 0)).with_fill_rule(FillRule::EvenOdd);
+    canvas.fill_path(&star(), &paint);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 50, 15), RGBA8::new(0, 255, 0, 255));
+
+    // The stencil buffer is reset after each fill.
+    canvas.fill_path(&star(), &paint);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn boolean_ops() {
+    let green = Paint::color(Color::rgb(0, 255, 0));
+
+    // The even-odd star becomes a ring of triangles around a pentagonal hole that fills with the default rule.
+    let mut canvas = canvas(100, 100);
+    let ring = star().boolean_op(&Path::new(), BooleanOp::Union, FillRule::EvenOdd);
+    canvas.fill_path(&ring, &green);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 50, 15), RGBA8::new(0, 255, 0, 255));
+
+    // Cutting a square out of the star.
+    let mut canvas = self::canvas(100, 100);
+    let mut square = Path::new();
+    square.rect(0.0, 0.0, 100.0, 40.0);
+    canvas.fill_path(
+        &star().boolean_op(&square, BooleanOp::Difference, FillRule::NonZero),
+        &green,
+    );
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 15), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 255, 0, 255));
+}
+
+#[test]
+fn stroke() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.move_to(10.0, 50.0);
+    path.line_to(90.0, 50.0);
+
+    canvas.stroke_path(&path, &Paint::color(Color::rgb(0, 0, 255)).with_line_width(10.0));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 50, 46), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 50, 40), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn stencil_stroke_does_not_overlap() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.move_to(10.0, 10.0);
+    path.line_to(90.0, 90.0);
+    path.line_to(90.0, 10.0);
+    path.line_to(10.0, 90.0);
+
+    let paint = Paint::color(Color::rgba(0, 0, 255, 128))
+        .with_line_width(10.0)
+        .with_stencil_strokes(true);
+    canvas.stroke_path(&path, &paint);
+    canvas.flush();
+
+    // The crossing is only blended once.
+    assert_eq!(pixel(&mut canvas, 50, 50), pixel(&mut canvas, 30, 30));
+    assert_eq!(pixel(&mut canvas, 50, 50).a, 128);
+}
+
+#[test]
+fn global_alpha_and_blending() {
+    let mut canvas = canvas(10, 10);
+    canvas.clear_rect(0, 0, 10, 10, Color::rgb(255, 255, 255));
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 10.0, 10.0);
+    canvas.set_global_alpha(0.5);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(0, 0, 0)));
+    canvas.flush();
+
+    let RGBA8 { r, g, b, a } = pixel(&mut canvas, 5, 5);
+    assert!((127..=128).contains(&r));
+    assert_eq!((r, r, 255), (g, b, a));
+}
+
+#[test]
+fn linear_gradient() {
+    let mut canvas = canvas(100, 10);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 10.0);
+    let paint = Paint::linear_gradient(0.0, 0.0, 100.0, 0.0, Color::rgb(0, 0, 0), Color::rgb(255, 255, 255));
+    canvas.fill_path(&path, &paint);
+    canvas.flush();
+
+    let left = pixel(&mut canvas, 1, 5);
+    let middle = pixel(&mut canvas, 50, 5);
+    let right = pixel(&mut canvas, 98, 5);
+
+    assert!(left.r < 10);
+    assert!((120..=135).contains(&middle.r));
+    assert!(right.r > 245);
+}
+
+#[test]
+fn render_to_image() {
+    let mut canvas = canvas(40, 40);
+
+    let image = canvas
+        .create_image_empty(20, 20, PixelFormat::Rgba8, ImageFlags::FLIP_Y)
+        .unwrap();
+
+    canvas.set_render_target(RenderTarget::Image(image));
+    canvas.clear_rect(0, 0, 20, 20, Color::rgba(0, 0, 0, 0));
+
+    // Only fill the top half of the image.
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 20.0, 10.0);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+
+    canvas.set_render_target(RenderTarget::Screen);
+
+    let mut path = Path::new();
+    path.rect(10.0, 10.0, 20.0, 20.0);
+    canvas.fill_path(&path, &Paint::image(image, 10.0, 10.0, 20.0, 20.0, 0.0, 1.0));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 20, 12), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 20, 27), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn gaussian_blur() {
+    let mut canvas = canvas(40, 40);
+
+    let flags = ImageFlags::FLIP_Y | ImageFlags::PREMULTIPLIED;
+    let source = canvas.create_image_empty(40, 40, PixelFormat::Rgba8, flags).unwrap();
+    let target = canvas.create_image_empty(40, 40, PixelFormat::Rgba8, flags).unwrap();
+
+    canvas.set_render_target(RenderTarget::Image(source));
+    canvas.clear_rect(0, 0, 40, 40, Color::rgba(0, 0, 0, 0));
+    let mut path = Path::new();
+    path.rect(10.0, 10.0, 20.0, 20.0);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(255, 255, 255)));
+
+    canvas.filter_image(target, ImageFilter::GaussianBlur { sigma: 3.0 }, source);
+
+    canvas.set_render_target(RenderTarget::Screen);
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 40.0, 40.0);
+    canvas.fill_path(&path, &Paint::image(target, 0.0, 0.0, 40.0, 40.0, 0.0, 1.0));
+    canvas.flush();
+
+    let center = pixel(&mut canvas, 20, 20);
+    let edge = pixel(&mut canvas, 10, 20);
+    let outside = pixel(&mut canvas, 7, 20);
+    let far = pixel(&mut canvas, 1, 20);
+
+    assert!(center.a > 250);
+    assert!((100..=160).contains(&edge.a));
+    assert!(outside.a > 0 && outside.a < edge.a);
+    assert_eq!(far.a, 0);
+}
+
+#[test]
+fn fill_text() {
+    let mut canvas = canvas(100, 40);
+    let font = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+
+    let paint = Paint::color(Color::rgb(0, 0, 0))
+        .with_font(&[font])
+        .with_font_size(30.0);
+    canvas.fill_text(5.0, 30.0, "Hello", &paint).unwrap();
+    canvas.flush();
+
+    let image = canvas.screenshot().unwrap();
+    assert!(image.pixels().any(|pixel| pixel.a == 255));
+    assert!(image.pixels().take(100 * 5).all(|pixel| pixel.a == 0));
+}
+
+#[test]
+fn fill_paragraph() {
+    let mut canvas = canvas(100, 80);
+    let font = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+
+    let paint = Paint::color(Color::rgb(0, 0, 0))
+        .with_font(&[font])
+        .with_font_size(20.0);
+    let paragraph = canvas
+        .layout_paragraph("Hello\nWorld", &paint, 100.0, LineHeight::Absolute(25.0))
+        .unwrap();
+    canvas.fill_paragraph(5.0, 0.0, &paragraph, &paint).unwrap();
+    canvas.flush();
+
+    let image = canvas.screenshot().unwrap();
+    let has_ink = |rows: std::ops::Range<usize>| {
+        image
+            .rows()
+            .skip(rows.start)
+            .take(rows.len())
+            .flatten()
+            .any(|pixel| pixel.a == 255)
+    };
+    assert!(has_ink(0..25));
+    assert!(has_ink(25..50));
+    assert!(!has_ink(50..80));
+}
+
+#[test]
+fn fill_attributed_text() {
+    let mut canvas = canvas(100, 40);
+    let font = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+
+    let paint = Paint::color(Color::rgb(255, 0, 0))
+        .with_font(&[font])
+        .with_font_size(30.0);
+    let text = AttributedText::new("III", &paint).with_str("III", &paint.clone().with_color(Color::rgb(0, 0, 255)));
+    canvas.fill_attributed_text(5.0, 30.0, &text).unwrap();
+    canvas.flush();
+
+    let image = canvas.screenshot().unwrap();
+    assert!(image
+        .pixels()
+        .any(|pixel| pixel.a == 255 && pixel.r == 255 && pixel.b == 0));
+    assert!(image
+        .pixels()
+        .any(|pixel| pixel.a == 255 && pixel.b == 255 && pixel.r == 0));
+}
+
+#[test]
+fn text_decoration() {
+    let mut canvas = canvas(120, 40);
+    let font = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+
+    let paint = Paint::color(Color::rgb(0, 0, 0))
+        .with_font(&[font])
+        .with_font_size(30.0)
+        .with_text_decoration(TextDecoration::UNDERLINE);
+    let font_metrics = canvas.measure_font(&paint).unwrap();
+    let glyphs = canvas.measure_text(5.0, 20.0, "xgx", &paint).unwrap().glyphs;
+    let row = (20.0 - font_metrics.underline_position() + font_metrics.underline_thickness() / 2.0) as usize;
+    let center = |i: usize| (glyphs[i].x + glyphs[i].width / 2.0) as usize;
+
+    // The underline is left out below the descender of the "g".
+    canvas.fill_text(5.0, 20.0, "xgx", &paint).unwrap();
+    canvas.flush();
+    assert!(pixel(&mut canvas, center(0), row).a > 100);
+    assert_eq!(pixel(&mut canvas, center(1), row).a, 0);
+    assert!(pixel(&mut canvas, center(2), row).a > 100);
+
+    canvas.clear_rect(0, 0, 120, 40, Color::rgba(0, 0, 0, 0));
+    let paint = paint.with_text_decoration_skip_ink(false);
+    canvas.fill_text(5.0, 20.0, "xgx", &paint).unwrap();
+    canvas.flush();
+    assert!(pixel(&mut canvas, center(1), row).a > 100);
+}
+
+#[test]
+fn dashed_stroke() {
+    let mut canvas = canvas(100, 20);
+
+    let mut path = Path::new();
+    path.move_to(0.0, 10.0);
+    path.line_to(100.0, 10.0);
+
+    let paint = Paint::color(Color::rgb(0, 0, 0))
+        .with_line_width(4.0)
+        .with_dash_array(&[10.0, 10.0]);
+    canvas.stroke_path(&path, &paint);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 5, 10).a, 255);
+    assert_eq!(pixel(&mut canvas, 15, 10).a, 0);
+    assert_eq!(pixel(&mut canvas, 25, 10).a, 255);
+}
+
+#[test]
+fn clip_path() {
+    let mut canvas = canvas(100, 100);
+
+    let mut clip = Path::new();
+    clip.circle(50.0, 50.0, 20.0);
+
+    let mut rect = Path::new();
+    rect.rect(0.0, 0.0, 100.0, 100.0);
+
+    canvas.save();
+    canvas.clip_path(&clip, FillRule::NonZero);
+    canvas.fill_path(&rect, &Paint::color(Color::rgb(255, 0, 0)));
+    canvas.restore();
+
+    let mut line = Path::new();
+    line.move_to(0.0, 90.0);
+    line.line_to(100.0, 90.0);
+    canvas.stroke_path(&line, &Paint::color(Color::rgb(0, 0, 255)).with_line_width(4.0));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 50, 35), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 50, 25), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0));
+
+    // The clip is undone by restore().
+    assert_eq!(pixel(&mut canvas, 5, 90), RGBA8::new(0, 0, 255, 255));
+}
+
+#[test]
+fn clip_paths_intersect() {
+    let mut canvas = canvas(100, 100);
+
+    let mut left = Path::new();
+    left.rect(0.0, 0.0, 60.0, 100.0);
+    let mut right = Path::new();
+    right.rect(40.0, 0.0, 60.0, 100.0);
+
+    canvas.clip_path(&left, FillRule::NonZero);
+    canvas.fill_path(&left, &Paint::color(Color::rgb(0, 255, 0)));
+
+    canvas.save();
+    canvas.clip_path(&right, FillRule::NonZero);
+    canvas.clear_rect(0, 0, 100, 100, Color::rgba(0, 0, 0, 0));
+
+    // Concave fills and stencil strokes are clipped as well.
+    canvas.fill_path(&star(), &Paint::color(Color::rgb(255, 0, 0)));
+    canvas.restore();
+
+    let mut line = Path::new();
+    line.move_to(0.0, 5.0);
+    line.line_to(100.0, 5.0);
+    let paint = Paint::color(Color::rgb(0, 0, 255))
+        .with_line_width(4.0)
+        .with_stencil_strokes(true);
+    canvas.stroke_path(&line, &paint);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(255, 0, 0, 255));
+    assert_eq!(pixel(&mut canvas, 20, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 80, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 20, 5), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 80, 5), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn clip_path_even_odd() {
+    let mut canvas = canvas(100, 100);
+
+    let mut rect = Path::new();
+    rect.rect(0.0, 0.0, 100.0, 100.0);
+
+    canvas.clip_path(&star(), FillRule::EvenOdd);
+    canvas.fill_path(&rect, &Paint::color(Color::rgb(0, 255, 0)));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 50, 50), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 50, 15), RGBA8::new(0, 255, 0, 255));
+    assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn clip_path_text_and_images() {
+    let mut canvas = canvas(100, 40);
+    let font = canvas.add_font("examples/assets/Roboto-Regular.ttf").unwrap();
+
+    let image = canvas
+        .create_image_empty(100, 40, PixelFormat::Rgba8, ImageFlags::empty())
+        .unwrap();
+    canvas.set_render_target(RenderTarget::Image(image));
+    canvas.clear_rect(0, 0, 100, 40, Color::rgb(255, 0, 0));
+    canvas.set_render_target(RenderTarget::Screen);
+
+    let mut clip = Path::new();
+    clip.rect(0.0, 0.0, 50.0, 40.0);
+    canvas.clip_path(&clip, FillRule::NonZero);
+
+    let mut rect = Path::new();
+    rect.rect(0.0, 0.0, 100.0, 40.0);
+    canvas.fill_path(&rect, &Paint::image(image, 0.0, 0.0, 100.0, 40.0, 0.0, 1.0));
+
+    let paint = Paint::color(Color::rgb(0, 0, 255))
+        .with_font(&[font])
+        .with_font_size(30.0);
+    canvas.fill_text(5.0, 30.0, "MMMMMMMM", &paint).unwrap();
+    canvas.flush();
+
+    let screenshot = canvas.screenshot().unwrap();
+    let (inside, outside): (Vec<_>, Vec<_>) = screenshot
+        .buf()
+        .iter()
+        .enumerate()
+        .partition(|(index, _)| index % 100 < 50);
+
+    assert!(inside.iter().any(|(_, pixel)| pixel.b == 255));
+    assert!(inside.iter().any(|(_, pixel)| pixel.r == 255));
+    assert!(outside.iter().all(|(_, pixel)| pixel.a == 0));
+}
+
+#[test]
+fn layer_group_opacity() {
+    let mut canvas = canvas(100, 100);
+
+    for frame in 0..2 {
+        canvas.clear_rect(0, 0, 100, 100, Color::rgba(0, 0, 0, 0));
+        canvas.save_layer(None, 0.5, CompositeOperation::SourceOver);
+
+        let mut path = Path::new();
+        path.rect(10.0, 10.0, 50.0, 50.0);
+        canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+
+        let mut path = Path::new();
+        path.rect(40.0, 40.0, 50.0, 50.0);
+        canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+
+        canvas.restore();
+        canvas.flush();
+
+        // The overlapping area is only blended once.
+        let overlap = pixel(&mut canvas, 50, 50);
+        assert!((127..=128).contains(&overlap.a), "frame {frame}");
+        assert_eq!(overlap, pixel(&mut canvas, 20, 20), "frame {frame}");
+        assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0), "frame {frame}");
+    }
+}
+
+#[test]
+fn flush_composites_open_layers() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.rect(10.0, 10.0, 50.0, 50.0);
+
+    canvas.save_layer(None, 0.5, CompositeOperation::SourceOver);
+    canvas.save_layer(None, 1.0, CompositeOperation::SourceOver);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+    canvas.flush();
+
+    assert!((127..=128).contains(&pixel(&mut canvas, 20, 20).a));
+
+    // The states of the layers were restored as well.
+    canvas.fill_path(&path, &Paint::color(Color::rgb(0, 0, 255)));
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 20, 20), RGBA8::new(0, 0, 255, 255));
+}
+
+#[test]
+fn layer_bounds_and_nesting() {
+    let mut canvas = canvas(100, 100);
+
+    let mut rect = Path::new();
+    rect.rect(0.0, 0.0, 100.0, 100.0);
+
+    canvas.translate(10.0, 10.0);
+    let bounds = Bounds {
+        minx: 0.0,
+        miny: 0.0,
+        maxx: 40.0,
+        maxy: 40.0,
+    };
+    canvas.save_layer(Some(bounds), 1.0, CompositeOperation::SourceOver);
+    canvas.fill_path(&rect, &Paint::color(Color::rgb(0, 0, 255)));
+
+    canvas.save_layer(None, 1.0, CompositeOperation::DestinationOut);
+    let mut hole = Path::new();
+    hole.rect(10.0, 10.0, 10.0, 10.0);
+    canvas.fill_path(&hole, &Paint::color(Color::rgb(0, 0, 0)));
+    canvas.restore();
+
+    canvas.restore();
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 12, 12), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 49, 49), RGBA8::new(0, 0, 255, 255));
+    assert_eq!(pixel(&mut canvas, 25, 25), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 5, 5), RGBA8::new(0, 0, 0, 0));
+    assert_eq!(pixel(&mut canvas, 60, 60), RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn blend_modes() {
+    let mut canvas = canvas(100, 100);
+
+    let mut backdrop = Path::new();
+    backdrop.rect(0.0, 0.0, 100.0, 50.0);
+    canvas.fill_path(&backdrop, &Paint::color(Color::rgb(255, 128, 0)));
+
+    let mut left = Path::new();
+    left.rect(0.0, 0.0, 50.0, 100.0);
+    let mut right = Path::new();
+    right.rect(50.0, 0.0, 50.0, 100.0);
+
+    let source = Paint::color(Color::rgb(128, 255, 255));
+    canvas.global_blend_mode(BlendMode::Multiply);
+    canvas.fill_path(&left, &source);
+    canvas.global_blend_mode(BlendMode::Difference);
+    canvas.fill_path(&right, &source);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 25, 25), RGBA8::new(128, 128, 0, 255));
+    assert_eq!(pixel(&mut canvas, 75, 25), RGBA8::new(127, 127, 255, 255));
+
+    // Without a backdrop, the source is drawn as is.
+    assert_eq!(pixel(&mut canvas, 25, 75), RGBA8::new(128, 255, 255, 255));
+    assert_eq!(pixel(&mut canvas, 75, 75), RGBA8::new(128, 255, 255, 255));
+}
+
+#[test]
+fn blend_mode_non_separable_and_translucent() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 100.0);
+    canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+
+    let mut left = Path::new();
+    left.rect(0.0, 0.0, 50.0, 100.0);
+    let mut right = Path::new();
+    right.rect(50.0, 0.0, 50.0, 100.0);
+
+    // Keeps the hue and saturation of the red backdrop with the luminosity of mid gray.
+    canvas.global_blend_mode(BlendMode::Luminosity);
+    canvas.fill_path(&left, &Paint::color(Color::rgb(128, 128, 128)));
+
+    // The blended color is mixed with the backdrop by the source alpha.
+    canvas.global_blend_mode(BlendMode::Screen);
+    canvas.fill_path(&right, &Paint::color(Color::rgba(0, 0, 255, 128)));
+    canvas.flush();
+
+    let blended = pixel(&mut canvas, 25, 50);
+    assert_eq!(blended.r, 255);
+    assert!((72..=74).contains(&blended.g) && blended.g == blended.b);
+    assert_eq!(pixel(&mut canvas, 75, 50), RGBA8::new(255, 0, 128, 255));
+}
+
+#[test]
+fn conic_gradient() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 100.0);
+
+    let paint = Paint::conic_gradient_stops(
+        50.0,
+        50.0,
+        0.0,
+        [
+            (0.0, Color::rgb(255, 0, 0)),
+            (0.5, Color::rgb(0, 255, 0)),
+            (1.0, Color::rgb(0, 0, 255)),
+        ],
+    );
+    canvas.fill_path(&path, &paint);
+    canvas.flush();
+
+    // The sweep starts along the positive x axis and runs clockwise.
+    let right = pixel(&mut canvas, 90, 51);
+    assert!(right.r > 240 && right.b < 10);
+    let left = pixel(&mut canvas, 10, 50);
+    assert!(left.g > 240 && left.r < 10 && left.b < 10);
+    let up = pixel(&mut canvas, 50, 10);
+    assert!(up.r < 10 && (120..=135).contains(&up.g) && (120..=135).contains(&up.b));
+
+    // Two color conic gradients rotate with the start angle.
+    let paint = Paint::conic_gradient(
+        50.0,
+        50.0,
+        std::f32::consts::PI,
+        Color::rgb(0, 0, 0),
+        Color::rgb(255, 255, 255),
+    );
+    canvas.fill_path(&path, &paint);
+    canvas.flush();
+
+    let right = pixel(&mut canvas, 90, 50);
+    assert!((120..=135).contains(&right.r));
+    let down = pixel(&mut canvas, 50, 90);
+    assert!((185..=200).contains(&down.r));
+}
+
+#[test]
+fn gradient_spread() {
+    let mut canvas = canvas(100, 30);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 10.0);
+    let black_to_white = |spread| {
+        Paint::linear_gradient(0.0, 0.0, 20.0, 0.0, Color::rgb(0, 0, 0), Color::rgb(255, 255, 255))
+            .with_gradient_spread(spread)
+    };
+    canvas.fill_path(&path, &black_to_white(GradientSpread::Pad));
+
+    canvas.translate(0.0, 10.0);
+    canvas.fill_path(&path, &black_to_white(GradientSpread::Repeat));
+
+    canvas.translate(0.0, 10.0);
+    let stops = [
+        (0.0, Color::rgb(0, 0, 0)),
+        (0.5, Color::rgb(0, 0, 255)),
+        (1.0, Color::rgb(255, 255, 255)),
+    ];
+    let paint = Paint::linear_gradient_stops(0.0, 0.0, 20.0, 0.0, stops).with_gradient_spread(GradientSpread::Reflect);
+    assert_eq!(paint.gradient_spread(), GradientSpread::Reflect);
+    canvas.fill_path(&path, &paint);
+    canvas.flush();
+
+    let r = |canvas: &mut Canvas<Software>, x, y| pixel(canvas, x, y).r as i32;
+
+    // Pad keeps the end color.
+    assert!(r(&mut canvas, 45, 5) > 250);
+    // Repeat starts over every 20 pixels.
+    assert!((r(&mut canvas, 45, 15) - r(&mut canvas, 5, 15)).abs() <= 2);
+    assert!(r(&mut canvas, 45, 15) < 80);
+    // Reflect runs backwards on every other repetition.
+    assert!((r(&mut canvas, 35, 25) - r(&mut canvas, 5, 25)).abs() <= 2);
+    assert!((pixel(&mut canvas, 29, 25).b as i32 - 255).abs() <= 4);
+    assert!(r(&mut canvas, 21, 25) > 200);
+}
+
+#[test]
+fn gradient_color_space() {
+    let mut canvas = canvas(100, 40);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 10.0);
+    let red_to_green = |color_space| {
+        Paint::linear_gradient(0.0, 0.0, 100.0, 0.0, Color::rgb(255, 0, 0), Color::rgb(0, 255, 0))
+            .with_gradient_color_space(color_space)
+    };
+    for color_space in [
+        GradientColorSpace::Srgb,
+        GradientColorSpace::LinearSrgb,
+        GradientColorSpace::Oklab,
+    ] {
+        canvas.fill_path(&path, &red_to_green(color_space));
+        canvas.translate(0.0, 10.0);
+    }
+
+    let stops = [(0.0, Color::rgb(255, 0, 0)), (1.0, Color::rgb(0, 255, 0))];
+    let paint = Paint::linear_gradient_stops(0.0, 0.0, 100.0, 0.0, stops)
+        .with_gradient_color_space(GradientColorSpace::LinearSrgb);
+    assert_eq!(paint.gradient_color_space(), GradientColorSpace::LinearSrgb);
+    canvas.fill_path(&path, &paint);
+    canvas.flush();
+
+    let srgb = pixel(&mut canvas, 50, 5);
+    let linear = pixel(&mut canvas, 50, 15);
+    let oklab = pixel(&mut canvas, 50, 25);
+    let linear_stops = pixel(&mut canvas, 50, 35);
+
+    // Interpolating gamma encoded components gives a dark midpoint.
+    assert!(srgb.r < 135 && srgb.g < 135);
+    // Linear light keeps the midpoint bright.
+    assert!(linear.r > 180 && linear.g > 180);
+    assert!(linear_stops.r > 180 && linear_stops.g > 180);
+    assert!(oklab.r > 150 && oklab.g > 150);
+    // The end points are unaffected.
+    assert!(pixel(&mut canvas, 0, 25).r > 250);
+    assert!(pixel(&mut canvas, 99, 25).g > 250);
+}
+
+#[test]
+fn gradient_color_space_with_transparent_color() {
+    let mut canvas = canvas(100, 30);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 10.0);
+
+    // A transparent red keeps its hue instead of fading from black, with two stops like with more stops.
+    let transparent_to_blue = [(0.0, Color::rgba(255, 0, 0, 0)), (1.0, Color::rgb(0, 0, 255))];
+    let paints = [
+        Paint::linear_gradient(0.0, 0.0, 100.0, 0.0, transparent_to_blue[0].1, transparent_to_blue[1].1),
+        Paint::linear_gradient_stops(0.0, 0.0, 100.0, 0.0, transparent_to_blue),
+    ];
+
+    for color_space in [GradientColorSpace::LinearSrgb, GradientColorSpace::Oklab] {
+        for paint in &paints {
+            canvas.clear_rect(0, 0, 100, 30, Color::rgba(0, 0, 0, 0));
+            canvas.fill_path(&path, &paint.clone().with_gradient_color_space(color_space));
+            canvas.flush();
+
+            let mid = pixel(&mut canvas, 50, 5);
+            assert!((120..=135).contains(&mid.a), "{color_space:?}: {mid:?}");
+            assert!(mid.r > 40 && mid.b > 40, "{color_space:?}: {mid:?}");
+        }
+    }
+}
+
+#[test]
+fn focal_radial_gradient() {
+    let mut canvas = canvas(100, 100);
+
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, 100.0, 100.0);
+
+    let black_to_white = Paint::focal_radial_gradient(
+        30.0,
+        50.0,
+        0.0,
+        50.0,
+        50.0,
+        40.0,
+        Color::rgb(0, 0, 0),
+        Color::rgb(255, 255, 255),
+    );
+    canvas.fill_path(&path, &black_to_white);
+    canvas.flush();
+
+    // Points are colored by the circle through them, so the gradient is compressed towards the near edge.
+    let r = |canvas: &mut Canvas<Software>, x, y| pixel(canvas, x, y).r as i32;
+    assert!(r(&mut canvas, 30, 50) < 10);
+    assert!((r(&mut canvas, 20, 50) - 121).abs() <= 6);
+    assert!((r(&mut canvas, 70, 50) - 172).abs() <= 6);
+    assert_eq!(r(&mut canvas, 95, 5), 255);
+
+    // With the focal point outside of the end circle, only the cone between the two circles is painted.
+    canvas.clear_rect(0, 0, 100, 100, Color::rgbaf(0.0, 0.0, 0.0, 0.0));
+    let cone = Paint::focal_radial_gradient_stops(
+        10.0,
+        50.0,
+        0.0,
+        70.0,
+        50.0,
+        20.0,
+        [
+            (0.0, Color::rgb(255, 0, 0)),
+            (0.5, Color::rgb(0, 255, 0)),
+            (1.0, Color::rgb(0, 0, 255)),
+        ],
+    );
+    canvas.fill_path(&path, &cone);
+    canvas.flush();
+
+    assert_eq!(pixel(&mut canvas, 5, 50).a, 0);
+    assert_eq!(pixel(&mut canvas, 40, 10).a, 0);
+    // The larger of the two circles through the point wins.
+    let inside = pixel(&mut canvas, 40, 50);
+    assert!(inside.a == 255 && inside.r == 0 && (115..=130).contains(&inside.g) && inside.b > inside.g);
+}